@@ -0,0 +1,132 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use diy_redis::config::ServerConfig;
+use diy_redis::connection::Connection;
+use diy_redis::frame::Frame;
+use diy_redis::server;
+use std::future;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+const TASKS: usize = 8;
+/// Few enough keys that the tasks keep landing on the same shards.
+const KEYS: usize = 4;
+
+/// A read-then-write done in one command, against the two commands a client
+/// without it would send. Each is a function of the key it works on.
+struct Op {
+    name: &'static str,
+    atomic: fn(&str) -> Vec<Frame>,
+    naive: fn(&str) -> Vec<Frame>,
+}
+
+const OPS: &[Op] = &[
+    Op {
+        name: "getset",
+        atomic: |key| vec![command(&["SET", key, "value", "GET"])],
+        naive: |key| vec![command(&["GET", key]), command(&["SET", key, "value"])],
+    },
+    Op {
+        name: "getex",
+        atomic: |key| vec![command(&["GETEX", key, "EX", "100"])],
+        naive: |key| vec![command(&["GET", key]), command(&["EXPIRE", key, "100"])],
+    },
+    Op {
+        name: "cas",
+        atomic: |key| vec![command(&["CAS", key, "value", "value"])],
+        naive: |key| vec![command(&["GET", key]), command(&["SET", key, "value"])],
+    },
+];
+
+fn command(args: &[&str]) -> Frame {
+    Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    )
+}
+
+/// Each op through a real server, from `TASKS` connections at once, so the
+/// throughput reads as logical operations per second whichever way they are
+/// sent. The naive variant waits for each reply before sending the next
+/// command, as a client deciding what to write from what it read must.
+fn bench_atomic_ops(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let addr = runtime.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(server::run(
+            listener,
+            ServerConfig::default(),
+            future::pending::<()>(),
+        ));
+        addr
+    });
+    let keys: Vec<String> = (0..KEYS).map(|key| format!("key:{key}")).collect();
+    let mut connections = runtime.block_on(async {
+        let mut connections = Vec::with_capacity(TASKS);
+        for _ in 0..TASKS {
+            connections.push(Connection::new(TcpStream::connect(addr).await.unwrap()));
+        }
+        for key in &keys {
+            connections[0]
+                .write_frame(&command(&["SET", key, "value"]))
+                .await
+                .unwrap();
+            connections[0].read_frame().await.unwrap();
+        }
+        connections
+    });
+
+    let mut group = c.benchmark_group("atomic_ops");
+    group.throughput(Throughput::Elements(TASKS as u64));
+    for op in OPS {
+        for (variant, frames) in [("atomic", op.atomic), ("naive", op.naive)] {
+            let requests: Vec<Vec<Frame>> = keys.iter().map(|key| frames(key)).collect();
+            group.bench_function(format!("{}/{variant}", op.name), |b| {
+                b.iter_custom(|iters| hammer(&runtime, &mut connections, &requests, iters))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Runs `iters` operations on every connection concurrently, cycling through
+/// the keys' `requests`, and returns how long it took for all of them.
+fn hammer(
+    runtime: &Runtime,
+    connections: &mut Vec<Connection>,
+    requests: &[Vec<Frame>],
+    iters: u64,
+) -> Duration {
+    runtime.block_on(async {
+        let start = Instant::now();
+        let tasks: Vec<_> = connections
+            .drain(..)
+            .enumerate()
+            .map(|(task, mut connection)| {
+                let requests = requests.to_vec();
+                tokio::spawn(async move {
+                    for i in 0..iters as usize {
+                        for frame in &requests[(task + i) % requests.len()] {
+                            connection.write_frame(frame).await.unwrap();
+                            connection.read_frame().await.unwrap().unwrap();
+                        }
+                    }
+                    connection
+                })
+            })
+            .collect();
+        for task in tasks {
+            connections.push(task.await.unwrap());
+        }
+        start.elapsed()
+    })
+}
+
+criterion_group!(benches, bench_atomic_ops);
+criterion_main!(benches);
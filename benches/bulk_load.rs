@@ -0,0 +1,53 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use diy_redis::db::{ShardedDb, Value};
+
+const KEYS: usize = 100_000;
+
+fn entries() -> Vec<(Bytes, Value)> {
+    (0..KEYS)
+        .map(|key| (format!("key:{key}").into(), Value::String("value".into())))
+        .collect()
+}
+
+/// Restoring through `load`, one lock per shard, against inserting the same
+/// keys one at a time, one lock each.
+fn bench_bulk_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_load");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    group.sample_size(10);
+
+    group.bench_function("batch", |b| {
+        b.iter_batched(
+            entries,
+            |entries| {
+                let mut db = ShardedDb::new();
+                db.load(entries, usize::MAX).unwrap();
+                db
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("per_key", |b| {
+        b.iter_batched(
+            entries,
+            |entries| {
+                let mut db = ShardedDb::new();
+                for (key, value) in entries {
+                    let Value::String(value) = value else {
+                        unreachable!("only strings are generated");
+                    };
+                    db.insert(&key, value);
+                }
+                db
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_bulk_load);
+criterion_main!(benches);
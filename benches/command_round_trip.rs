@@ -0,0 +1,75 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use diy_redis::cmd::Command;
+use diy_redis::connection::Connection;
+use diy_redis::db::ShardedDb;
+use diy_redis::frame::{Frame, MAX_BULK_LEN};
+use std::time::{Duration, Instant};
+use tokio::io::DuplexStream;
+use tokio::runtime::Runtime;
+
+/// The server side of the pipe: parses each frame into a command, runs it
+/// against `db` and writes the reply back, as a connection handler does.
+async fn serve(mut connection: Connection<DuplexStream>, mut db: ShardedDb) {
+    while let Ok(Some(frame)) = connection.read_frame().await {
+        let response = match Command::from_frame(frame) {
+            Ok(Command::Get(cmd)) => cmd.apply(&db),
+            Ok(Command::Set(cmd)) => cmd.apply(&mut db, MAX_BULK_LEN, None, |_| ()),
+            Ok(command) => Frame::Error(format!("unexpected {}", command.get_name())),
+            Err(err) => Frame::Error(format!("ERR {err}")),
+        };
+        if connection.write_frame(&response).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn command(args: &[&'static str]) -> Frame {
+    Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::from_static(arg.as_bytes())))
+            .collect(),
+    )
+}
+
+fn bench_command_round_trip(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    runtime.spawn(serve(Connection::new(server), ShardedDb::new()));
+    let mut client = Connection::new(client);
+
+    let set = command(&["SET", "key", "value"]);
+    let get = command(&["GET", "key"]);
+
+    let mut group = c.benchmark_group("command_round_trip");
+    // a SET and a GET per iteration, so throughput reads as commands per second
+    group.throughput(Throughput::Elements(2));
+    group.bench_function("set_get", |b| {
+        b.iter_custom(|iters| round_trips(&runtime, &mut client, &set, &get, iters))
+    });
+    group.finish();
+}
+
+fn round_trips(
+    runtime: &Runtime,
+    client: &mut Connection<DuplexStream>,
+    set: &Frame,
+    get: &Frame,
+    iters: u64,
+) -> Duration {
+    runtime.block_on(async {
+        let start = Instant::now();
+        for _ in 0..iters {
+            client.write_frame(set).await.unwrap();
+            client.read_frame().await.unwrap().unwrap();
+            client.write_frame(get).await.unwrap();
+            client.read_frame().await.unwrap().unwrap();
+        }
+        start.elapsed()
+    })
+}
+
+criterion_group!(benches, bench_command_round_trip);
+criterion_main!(benches);
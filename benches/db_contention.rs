@@ -0,0 +1,158 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use diy_redis::db::ShardedDb;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const KEYS: usize = 10_000;
+const THREADS: usize = 8;
+const OPS_PER_THREAD: usize = 10_000;
+/// One in this many operations is a write, the rest reads.
+const WRITE_EVERY: usize = 10;
+
+/// A few hot keys take most of the traffic, as with real caches. Sampling
+/// walks a precomputed CDF, so it costs a binary search per key.
+struct Zipf {
+    cdf: Vec<f64>,
+}
+
+impl Zipf {
+    fn new(n: usize, exponent: f64) -> Self {
+        let weights: Vec<f64> = (1..=n)
+            .map(|rank| 1.0 / (rank as f64).powf(exponent))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut cumulative = 0.0;
+        let cdf = weights
+            .iter()
+            .map(|weight| {
+                cumulative += weight / total;
+                cumulative
+            })
+            .collect();
+        Self { cdf }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> usize {
+        let point: f64 = rng.gen();
+        self.cdf
+            .partition_point(|cumulative| *cumulative < point)
+            .min(self.cdf.len() - 1)
+    }
+}
+
+/// The store operations being compared, so every backend runs the same load.
+/// Each thread works on its own clone of a shared handle.
+trait Store: Clone + Send {
+    fn get(&self, key: &str) -> Option<Bytes>;
+    fn insert(&mut self, key: &str, value: Bytes);
+}
+
+impl Store for ShardedDb {
+    fn get(&self, key: &str) -> Option<Bytes> {
+        ShardedDb::get(self, key).unwrap()
+    }
+
+    fn insert(&mut self, key: &str, value: Bytes) {
+        ShardedDb::insert(self, key, value);
+    }
+}
+
+/// The same layout as `ShardedDb` with reader/writer locks instead of mutexes.
+#[derive(Clone)]
+struct RwSharded {
+    shards: Arc<Vec<RwLock<HashMap<String, Bytes>>>>,
+}
+
+impl RwSharded {
+    fn new(num_shards: usize) -> Self {
+        Self {
+            shards: Arc::new(
+                (0..num_shards)
+                    .map(|_| RwLock::new(HashMap::new()))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn shard(&self, key: &str) -> &RwLock<HashMap<String, Bytes>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[(hasher.finish() % self.shards.len() as u64) as usize]
+    }
+}
+
+impl Store for RwSharded {
+    fn get(&self, key: &str) -> Option<Bytes> {
+        self.shard(key).read().unwrap().get(key).cloned()
+    }
+
+    fn insert(&mut self, key: &str, value: Bytes) {
+        self.shard(key)
+            .write()
+            .unwrap()
+            .insert(key.to_string(), value);
+    }
+}
+
+/// Runs `THREADS` threads of Zipf-distributed gets and inserts against
+/// `store`, returning the wall time of the slowest.
+fn hammer(store: &impl Store, keys: &[String], zipf: &Zipf) -> Duration {
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let mut store = store.clone();
+            scope.spawn(move || {
+                let mut rng = StdRng::seed_from_u64(thread as u64);
+                let value = Bytes::from_static(b"value");
+                for op in 0..OPS_PER_THREAD {
+                    let key = &keys[zipf.sample(&mut rng)];
+                    if op % WRITE_EVERY == 0 {
+                        store.insert(key, value.clone());
+                    } else {
+                        std::hint::black_box(store.get(key));
+                    }
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn bench_contention(c: &mut Criterion) {
+    let keys: Vec<String> = (0..KEYS).map(|key| format!("key:{key}")).collect();
+    let zipf = Zipf::new(KEYS, 1.0);
+
+    let mut group = c.benchmark_group("db_contention");
+    group.throughput(Throughput::Elements((THREADS * OPS_PER_THREAD) as u64));
+
+    for shards in [1, 8, 64] {
+        let mut db = ShardedDb::new_sized(shards);
+        let mut rw = RwSharded::new(shards);
+        for key in &keys {
+            db.insert(key, Bytes::from_static(b"value"));
+            rw.insert(key, Bytes::from_static(b"value"));
+        }
+
+        group.bench_with_input(BenchmarkId::new("mutex", shards), &db, |b, db| {
+            b.iter_custom(|iters| (0..iters).map(|_| hammer(db, &keys, &zipf)).sum())
+        });
+        group.bench_with_input(BenchmarkId::new("rwlock", shards), &rw, |b, rw| {
+            b.iter_custom(|iters| (0..iters).map(|_| hammer(rw, &keys, &zipf)).sum())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_contention
+}
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use diy_redis::frame::Frame;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations so the bench can report how many an encode makes.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn bench_encode_integer_array(c: &mut Criterion) {
+    let frame = Frame::Array((0..10_000).map(|num| Frame::Integer(num * 7919)).collect());
+    let mut dst = Vec::with_capacity(frame.encoded_len());
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    frame.encode_into(&mut dst);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!("encode_integer_array: {allocations} allocations per encode");
+
+    c.bench_function("encode_integer_array", |b| {
+        b.iter(|| {
+            dst.clear();
+            frame.encode_into(&mut dst);
+            black_box(&dst);
+        })
+    });
+}
+
+criterion_group!(benches, bench_encode_integer_array);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use diy_redis::frame::Frame;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations so the bench can report how many a reply makes.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Builds, encodes and drops an OK reply, as the server does for every SET.
+fn reply_ok(dst: &mut Vec<u8>) {
+    dst.clear();
+    let frame = Frame::ok();
+    frame.encode_into(dst);
+}
+
+fn bench_encode_ok_reply(c: &mut Criterion) {
+    let mut dst = Vec::with_capacity(16);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    reply_ok(&mut dst);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!("encode_ok_reply: {allocations} allocations per reply");
+
+    c.bench_function("encode_ok_reply", |b| {
+        b.iter(|| {
+            reply_ok(&mut dst);
+            black_box(&dst);
+        })
+    });
+}
+
+criterion_group!(benches, bench_encode_ok_reply);
+criterion_main!(benches);
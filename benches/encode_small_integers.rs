@@ -0,0 +1,26 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use diy_redis::frame::Frame;
+
+/// Encodes integer replies one at a time, the way counts from DEL or INCR go
+/// out, for values inside the range of shared integers and just above it.
+fn bench_encode_small_integers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_small_integers");
+    for (name, range) in [("shared", -1..=256), ("formatted", 257..=514)] {
+        let frames: Vec<Frame> = range.map(Frame::Integer).collect();
+        let mut dst = Vec::with_capacity(16 * frames.len());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                dst.clear();
+                for frame in &frames {
+                    frame.encode_into(&mut dst);
+                }
+                black_box(&dst);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode_small_integers);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use diy_redis::db::{ShardedDb, DEFAULT_SHARDS};
+use std::time::{Duration, Instant};
+
+const KEYS: usize = 200_000;
+
+/// Tail latency of single inserts while a database fills up to `KEYS`, with
+/// and without each shard's map sized for it up front. Every iteration fills
+/// a fresh database and reports its slowest insert, so criterion's time per
+/// iteration reads as the worst case. Rehashes are too rare to reach p99:
+/// a shard grows only a dozen or so times on the way to `KEYS`.
+fn bench_insert_latency(c: &mut Criterion) {
+    let keys: Vec<String> = (0..KEYS).map(|key| format!("key:{key}")).collect();
+
+    let mut group = c.benchmark_group("insert_latency_max");
+    group.sample_size(10);
+    group.bench_function("growing", |b| {
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| fill_max(ShardedDb::new_seeded(DEFAULT_SHARDS, 0), &keys))
+                .sum()
+        })
+    });
+    group.bench_function("preallocated", |b| {
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| fill_max(ShardedDb::with_capacity(DEFAULT_SHARDS, 0, KEYS), &keys))
+                .sum()
+        })
+    });
+    group.finish();
+}
+
+fn fill_max(mut db: ShardedDb, keys: &[String]) -> Duration {
+    let latencies = keys.iter().map(|key| {
+        let start = Instant::now();
+        db.insert(key, "value".into());
+        start.elapsed()
+    });
+    latencies.into_iter().max().unwrap()
+}
+
+criterion_group!(benches, bench_insert_latency);
+criterion_main!(benches);
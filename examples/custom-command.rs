@@ -0,0 +1,51 @@
+//! Serves the built-in commands plus `HELLO-CUSTOM [name]`, which greets
+//! `name` and remembers it under the `last-greeted` key. Try it with
+//! `redis-cli HELLO-CUSTOM you`.
+
+use bytes::Bytes;
+use diy_redis::cmd::{CommandHandler, HandlerFuture};
+use diy_redis::config::ServerConfig;
+use diy_redis::db::ShardedDb;
+use diy_redis::frame::Frame;
+use diy_redis::server;
+use tokio::signal;
+
+struct HelloCustom;
+
+impl CommandHandler for HelloCustom {
+    fn name(&self) -> &str {
+        "hello-custom"
+    }
+
+    fn execute<'a>(&'a self, args: Vec<Bytes>, db: &'a mut ShardedDb) -> HandlerFuture<'a> {
+        Box::pin(async move {
+            let name = match &args[..] {
+                [] => Bytes::from_static(b"world"),
+                [name] => name.clone(),
+                _ => {
+                    return Frame::Error(
+                        "ERR wrong number of arguments for 'hello-custom' command".to_string(),
+                    )
+                }
+            };
+
+            let greeting = format!("Hello, {}!", String::from_utf8_lossy(&name));
+            db.insert("last-greeted", name);
+            Frame::Bulk(greeting.into())
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let mut config = ServerConfig::default();
+    config.commands.register(HelloCustom);
+    let listener = config
+        .socket
+        .listen("127.0.0.1:6379".parse().unwrap())
+        .unwrap();
+
+    server::run(listener, config, signal::ctrl_c()).await;
+}
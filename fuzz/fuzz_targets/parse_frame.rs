@@ -0,0 +1,23 @@
+#![no_main]
+
+use diy_redis::frame::parse;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+// Drains the input the way the connection does: parse frames until one fails.
+// Every successful parse has to consume input, which is what guarantees the
+// loop terminates, and the cursor must never run past the buffer.
+fuzz_target!(|data: &[u8]| {
+    let mut buff = Cursor::new(data);
+
+    loop {
+        let start = buff.position();
+        match parse(&mut buff) {
+            Ok(_) => {
+                assert!(buff.position() > start, "parse succeeded without consuming input");
+                assert!(buff.position() <= data.len() as u64, "cursor ran past the buffer");
+            }
+            Err(_) => break,
+        }
+    }
+});
@@ -0,0 +1,167 @@
+use crate::connection::{self, Connection};
+use crate::frame::Frame;
+use bytes::Bytes;
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+mod pool;
+
+pub use pool::{Pool, PooledClient};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("connection closed by server")]
+    ConnectionClosed,
+    /// The server replied with an error; the connection is still usable.
+    #[error("{0}")]
+    Server(String),
+    #[error("unexpected response: {0:?}")]
+    UnexpectedFrame(Frame),
+    #[error(transparent)]
+    Connection(#[from] connection::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub struct Client {
+    connection: Connection,
+    broken: bool,
+}
+
+impl Client {
+    pub async fn connect<T: ToSocketAddrs>(addr: T) -> Result<Self> {
+        let socket = TcpStream::connect(addr).await?;
+        Ok(Self {
+            connection: Connection::new(socket),
+            broken: false,
+        })
+    }
+
+    pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>> {
+        match self.request(command(["GET", key])).await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(Error::UnexpectedFrame(frame)),
+        }
+    }
+
+    pub async fn set(&mut self, key: &str, value: Bytes) -> Result<()> {
+        let frame = Frame::Array(vec![bulk("SET"), bulk(key), Frame::Bulk(value)]);
+
+        match self.request(frame).await? {
+            Frame::Simple(content) if content == "OK" => Ok(()),
+            frame => Err(Error::UnexpectedFrame(frame)),
+        }
+    }
+
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            client: self,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Whether an I/O or protocol failure has left the connection unusable.
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// Sends `frame` and reads its reply, turning an error reply into
+    /// `Error::Server`. The connection counts as broken until the reply is in,
+    /// so a request cancelled halfway leaves it marked as such rather than
+    /// with a stray reply waiting to be read as the next one.
+    async fn request(&mut self, frame: Frame) -> Result<Frame> {
+        self.broken = true;
+        let written = self.connection.write_frame(&frame).await;
+        self.check(written)?;
+        let response = self.read_response().await?;
+        self.broken = false;
+        match response {
+            Frame::Error(message) => Err(Error::Server(message)),
+            frame => Ok(frame),
+        }
+    }
+
+    async fn read_response(&mut self) -> Result<Frame> {
+        let frame = self.connection.read_frame().await;
+        self.check(frame)?.ok_or_else(|| {
+            self.broken = true;
+            Error::ConnectionClosed
+        })
+    }
+
+    fn check<T>(&mut self, result: connection::Result<T>) -> Result<T> {
+        result.map_err(|err| {
+            self.broken = true;
+            err.into()
+        })
+    }
+}
+
+/// Commands queued to be sent in one write, with their replies read back in
+/// order once all of them have been sent.
+pub struct Pipeline<'a> {
+    client: &'a mut Client,
+    commands: Vec<Frame>,
+}
+
+impl Pipeline<'_> {
+    pub fn cmd<I, A>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = A>,
+        A: AsRef<[u8]>,
+    {
+        self.commands.push(command(args));
+        self
+    }
+
+    pub fn get(&mut self, key: &str) -> &mut Self {
+        self.cmd(["GET", key])
+    }
+
+    pub fn set(&mut self, key: &str, value: impl AsRef<[u8]>) -> &mut Self {
+        self.cmd([b"SET".as_ref(), key.as_bytes(), value.as_ref()])
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns one reply per queued command, in order. A command the server
+    /// rejects shows up as a `Frame::Error` in its slot rather than failing the
+    /// whole batch, so the remaining replies stay aligned.
+    pub async fn execute(self) -> Result<Vec<Frame>> {
+        self.client.broken = true;
+        let written = self.client.connection.write_frames(&self.commands).await;
+        self.client.check(written)?;
+
+        let mut responses = Vec::with_capacity(self.commands.len());
+        for _ in 0..self.commands.len() {
+            responses.push(self.client.read_response().await?);
+        }
+        self.client.broken = false;
+
+        Ok(responses)
+    }
+}
+
+fn command<I, A>(args: I) -> Frame
+where
+    I: IntoIterator<Item = A>,
+    A: AsRef<[u8]>,
+{
+    Frame::Array(
+        args.into_iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_ref())))
+            .collect(),
+    )
+}
+
+fn bulk(content: &str) -> Frame {
+    Frame::Bulk(Bytes::copy_from_slice(content.as_bytes()))
+}
@@ -0,0 +1,101 @@
+use crate::client::{Client, Result};
+use std::net::SocketAddr;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A bounded set of client connections to one server. Connections are opened
+/// lazily on checkout and handed back to the pool when the guard drops, unless
+/// they broke while checked out.
+#[derive(Clone)]
+pub struct Pool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    addr: SocketAddr,
+    idle: Mutex<Vec<Client>>,
+    permits: Arc<Semaphore>,
+    open: AtomicUsize,
+}
+
+impl Pool {
+    pub fn new(addr: SocketAddr, max_size: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                addr,
+                idle: Mutex::new(Vec::with_capacity(max_size)),
+                permits: Arc::new(Semaphore::new(max_size)),
+                open: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Waits for a free slot, then reuses an idle connection or opens a new one.
+    pub async fn get(&self) -> Result<PooledClient> {
+        let permit = Arc::clone(&self.shared.permits)
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+
+        let idle = self.shared.idle.lock().unwrap().pop();
+        let client = match idle {
+            Some(client) => client,
+            None => {
+                let client = Client::connect(self.shared.addr).await?;
+                self.shared.open.fetch_add(1, Ordering::Relaxed);
+                client
+            }
+        };
+
+        Ok(PooledClient {
+            client: Some(client),
+            shared: Arc::clone(&self.shared),
+            _permit: permit,
+        })
+    }
+
+    /// Number of connections currently open, idle or checked out.
+    pub fn open_connections(&self) -> usize {
+        self.shared.open.load(Ordering::Relaxed)
+    }
+
+    pub fn idle_connections(&self) -> usize {
+        self.shared.idle.lock().unwrap().len()
+    }
+}
+
+pub struct PooledClient {
+    client: Option<Client>,
+    shared: Arc<Shared>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+
+        if client.is_broken() {
+            self.shared.open.fetch_sub(1, Ordering::Relaxed);
+        } else {
+            self.shared.idle.lock().unwrap().push(client);
+        }
+    }
+}
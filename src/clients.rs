@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Server-wide registry of open connections, behind CLIENT LIST and KILL.
+#[derive(Clone, Default)]
+pub struct Clients {
+    inner: Arc<Mutex<HashMap<u64, Info>>>,
+}
+
+struct Info {
+    addr: SocketAddr,
+    name: Option<String>,
+    connected_at: Instant,
+    activity: Arc<Mutex<Activity>>,
+    no_evict: bool,
+    no_touch: bool,
+    pubsub: bool,
+    monitor: bool,
+    kill: Option<oneshot::Sender<()>>,
+}
+
+/// What a connection last did. The connection holds its own handle, so
+/// recording a command doesn't take the registry lock.
+struct Activity {
+    last_active: Instant,
+    last_command: String,
+}
+
+impl Info {
+    /// The `flags` field of CLIENT LIST: `N` when none are set.
+    fn flags(&self) -> String {
+        let mut flags = String::new();
+        if self.no_evict {
+            flags.push('e');
+        }
+        if self.no_touch {
+            flags.push('T');
+        }
+        if self.pubsub {
+            flags.push('P');
+        }
+        if self.monitor {
+            flags.push('O');
+        }
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        flags
+    }
+}
+
+impl Clients {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a connection under an id unique for the lifetime of the process.
+    /// It is removed again when the returned handle is dropped.
+    pub fn register(&self, addr: SocketAddr) -> Registration {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let (kill, killed) = oneshot::channel();
+        let now = Instant::now();
+        let activity = Arc::new(Mutex::new(Activity {
+            last_active: now,
+            last_command: "NULL".to_string(),
+        }));
+        self.inner.lock().unwrap().insert(
+            id,
+            Info {
+                addr,
+                name: None,
+                connected_at: now,
+                activity: activity.clone(),
+                no_evict: false,
+                no_touch: false,
+                pubsub: false,
+                monitor: false,
+                kill: Some(kill),
+            },
+        );
+
+        Registration {
+            id,
+            clients: self.clone(),
+            activity,
+            killed,
+        }
+    }
+
+    /// One line per connection, in the `field=value` format of Redis's
+    /// CLIENT LIST, ordered by id.
+    pub fn list(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut ids: Vec<_> = inner.keys().copied().collect();
+        ids.sort_unstable();
+
+        let now = Instant::now();
+        let mut list = String::new();
+        for id in ids {
+            let info = &inner[&id];
+            let activity = info.activity.lock().unwrap();
+            let _ = writeln!(
+                list,
+                "id={id} addr={} name={} age={} idle={} flags={} cmd={}",
+                info.addr,
+                info.name.as_deref().unwrap_or(""),
+                (now - info.connected_at).as_secs(),
+                (now - activity.last_active).as_secs(),
+                info.flags(),
+                activity.last_command,
+            );
+        }
+        list
+    }
+
+    /// Tells every connection that has sent nothing for longer than `timeout`
+    /// to close, returning how many there were. Subscribers and MONITOR
+    /// connections only listen, so they are left alone, as in Redis.
+    pub fn kill_idle(&self, timeout: Duration) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        inner
+            .values_mut()
+            .filter(|info| !info.pubsub && !info.monitor)
+            .filter(|info| now - info.activity.lock().unwrap().last_active > timeout)
+            .filter_map(|info| info.kill.take())
+            .filter_map(|kill| kill.send(()).ok())
+            .count()
+    }
+
+    /// Tells the connection with `id` to close, returning whether there was
+    /// one.
+    pub fn kill(&self, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(kill) = inner.get_mut(&id).and_then(|info| info.kill.take()) else {
+            return false;
+        };
+        let _ = kill.send(());
+        true
+    }
+}
+
+/// A connection's entry in `Clients`.
+pub struct Registration {
+    id: u64,
+    clients: Clients,
+    activity: Arc<Mutex<Activity>>,
+    killed: oneshot::Receiver<()>,
+}
+
+impl Registration {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.with_info(|info| info.name.clone()).flatten()
+    }
+
+    pub fn set_name(&self, name: Option<String>) {
+        self.with_info(|info| info.name = name);
+    }
+
+    /// CLIENT NO-EVICT. There is no client eviction to exempt the connection
+    /// from, so it only shows in CLIENT LIST.
+    pub fn set_no_evict(&self, on: bool) {
+        self.with_info(|info| info.no_evict = on);
+    }
+
+    /// CLIENT NO-TOUCH, as shown in CLIENT LIST. The connection's own
+    /// `ShardedDb` handle is what stops its reads counting as accesses.
+    pub fn set_no_touch(&self, on: bool) {
+        self.with_info(|info| info.no_touch = on);
+    }
+
+    /// Notes whether the connection is subscribed to anything.
+    pub fn set_pubsub(&self, on: bool) {
+        self.with_info(|info| info.pubsub = on);
+    }
+
+    /// Notes whether the connection is running MONITOR.
+    pub fn set_monitor(&self, on: bool) {
+        self.with_info(|info| info.monitor = on);
+    }
+
+    /// Notes that `command` was just received.
+    pub fn record(&self, command: &str) {
+        let mut activity = self.activity.lock().unwrap();
+        activity.last_active = Instant::now();
+        // reusing the buffer, a command name rarely needs an allocation
+        activity.last_command.clear();
+        activity.last_command.push_str(command);
+    }
+
+    /// Resolves once CLIENT KILL names this connection.
+    pub async fn killed(&mut self) {
+        if (&mut self.killed).await.is_err() {
+            std::future::pending().await
+        }
+    }
+
+    fn with_info<R>(&self, f: impl FnOnce(&mut Info) -> R) -> Option<R> {
+        self.clients.inner.lock().unwrap().get_mut(&self.id).map(f)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.clients.inner.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clients::Clients;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn kill_idle_spares_active_connections() {
+        // Arrange
+        let clients = Clients::new();
+        let mut idle = clients.register("127.0.0.1:5000".parse().unwrap());
+        let active = clients.register("127.0.0.1:5001".parse().unwrap());
+        tokio::time::advance(Duration::from_secs(5)).await;
+        active.record("ping");
+
+        // Act
+        let killed = clients.kill_idle(Duration::from_secs(3));
+        idle.killed().await;
+
+        // Assert
+        assert_eq!(killed, 1);
+        assert_eq!(clients.kill_idle(Duration::from_secs(3)), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn kill_idle_spares_subscribers_and_monitors() {
+        // Arrange
+        let clients = Clients::new();
+        let subscriber = clients.register("127.0.0.1:5000".parse().unwrap());
+        let monitor = clients.register("127.0.0.1:5001".parse().unwrap());
+        subscriber.set_pubsub(true);
+        monitor.set_monitor(true);
+        tokio::time::advance(Duration::from_secs(5)).await;
+
+        // Act
+        let killed = clients.kill_idle(Duration::from_secs(3));
+
+        // Assert
+        assert_eq!(killed, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn list_formats_each_connection() {
+        // Arrange
+        let clients = Clients::new();
+        let first = clients.register("127.0.0.1:5000".parse().unwrap());
+        let second = clients.register("127.0.0.1:5001".parse().unwrap());
+        second.set_name(Some("worker".to_string()));
+        second.set_no_evict(true);
+        second.set_no_touch(true);
+        tokio::time::advance(Duration::from_secs(3)).await;
+        second.record("get");
+
+        // Act
+        let list = clients.list();
+
+        // Assert
+        assert_eq!(
+            list,
+            format!(
+                "id={} addr=127.0.0.1:5000 name= age=3 idle=3 flags=N cmd=NULL\n\
+                 id={} addr=127.0.0.1:5001 name=worker age=3 idle=0 flags=eT cmd=get\n",
+                first.id(),
+                second.id()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn kill_signals_connection_once() {
+        // Arrange
+        let clients = Clients::new();
+        let mut client = clients.register("127.0.0.1:5000".parse().unwrap());
+
+        // Act
+        let killed = clients.kill(client.id());
+        let killed_again = clients.kill(client.id());
+        client.killed().await;
+
+        // Assert
+        assert!(killed);
+        assert!(!killed_again);
+    }
+
+    #[test]
+    fn drop_unregisters() {
+        // Arrange
+        let clients = Clients::new();
+        let client = clients.register("127.0.0.1:5000".parse().unwrap());
+        let id = client.id();
+
+        // Act
+        drop(client);
+
+        // Assert
+        assert!(clients.list().is_empty());
+        assert!(!clients.kill(id));
+    }
+}
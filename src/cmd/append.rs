@@ -0,0 +1,36 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Append {
+    key: Bytes,
+    value: Bytes,
+}
+
+impl Append {
+    pub fn new(key: impl Into<Bytes>, value: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            value,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let value = parse.next_bytes()?;
+        Ok(Self { key, value })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb, max_value_size: usize) -> Frame {
+        match db.append(&self.key, &self.value, max_value_size) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// `CAS key expected new`: swaps in `new` only while the key still holds
+/// `expected`, checked and written under one shard lock. `new` is held to
+/// the same size limit as SET.
+#[derive(Debug)]
+pub struct Cas {
+    key: Bytes,
+    expected: Bytes,
+    new: Bytes,
+}
+
+impl Cas {
+    pub fn new(key: impl Into<Bytes>, expected: Bytes, new: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            expected,
+            new,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let expected = parse.next_bytes()?;
+        let new = parse.next_bytes()?;
+        Ok(Self { key, expected, new })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb, max_value_size: usize) -> Frame {
+        match db.compare_and_swap(&self.key, &self.expected, self.new, max_value_size) {
+            Ok(swapped) => Frame::Integer(swapped as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
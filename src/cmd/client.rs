@@ -0,0 +1,113 @@
+use crate::clients::{Clients, Registration};
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::{help, CommandError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use anyhow::anyhow;
+
+#[derive(Debug)]
+pub enum Client {
+    Id,
+    GetName,
+    /// An empty name clears the current one.
+    SetName(String),
+    List,
+    Kill {
+        id: u64,
+    },
+    NoEvict(bool),
+    NoTouch(bool),
+    Help,
+}
+
+const HELP: &[&str] = &[
+    "CLIENT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "GETNAME",
+    "    Return the name of the current connection.",
+    "ID",
+    "    Return the ID of the current connection.",
+    "KILL ID <client-id>",
+    "    Kill connections by client id.",
+    "LIST",
+    "    Return information about client connections.",
+    "NO-EVICT (ON|OFF)",
+    "    Protect current client connection from eviction.",
+    "NO-TOUCH (ON|OFF)",
+    "    Will not touch LRU/LFU stats when this mode is on.",
+    "SETNAME <name>",
+    "    Assign the name <name> to the current connection.",
+    "HELP",
+    "    Print this help.",
+];
+
+impl Client {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "id" => Ok(Client::Id),
+            "getname" => Ok(Client::GetName),
+            "setname" => {
+                let name = parse.next_string()?;
+                // the name shows up in space-separated listings, so it must be one printable word
+                if !name.bytes().all(|byte| (b'!'..=b'~').contains(&byte)) {
+                    return Err(anyhow!(
+                        "Client names cannot contain spaces, newlines or special characters."
+                    )
+                    .into());
+                }
+                Ok(Client::SetName(name))
+            }
+            "list" => Ok(Client::List),
+            "kill" => {
+                if parse.next_string()?.to_lowercase() != "id" {
+                    return Err(CommandError::Syntax.into());
+                }
+                let id = u64::try_from(parse.next_int()?)
+                    .map_err(|_| anyhow!("client-id should be greater than 0"))?;
+                Ok(Client::Kill { id })
+            }
+            "no-evict" => parse_switch(parse).map(Client::NoEvict),
+            "no-touch" => parse_switch(parse).map(Client::NoTouch),
+            "help" => Ok(Client::Help),
+            _ => Err(CommandError::unknown_subcommand("CLIENT", subcommand).into()),
+        }
+    }
+
+    /// KILL replies with the number of connections closed, which may include
+    /// the caller's own once this reply is written. NO-TOUCH applies to `db`,
+    /// the connection's own handle.
+    pub fn apply(self, client: &Registration, clients: &Clients, db: &mut ShardedDb) -> Frame {
+        match self {
+            Client::Id => Frame::Integer(client.id() as i64),
+            Client::GetName => client
+                .name()
+                .map_or(Frame::Null, |name| Frame::Bulk(name.into())),
+            Client::SetName(name) => {
+                client.set_name(Some(name).filter(|name| !name.is_empty()));
+                Frame::ok()
+            }
+            Client::List => Frame::Bulk(clients.list().into()),
+            Client::Kill { id } => Frame::Integer(clients.kill(id) as i64),
+            Client::NoEvict(on) => {
+                client.set_no_evict(on);
+                Frame::ok()
+            }
+            Client::NoTouch(on) => {
+                client.set_no_touch(on);
+                db.set_touch(!on);
+                Frame::ok()
+            }
+            Client::Help => help(HELP),
+        }
+    }
+}
+
+/// The `ON` or `OFF` argument of NO-EVICT and NO-TOUCH.
+fn parse_switch(parse: &mut Parse) -> Result<bool, ParseError> {
+    match &parse.next_string()?.to_lowercase()[..] {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(CommandError::Syntax.into()),
+    }
+}
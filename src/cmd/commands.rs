@@ -0,0 +1,64 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::table::{self, Spec, SPECS};
+use crate::cmd::CommandError;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// `COMMAND`, named in the plural to keep clear of the `Command` enum.
+#[derive(Debug)]
+pub enum Commands {
+    All,
+    Count,
+    Info { names: Vec<String> },
+}
+
+impl Commands {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        if !parse.has_remaining() {
+            return Ok(Commands::All);
+        }
+
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "count" => Ok(Commands::Count),
+            "info" => {
+                let names = parse.remaining_strings(0)?;
+                Ok(Commands::Info { names })
+            }
+            _ => Err(CommandError::unknown_subcommand("COMMAND", subcommand).into()),
+        }
+    }
+
+    /// INFO replies `[name, arity, [flags], first key, last key, step]` per
+    /// name, or null for names the server doesn't know; bare COMMAND replies
+    /// that for every command.
+    pub fn apply(self) -> Frame {
+        match self {
+            Commands::All => Frame::Array(SPECS.iter().map(describe).collect()),
+            Commands::Count => Frame::Integer(SPECS.len() as i64),
+            Commands::Info { names } => Frame::Array(
+                names
+                    .iter()
+                    .map(|name| table::lookup(&name.to_lowercase()).map_or(Frame::Null, describe))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn describe(spec: &Spec) -> Frame {
+    Frame::Array(vec![
+        Frame::Bulk(Bytes::from_static(spec.name.as_bytes())),
+        Frame::Integer(spec.arity),
+        Frame::Set(
+            spec.flags
+                .names()
+                .map(|flag| Frame::Simple(Bytes::from_static(flag.as_bytes())))
+                .collect(),
+        ),
+        Frame::Integer(spec.first_key),
+        Frame::Integer(spec.last_key),
+        Frame::Integer(spec.step),
+    ])
+}
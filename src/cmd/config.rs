@@ -0,0 +1,124 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::config::ServerConfig;
+use crate::frame::Frame;
+use crate::stats::CommandStats;
+use bytes::Bytes;
+use std::sync::RwLock;
+
+#[derive(Debug)]
+pub enum Config {
+    Get { pattern: String },
+    Set { name: String, value: String },
+    ResetStat,
+}
+
+impl Config {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "get" => Ok(Config::Get {
+                pattern: parse.next_string()?,
+            }),
+            "set" => Ok(Config::Set {
+                name: parse.next_string()?,
+                value: parse.next_string()?,
+            }),
+            "resetstat" => Ok(Config::ResetStat),
+            _ => Err(CommandError::unknown_subcommand("CONFIG", subcommand).into()),
+        }
+    }
+
+    pub fn apply(self, config: &RwLock<ServerConfig>, stats: &CommandStats) -> Frame {
+        match self {
+            Config::Get { pattern } => {
+                let config = config.read().unwrap();
+                let frames = config
+                    .matching(&pattern)
+                    .into_iter()
+                    .flat_map(|(name, value)| {
+                        [
+                            Frame::Bulk(Bytes::from_static(name.as_bytes())),
+                            Frame::Bulk(value.into()),
+                        ]
+                    })
+                    .collect();
+                Frame::Array(frames)
+            }
+            Config::Set { name, value } => match config.write().unwrap().set(&name, &value) {
+                Ok(()) => Frame::ok(),
+                Err(err) => Frame::Error(format!("ERR {err}")),
+            },
+            Config::ResetStat => {
+                stats.reset();
+                Frame::ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Config;
+    use crate::config::{EvictionPolicy, ServerConfig};
+    use crate::frame::Frame;
+    use crate::stats::CommandStats;
+    use std::sync::RwLock;
+
+    #[test]
+    fn apply_get_known_parameter() {
+        // Arrange
+        let config = RwLock::new(ServerConfig::default());
+        let cmd = Config::Get {
+            pattern: "maxmemory".to_string(),
+        };
+
+        // Act
+        let frame = cmd.apply(&config, &CommandStats::new());
+
+        // Assert
+        assert_eq!(
+            frame,
+            Frame::Array(vec![
+                Frame::Bulk("maxmemory".into()),
+                Frame::Bulk("0".into())
+            ])
+        );
+    }
+
+    #[test]
+    fn apply_get_unknown_parameter_empty() {
+        // Arrange
+        let config = RwLock::new(ServerConfig::default());
+        let cmd = Config::Get {
+            pattern: "no-such-parameter".to_string(),
+        };
+
+        // Act
+        let frame = cmd.apply(&config, &CommandStats::new());
+
+        // Assert
+        assert_eq!(frame, Frame::Array(vec![]));
+    }
+
+    #[test]
+    fn apply_set_eviction_policy_at_runtime() {
+        // Arrange
+        let config = RwLock::new(ServerConfig::default());
+        let cmd = Config::Set {
+            name: "maxmemory-policy".to_string(),
+            value: "allkeys-lru".to_string(),
+        };
+
+        // Act
+        let frame = cmd.apply(&config, &CommandStats::new());
+
+        // Assert
+        assert_eq!(frame, Frame::ok());
+        assert_eq!(
+            config.read().unwrap().maxmemory_policy,
+            EvictionPolicy::AllKeysLru
+        );
+    }
+}
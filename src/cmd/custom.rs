@@ -0,0 +1,123 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// What `CommandHandler::execute` returns, boxed since an `async fn` in a
+/// trait can't be called through `dyn`.
+pub type HandlerFuture<'a> = Pin<Box<dyn Future<Output = Frame> + Send + 'a>>;
+
+/// A command added by an application embedding the server, for verbs of its
+/// own or to replace a built-in one. Custom commands are held to READONLY and
+/// `maxmemory` like built-in ones, but don't fire keyspace notifications.
+pub trait CommandHandler: Send + Sync {
+    /// The name clients send, matched case-insensitively.
+    fn name(&self) -> &str;
+
+    /// Whether the command changes the dataset, refusing it on a read-only
+    /// replica. Assumed unless the handler says otherwise.
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    /// Whether the command can grow the dataset, refusing it once eviction
+    /// can't bring memory back under `maxmemory`.
+    fn may_grow(&self) -> bool {
+        self.is_write()
+    }
+
+    /// Runs the command with the arguments after its name, against the
+    /// database the connection has selected.
+    fn execute<'a>(&'a self, args: Vec<Bytes>, db: &'a mut ShardedDb) -> HandlerFuture<'a>;
+}
+
+/// Custom commands by name, which the server consults before its built-in
+/// ones.
+#[derive(Clone, Default)]
+pub struct Registry {
+    handlers: HashMap<String, Arc<dyn CommandHandler>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces any handler already registered under the same name.
+    pub fn register(&mut self, handler: impl CommandHandler + 'static) {
+        self.handlers
+            .insert(handler.name().to_lowercase(), Arc::new(handler));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn CommandHandler>> {
+        self.handlers.get(&name.to_lowercase()).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+/// The arguments after the command name, which must be strings just like a
+/// built-in command's.
+pub(crate) fn parse_args(frame: Frame) -> Result<Vec<Bytes>, ParseError> {
+    let mut parse = Parse::new(frame)?;
+    parse.next_bytes()?;
+    let mut args = Vec::new();
+    while parse.has_remaining() {
+        args.push(parse.next_bytes()?);
+    }
+    Ok(args)
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{CommandHandler, HandlerFuture, Registry};
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+    use bytes::Bytes;
+
+    struct Echo(&'static str);
+
+    impl CommandHandler for Echo {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn execute<'a>(&'a self, args: Vec<Bytes>, _db: &'a mut ShardedDb) -> HandlerFuture<'a> {
+            Box::pin(async move { Frame::Array(args.into_iter().map(Frame::Bulk).collect()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn get_matches_name_case_insensitively() {
+        // Arrange
+        let mut registry = Registry::new();
+        registry.register(Echo("Echo-Custom"));
+        let mut db = ShardedDb::new();
+
+        // Act
+        let handler = registry.get("ECHO-CUSTOM").unwrap();
+        let response = handler.execute(vec!["hi".into()], &mut db).await;
+
+        // Assert
+        assert_eq!(response, Frame::Array(vec![Frame::Bulk("hi".into())]));
+        assert!(registry.get("echo").is_none());
+        assert_eq!(format!("{registry:?}"), r#"{"echo-custom"}"#);
+    }
+}
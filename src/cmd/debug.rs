@@ -0,0 +1,111 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{ListLimit, ShardedDb};
+use crate::dump;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub enum Debug {
+    Object { key: Bytes },
+    FlushShard { index: usize },
+    SetActiveExpire { enabled: bool },
+    ListMaxListpackSize { limit: ListLimit },
+}
+
+impl Debug {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "object" => Ok(Debug::Object {
+                key: parse.next_bytes()?,
+            }),
+            "flushshard" => Ok(Debug::FlushShard {
+                // a negative index is out of range like any other
+                index: usize::try_from(parse.next_int()?).unwrap_or(usize::MAX),
+            }),
+            "set-active-expire" => Ok(Debug::SetActiveExpire {
+                enabled: parse.next_int()? != 0,
+            }),
+            "list-max-listpack-size" => Ok(Debug::ListMaxListpackSize {
+                limit: ListLimit::new(parse.next_int()?).ok_or(CommandError::NotAnInteger)?,
+            }),
+            _ => Err(CommandError::unknown_subcommand("DEBUG", subcommand).into()),
+        }
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match self {
+            Debug::Object { key } => {
+                let details = db.inspect(&key, |value| {
+                    format!(
+                        "serializedlength:{} encoding:{}",
+                        dump::serialized_len(value),
+                        value.encoding()
+                    )
+                });
+
+                match details {
+                    Some(details) => Frame::Simple(details.into()),
+                    None => Frame::Error("ERR no such key".to_string()),
+                }
+            }
+            Debug::FlushShard { index } => match db.flush_shard(index) {
+                Ok(_) => Frame::ok(),
+                Err(err) => Frame::Error(err.to_string()),
+            },
+            Debug::SetActiveExpire { enabled } => {
+                db.set_active_expire(enabled);
+                Frame::ok()
+            }
+            Debug::ListMaxListpackSize { limit } => {
+                db.set_list_limit(limit);
+                Frame::ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Debug, Dump};
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_object_serialized_length_matches_dump() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "some value".into());
+
+        // Act
+        let details = Debug::Object { key: "key".into() }.apply(&db);
+        let dump = Dump::new("key").apply(&db);
+
+        // Assert
+        let Frame::Bulk(dump) = dump else {
+            panic!("Expected Frame::Bulk variant");
+        };
+        assert_eq!(
+            details,
+            Frame::Simple(format!("serializedlength:{} encoding:embstr", dump.len()).into())
+        );
+    }
+
+    #[test]
+    fn apply_object_integer_encoding() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "12345".into());
+
+        // Act
+        let details = Debug::Object { key: "key".into() }.apply(&db);
+
+        // Assert
+        let Frame::Simple(details) = details else {
+            panic!("Expected Frame::Simple variant");
+        };
+        assert!(details.ends_with(b"encoding:int"));
+    }
+}
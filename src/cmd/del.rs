@@ -0,0 +1,101 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{self, ShardedDb};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// DEL, and UNLINK, which frees large values on a background task.
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<Bytes>,
+    lazy: bool,
+}
+
+impl Del {
+    pub fn new(keys: Vec<Bytes>, lazy: bool) -> Self {
+        Self { keys, lazy }
+    }
+
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub fn lazy(&self) -> bool {
+        self.lazy
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, lazy: bool) -> Result<Self, ParseError> {
+        let keys = parse.remaining_bytes(1)?;
+
+        Ok(Self { keys, lazy })
+    }
+
+    /// Replies with how many of the keys existed, handing each one removed to
+    /// `deleted`. UNLINK frees values estimated above `lazyfree_threshold`
+    /// bytes off the connection's task.
+    pub fn apply(
+        self,
+        db: &mut ShardedDb,
+        lazyfree_threshold: usize,
+        mut deleted: impl FnMut(&Bytes),
+    ) -> Frame {
+        let removed = self
+            .keys
+            .iter()
+            .filter(|key| match db.delete(key) {
+                Some(value) if self.lazy => {
+                    let size = value.heap_size();
+                    db::free_lazily(value, size, lazyfree_threshold);
+                    true
+                }
+                removed => removed.is_some(),
+            })
+            .inspect(|key| deleted(key))
+            .count();
+
+        Frame::Integer(removed as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Del;
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_counts_existing_keys() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("a", "1".into());
+        db.insert("b", "2".into());
+
+        // Act
+        let response = Del::new(vec!["a".into(), "missing".into(), "b".into()], false).apply(
+            &mut db,
+            0,
+            |_| {},
+        );
+
+        // Assert
+        assert_eq!(response, Frame::Integer(2));
+        assert!(db.is_empty());
+    }
+
+    #[test]
+    fn apply_counts_deleted_types_and_reports_keys() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("string", "1".into());
+        db.list_push("list", End::Right, vec!["a".into()]).unwrap();
+        let mut deleted = Vec::new();
+
+        // Act
+        Del::new(vec!["list".into(), "string".into()], false)
+            .apply(&mut db, 0, |key| deleted.push(key.clone()));
+
+        // Assert
+        assert_eq!(deleted, ["list", "string"]);
+        let counts = db.deleted_keys();
+        assert_eq!((counts.lists, counts.strings, counts.sets), (1, 1, 0));
+    }
+}
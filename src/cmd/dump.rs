@@ -0,0 +1,28 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::dump;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Dump {
+    key: Bytes,
+}
+
+impl Dump {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        Ok(Self { key })
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.inspect(&self.key, dump::serialize) {
+            Some(dump) => Frame::Bulk(dump.into()),
+            None => Frame::Null,
+        }
+    }
+}
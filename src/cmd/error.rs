@@ -0,0 +1,76 @@
+use crate::frame::Frame;
+
+/// Errors a command reports to its client, each rendering as the exact reply
+/// Redis gives, error code included.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum CommandError {
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotAnInteger,
+    #[error("ERR syntax error")]
+    Syntax,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR unknown subcommand '{subcommand}'. Try {command} HELP.")]
+    UnknownSubcommand {
+        command: &'static str,
+        subcommand: String,
+    },
+}
+
+impl CommandError {
+    pub fn unknown_subcommand(command: &'static str, subcommand: impl ToString) -> Self {
+        Self::UnknownSubcommand {
+            command,
+            subcommand: subcommand.to_string(),
+        }
+    }
+}
+
+impl From<CommandError> for Frame {
+    fn from(err: CommandError) -> Self {
+        Frame::Error(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::CommandError;
+    use crate::frame::Frame;
+
+    #[test]
+    fn variants_render_redis_messages() {
+        // Arrange
+        let cases = [
+            (
+                CommandError::WrongArity("get".into()),
+                "ERR wrong number of arguments for 'get' command",
+            ),
+            (
+                CommandError::WrongType,
+                "WRONGTYPE Operation against a key holding the wrong kind of value",
+            ),
+            (
+                CommandError::NotAnInteger,
+                "ERR value is not an integer or out of range",
+            ),
+            (CommandError::Syntax, "ERR syntax error"),
+            (CommandError::NoSuchKey, "ERR no such key"),
+            (
+                CommandError::unknown_subcommand("OBJECT", "nope"),
+                "ERR unknown subcommand 'nope'. Try OBJECT HELP.",
+            ),
+        ];
+
+        for (err, expected) in cases {
+            // Act
+            let frame = Frame::from(err);
+
+            // Assert
+            assert_eq!(frame, Frame::Error(expected.to_string()));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug)]
+pub struct Expire {
+    key: Bytes,
+    seconds: i64,
+}
+
+impl Expire {
+    pub fn new(key: impl Into<Bytes>, seconds: i64) -> Self {
+        Self {
+            key: key.into(),
+            seconds,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let seconds = parse.next_int()?;
+        Ok(Self { key, seconds })
+    }
+
+    /// A deadline that is already in the past deletes the key, as in Redis.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        let applied = match u64::try_from(self.seconds) {
+            Ok(seconds) if seconds > 0 => {
+                let ttl = Duration::from_secs(seconds);
+                if Instant::now().checked_add(ttl).is_none() {
+                    return Frame::Error("ERR invalid expire time in 'expire' command".to_string());
+                }
+                db.expire(&self.key, ttl)
+            }
+            _ => db.remove(&self.key).is_some(),
+        };
+
+        Frame::Integer(applied as i64)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{self, ShardedDb};
+use crate::frame::Frame;
+
+/// FLUSHALL, clearing every database, and FLUSHDB, clearing the selected one.
+#[derive(Debug)]
+pub struct Flush {
+    all: bool,
+    lazy: bool,
+}
+
+impl Flush {
+    pub fn new(all: bool, lazy: bool) -> Self {
+        Self { all, lazy }
+    }
+
+    pub fn all(&self) -> bool {
+        self.all
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, all: bool) -> Result<Self, ParseError> {
+        let lazy = if parse.has_remaining() {
+            match &parse.next_string()?.to_uppercase()[..] {
+                "ASYNC" => true,
+                "SYNC" => false,
+                _ => return Err(CommandError::Syntax.into()),
+            }
+        } else {
+            false
+        };
+
+        Ok(Self { all, lazy })
+    }
+
+    /// With `ASYNC` the keys are gone right away but, once they add up to more
+    /// than `lazyfree_threshold` bytes, freed on a blocking thread so a large
+    /// flush doesn't stall the connection.
+    pub fn apply(self, db: &ShardedDb, dbs: &[ShardedDb], lazyfree_threshold: usize) -> Frame {
+        let targets = if self.all {
+            dbs
+        } else {
+            std::slice::from_ref(db)
+        };
+        let flushed: Vec<_> = targets.iter().map(ShardedDb::flush).collect();
+        if self.lazy {
+            let size = flushed.iter().map(db::Flushed::size).sum();
+            db::free_lazily(flushed, size, lazyfree_threshold);
+        }
+
+        Frame::ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Flush;
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+
+    #[tokio::test]
+    async fn apply_empties_database() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        for key in ["a", "b", "c"] {
+            db.insert(key, "value".into());
+        }
+
+        // Act
+        let sync = Flush::new(false, false).apply(&db, &[], 0);
+        db.insert("d", "value".into());
+        let lazy = Flush::new(true, true).apply(&db, std::slice::from_ref(&db), 0);
+
+        // Assert
+        assert_eq!(sync, Frame::ok());
+        assert_eq!(lazy, Frame::ok());
+        assert!(db.is_empty());
+        assert_eq!(db.used_memory(), 0);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Get {
+    key: Bytes,
+}
+
+impl Get {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        Ok(Self { key })
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.get(&self.key) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
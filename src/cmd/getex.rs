@@ -0,0 +1,147 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{Expiry, ShardedDb};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: Bytes,
+    expiry: Expiry,
+}
+
+impl GetEx {
+    pub fn new(key: impl Into<Bytes>, expiry: Expiry) -> Self {
+        Self {
+            key: key.into(),
+            expiry,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        if !parse.has_remaining() {
+            return Ok(Self {
+                key,
+                expiry: Expiry::Keep,
+            });
+        }
+
+        let option = parse.next_string()?.to_uppercase();
+        let to_expiry: fn(u64) -> Option<Expiry> = match &option[..] {
+            "PERSIST" => return Ok(Self::new(key, Expiry::Persist)),
+            "EX" => |secs| Some(Expiry::In(Duration::from_secs(secs))),
+            "PX" => |millis| Some(Expiry::In(Duration::from_millis(millis))),
+            "EXAT" => |secs| {
+                UNIX_EPOCH
+                    .checked_add(Duration::from_secs(secs))
+                    .map(Expiry::At)
+            },
+            "PXAT" => |millis| {
+                UNIX_EPOCH
+                    .checked_add(Duration::from_millis(millis))
+                    .map(Expiry::At)
+            },
+            _ => return Err(CommandError::Syntax.into()),
+        };
+
+        let amount = parse.next_int()?;
+        let expiry = u64::try_from(amount)
+            .ok()
+            .filter(|&amount| amount > 0)
+            .and_then(to_expiry)
+            .filter(|expiry| expiry.is_valid())
+            .ok_or_else(|| anyhow!("invalid expire time in 'getex' command"))?;
+
+        Ok(Self { key, expiry })
+    }
+
+    /// Replies like GET. A missing key is a null and leaves nothing to expire.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.get_ex(&self.key, self.expiry) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Command, GetEx};
+    use crate::db::{Expiry, ShardedDb};
+    use crate::frame::Frame;
+    use std::time::Duration;
+
+    fn command_frame(parts: &[&str]) -> Frame {
+        Frame::Array(
+            parts
+                .iter()
+                .map(|part| Frame::Bulk(part.to_string().into()))
+                .collect(),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn apply_ex_then_persist() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+        let Ok(Command::GetEx(with_ttl)) =
+            Command::from_frame(command_frame(&["GETEX", "key", "EX", "100"]))
+        else {
+            panic!("expected GETEX");
+        };
+
+        // Act
+        let first = with_ttl.apply(&mut db);
+        let ttl = db.ttl("key");
+        let second = GetEx::new("key", Expiry::Persist).apply(&mut db);
+
+        // Assert
+        assert_eq!(first, Frame::Bulk("value".into()));
+        assert_eq!(ttl, Some(Some(Duration::from_secs(100))));
+        assert_eq!(second, Frame::Bulk("value".into()));
+        assert_eq!(db.ttl("key"), Some(None));
+    }
+
+    #[test]
+    fn apply_missing_key_null() {
+        // Act
+        let response = GetEx::new("missing", Expiry::Persist).apply(&mut ShardedDb::new());
+
+        // Assert
+        assert_eq!(response, Frame::Null);
+    }
+
+    #[test]
+    fn parse_rejects_two_options() {
+        // Act
+        let parsed = Command::from_frame(command_frame(&["GETEX", "key", "EX", "1", "PERSIST"]));
+
+        // Assert
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unrepresentable_ttl() {
+        // Arrange
+        let huge = i64::MAX.to_string();
+        let frame = command_frame(&["GETEX", "key", "EX", &huge]);
+
+        // Act
+        let parsed = Command::from_frame(frame);
+
+        // Assert
+        let Err(err) = parsed else {
+            panic!("expected GETEX to be rejected");
+        };
+        assert_eq!(err.to_string(), "invalid expire time in 'getex' command");
+    }
+}
@@ -0,0 +1,53 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::{Frame, Protocol};
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<i64>,
+}
+
+impl Hello {
+    pub fn new(protover: Option<i64>) -> Self {
+        Self { protover }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let protover = if parse.has_remaining() {
+            Some(parse.next_int()?)
+        } else {
+            None
+        };
+
+        Ok(Self { protover })
+    }
+
+    /// Switches `protocol` to the requested version, replying with the server
+    /// details in that version.
+    pub fn apply(self, protocol: &mut Protocol) -> Frame {
+        match self.protover {
+            None => {}
+            Some(2) => *protocol = Protocol::Resp2,
+            Some(3) => *protocol = Protocol::Resp3,
+            Some(_) => return Frame::Error("NOPROTO unsupported protocol version".to_string()),
+        }
+
+        let proto = match protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+
+        Frame::Map(vec![
+            (bulk("server"), bulk("redis")),
+            (bulk("version"), bulk(env!("CARGO_PKG_VERSION"))),
+            (bulk("proto"), Frame::Integer(proto)),
+            (bulk("mode"), bulk("standalone")),
+            (bulk("role"), bulk("master")),
+            (bulk("modules"), Frame::Array(vec![])),
+        ])
+    }
+}
+
+fn bulk(content: &'static str) -> Frame {
+    Frame::Bulk(Bytes::from_static(content.as_bytes()))
+}
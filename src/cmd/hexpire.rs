@@ -0,0 +1,259 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{ExpireCondition, ShardedDb};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug)]
+pub struct HExpire {
+    key: Bytes,
+    seconds: u64,
+    condition: Option<ExpireCondition>,
+    fields: Vec<Bytes>,
+}
+
+impl HExpire {
+    pub fn new(
+        key: impl Into<Bytes>,
+        seconds: u64,
+        condition: Option<ExpireCondition>,
+        fields: Vec<Bytes>,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            seconds,
+            condition,
+            fields,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let seconds = u64::try_from(parse.next_int()?)
+            .map_err(|_| anyhow!("invalid expire time, must be >= 0"))?;
+
+        let mut keyword = parse.next_string()?.to_uppercase();
+        let condition = match &keyword[..] {
+            "NX" => Some(ExpireCondition::Nx),
+            "XX" => Some(ExpireCondition::Xx),
+            "GT" => Some(ExpireCondition::Gt),
+            "LT" => Some(ExpireCondition::Lt),
+            _ => None,
+        };
+        if condition.is_some() {
+            keyword = parse.next_string()?.to_uppercase();
+        }
+        let fields = parse_fields(parse, &keyword)?;
+
+        Ok(Self {
+            key,
+            seconds,
+            condition,
+            fields,
+        })
+    }
+
+    /// Replies with a status per field, see `ShardedDb::hash_expire`. Zero
+    /// seconds deletes the fields.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        let Some(deadline) = Instant::now().checked_add(Duration::from_secs(self.seconds)) else {
+            return Frame::Error("ERR invalid expire time in 'hexpire' command".to_string());
+        };
+
+        match db.hash_expire(&self.key, deadline, self.condition, &self.fields) {
+            Ok(replies) => Frame::Array(replies.into_iter().map(Frame::Integer).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HPersist {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+impl HPersist {
+    pub fn new(key: impl Into<Bytes>, fields: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            fields,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let keyword = parse.next_string()?.to_uppercase();
+        let fields = parse_fields(parse, &keyword)?;
+        Ok(Self { key, fields })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.hash_persist(&self.key, &self.fields) {
+            Ok(replies) => Frame::Array(replies.into_iter().map(Frame::Integer).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HTtl {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+impl HTtl {
+    pub fn new(key: impl Into<Bytes>, fields: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            fields,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let keyword = parse.next_string()?.to_uppercase();
+        let fields = parse_fields(parse, &keyword)?;
+        Ok(Self { key, fields })
+    }
+
+    /// Per field, `-2` when missing, `-1` without a deadline, otherwise the
+    /// remaining seconds rounded like TTL.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.hash_ttl(&self.key, &self.fields) {
+            Ok(ttls) => Frame::Array(
+                ttls.into_iter()
+                    .map(|ttl| match ttl {
+                        None => Frame::Integer(-2),
+                        Some(None) => Frame::Integer(-1),
+                        Some(Some(ttl)) => Frame::Integer(((ttl.as_millis() + 500) / 1000) as i64),
+                    })
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+/// Reads `FIELDS numfields field [field ...]`, `keyword` being the already
+/// consumed first word.
+fn parse_fields(parse: &mut Parse, keyword: &str) -> Result<Vec<Bytes>, ParseError> {
+    if keyword != "FIELDS" {
+        return Err(
+            anyhow!("Mandatory argument FIELDS is missing or not at the right position").into(),
+        );
+    }
+
+    let count = usize::try_from(parse.next_int()?)
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| anyhow!("Parameter `numFields` should be greater than 0"))?;
+    let mismatch = || anyhow!("The `numfields` parameter must match the number of arguments");
+
+    let mut fields = Vec::new();
+    for _ in 0..count {
+        if !parse.has_remaining() {
+            return Err(mismatch().into());
+        }
+        fields.push(parse.next_bytes()?);
+    }
+    if parse.has_remaining() {
+        return Err(mismatch().into());
+    }
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{HExpire, HGetAll, HPersist, HSet, HTtl};
+    use crate::db::{ExpireCondition, ShardedDb};
+    use crate::frame::Frame;
+    use bytes::Bytes;
+    use std::time::Duration;
+
+    fn db_with_hash() -> ShardedDb {
+        let mut db = ShardedDb::new();
+        HSet::new(
+            "hash",
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+        )
+        .apply(&mut db);
+        db
+    }
+
+    fn fields(names: &[&'static str]) -> Vec<Bytes> {
+        names
+            .iter()
+            .map(|name| Bytes::from_static(name.as_bytes()))
+            .collect()
+    }
+
+    fn integers(values: &[i64]) -> Frame {
+        Frame::Array(values.iter().copied().map(Frame::Integer).collect())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_field_absent_from_hgetall() {
+        // Arrange
+        let mut db = db_with_hash();
+
+        // Act
+        let expire = HExpire::new("hash", 10, None, fields(&["a", "missing"])).apply(&mut db);
+        tokio::time::advance(Duration::from_secs(11)).await;
+        let all = HGetAll::new("hash").apply(&db);
+
+        // Assert
+        assert_eq!(expire, integers(&[1, -2]));
+        assert_eq!(
+            all,
+            Frame::Array(vec![Frame::Bulk("b".into()), Frame::Bulk("2".into())])
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn httl_sentinels() {
+        // Arrange
+        let mut db = db_with_hash();
+        HExpire::new("hash", 100, None, fields(&["a"])).apply(&mut db);
+
+        // Act
+        let ttls = HTtl::new("hash", fields(&["a", "b", "missing"])).apply(&db);
+        let missing_key = HTtl::new("nope", fields(&["a"])).apply(&db);
+
+        // Assert
+        assert_eq!(ttls, integers(&[100, -1, -2]));
+        assert_eq!(missing_key, integers(&[-2]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn conditions_zero_seconds_and_persist() {
+        // Arrange
+        let mut db = db_with_hash();
+
+        // Act
+        let xx = HExpire::new("hash", 10, Some(ExpireCondition::Xx), fields(&["a"])).apply(&mut db);
+        let nx = HExpire::new("hash", 10, Some(ExpireCondition::Nx), fields(&["a"])).apply(&mut db);
+        let gt = HExpire::new("hash", 5, Some(ExpireCondition::Gt), fields(&["a"])).apply(&mut db);
+        let persist = HPersist::new("hash", fields(&["a", "b"])).apply(&mut db);
+        let deleted = HExpire::new("hash", 0, None, fields(&["a", "b"])).apply(&mut db);
+
+        // Assert
+        assert_eq!(xx, integers(&[0]));
+        assert_eq!(nx, integers(&[1]));
+        assert_eq!(gt, integers(&[0]));
+        assert_eq!(persist, integers(&[1, -1]));
+        assert_eq!(deleted, integers(&[2, 2]));
+        assert!(db.is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HGet {
+    key: Bytes,
+    field: Bytes,
+}
+
+impl HGet {
+    pub fn new(key: impl Into<Bytes>, field: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            field,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let field = parse.next_bytes()?;
+        Ok(Self { key, field })
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.hash_get(&self.key, &self.field) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
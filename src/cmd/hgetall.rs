@@ -0,0 +1,33 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HGetAll {
+    key: Bytes,
+}
+
+impl HGetAll {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        Ok(Self { key })
+    }
+
+    /// Fields each followed by their value, in no particular order.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.hash_get_all(&self.key) {
+            Ok(pairs) => Frame::Array(
+                pairs
+                    .into_iter()
+                    .flat_map(|(field, value)| [Frame::Bulk(field), Frame::Bulk(value)])
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::srandmember::parse_count;
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HRandField {
+    key: Bytes,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+impl HRandField {
+    pub fn new(key: impl Into<Bytes>, count: Option<i64>, with_values: bool) -> Self {
+        Self {
+            key: key.into(),
+            count,
+            with_values,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let count = if parse.has_remaining() {
+            Some(parse_count(parse)?)
+        } else {
+            None
+        };
+        let with_values = if count.is_some() && parse.has_remaining() {
+            if parse.next_string()?.to_uppercase() != "WITHVALUES" {
+                return Err(CommandError::Syntax.into());
+            }
+            true
+        } else {
+            false
+        };
+
+        Ok(Self {
+            key,
+            count,
+            with_values,
+        })
+    }
+
+    /// Without a count the reply is a single field, null for a missing key.
+    /// With one it is an array of fields, each followed by its value when
+    /// `WITHVALUES` is given.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let fields = match db.hash_random_fields(&self.key, self.count.unwrap_or(1)) {
+            Ok(fields) => fields,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        if self.count.is_none() {
+            return fields
+                .into_iter()
+                .next()
+                .map_or(Frame::Null, |(field, _)| Frame::Bulk(field));
+        }
+
+        let frames = fields
+            .into_iter()
+            .flat_map(|(field, value)| {
+                let value = self.with_values.then_some(Frame::Bulk(value));
+                std::iter::once(Frame::Bulk(field)).chain(value)
+            })
+            .collect();
+        Frame::Array(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{HRandField, HSet};
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_with_values_pairs_fields() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        HSet::new(
+            "hash",
+            vec![("f1".into(), "v1".into()), ("f2".into(), "v2".into())],
+        )
+        .apply(&mut db);
+
+        // Act
+        let distinct = HRandField::new("hash", Some(5), true).apply(&db);
+        let repeated = HRandField::new("hash", Some(-6), false).apply(&db);
+
+        // Assert
+        let Frame::Array(distinct) = distinct else {
+            panic!("expected an array");
+        };
+        assert_eq!(distinct.len(), 4);
+        for pair in distinct.chunks(2) {
+            let expected = match &pair[0] {
+                Frame::Bulk(field) if field == "f1" => "v1",
+                Frame::Bulk(field) if field == "f2" => "v2",
+                frame => panic!("unexpected field {frame:?}"),
+            };
+            assert_eq!(pair[1], Frame::Bulk(expected.into()));
+        }
+        let Frame::Array(repeated) = repeated else {
+            panic!("expected an array");
+        };
+        assert_eq!(repeated.len(), 6);
+    }
+}
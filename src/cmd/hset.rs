@@ -0,0 +1,41 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HSet {
+    key: Bytes,
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl HSet {
+    pub fn new(key: impl Into<Bytes>, pairs: Vec<(Bytes, Bytes)>) -> Self {
+        Self {
+            key: key.into(),
+            pairs,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let mut pairs = vec![(parse.next_bytes()?, parse.next_bytes()?)];
+        while parse.has_remaining() {
+            pairs.push((parse.next_bytes()?, parse.next_bytes()?));
+        }
+
+        Ok(Self { key, pairs })
+    }
+
+    /// Replies with how many fields were added rather than updated.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.hash_set(&self.key, self.pairs) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: Bytes,
+    increment: f64,
+}
+
+impl IncrByFloat {
+    pub fn new(key: impl Into<Bytes>, increment: f64) -> Self {
+        Self {
+            key: key.into(),
+            increment,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let increment = parse.next_float()?;
+        Ok(Self { key, increment })
+    }
+
+    /// Replies with the new value, formatted the way it was stored.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.incr_by_float(&self.key, self.increment) {
+            Ok(value) => Frame::Bulk(value),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: Bytes,
+    field: Bytes,
+    increment: f64,
+}
+
+impl HIncrByFloat {
+    pub fn new(key: impl Into<Bytes>, field: Bytes, increment: f64) -> Self {
+        Self {
+            key: key.into(),
+            field,
+            increment,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let field = parse.next_bytes()?;
+        let increment = parse.next_float()?;
+        Ok(Self {
+            key,
+            field,
+            increment,
+        })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.hash_incr_by_float(&self.key, self.field, self.increment) {
+            Ok(value) => Frame::Bulk(value),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
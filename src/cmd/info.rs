@@ -0,0 +1,138 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::config::ServerConfig;
+use crate::db::{DeletedKeys, ShardedDb};
+use crate::frame::Frame;
+use crate::stats::CommandStats;
+use std::fmt::Write;
+
+const SECTIONS: &[&str] = &["memory", "stats", "commandstats"];
+
+#[derive(Debug)]
+pub struct Info {
+    section: Option<String>,
+}
+
+impl Info {
+    pub fn new(section: Option<String>) -> Self {
+        Self { section }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let section = if parse.has_remaining() {
+            Some(parse.next_string()?.to_lowercase())
+        } else {
+            None
+        };
+
+        Ok(Self { section })
+    }
+
+    /// Renders the requested section, or all of them, in Redis's
+    /// `# Section` / `field:value` format. Unknown sections render empty.
+    /// Figures cover every database, not just the selected one.
+    pub fn apply(self, dbs: &[ShardedDb], config: &ServerConfig, stats: &CommandStats) -> Frame {
+        let sections: Vec<&str> = match self.section.as_deref() {
+            None | Some("all" | "default" | "everything") => SECTIONS.to_vec(),
+            Some(section) => SECTIONS
+                .iter()
+                .copied()
+                .filter(|candidate| *candidate == section)
+                .collect(),
+        };
+
+        let mut info = String::new();
+        for section in sections {
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            match section {
+                "memory" => {
+                    info.push_str("# Memory\r\n");
+                    let _ = write!(
+                        info,
+                        "used_memory:{}\r\nmaxmemory:{}\r\nmaxmemory_policy:{}\r\nevicted_keys:{}\r\n",
+                        dbs.iter().map(ShardedDb::used_memory).sum::<usize>(),
+                        config.maxmemory,
+                        config.maxmemory_policy,
+                        dbs.iter().map(ShardedDb::evicted_keys).sum::<u64>(),
+                    );
+                }
+                "stats" => {
+                    let mut deleted = DeletedKeys::default();
+                    for db in dbs {
+                        deleted.add(db.deleted_keys());
+                    }
+                    info.push_str("# Stats\r\n");
+                    let _ = write!(
+                        info,
+                        "expired_keys:{}\r\ndeleted_strings:{}\r\ndeleted_lists:{}\r\ndeleted_sets:{}\r\ndeleted_hashes:{}\r\ndeleted_streams:{}\r\ntotal_protocol_errors:{}\r\n",
+                        dbs.iter().map(ShardedDb::expired_keys).sum::<u64>(),
+                        deleted.strings,
+                        deleted.lists,
+                        deleted.sets,
+                        deleted.hashes,
+                        deleted.streams,
+                        stats.protocol_errors(),
+                    );
+                }
+                "commandstats" => {
+                    info.push_str("# Commandstats\r\n");
+                    for stat in stats.called() {
+                        let _ = write!(
+                            info,
+                            "cmdstat_{}:calls={},usec={},usec_per_call={:.2}\r\n",
+                            stat.name,
+                            stat.calls,
+                            stat.usec,
+                            stat.usec as f64 / stat.calls as f64,
+                        );
+                    }
+                }
+                _ => unreachable!("every name in SECTIONS is rendered"),
+            }
+        }
+
+        Frame::Bulk(info.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Info;
+    use crate::config::ServerConfig;
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+    use crate::stats::CommandStats;
+
+    #[test]
+    fn apply_memory_lists_fields() {
+        // Arrange
+        let mut dbs = [ShardedDb::new(), ShardedDb::new()];
+        dbs[0].insert("key", "value".into());
+        dbs[1].insert("other", "value".into());
+        let config = ServerConfig::default();
+
+        // Act
+        let response = Info::new(Some("memory".into())).apply(&dbs, &config, &CommandStats::new());
+
+        // Assert
+        let expected = format!(
+            "# Memory\r\nused_memory:{}\r\nmaxmemory:0\r\nmaxmemory_policy:noeviction\r\nevicted_keys:0\r\n",
+            dbs[0].used_memory() + dbs[1].used_memory()
+        );
+        assert_eq!(response, Frame::Bulk(expected.into()));
+    }
+
+    #[test]
+    fn apply_unknown_section_empty() {
+        // Act
+        let response = Info::new(Some("nope".into())).apply(
+            &[ShardedDb::new()],
+            &ServerConfig::default(),
+            &CommandStats::new(),
+        );
+
+        // Assert
+        assert_eq!(response, Frame::Bulk("".into()));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::frame::Frame;
+use crate::latency::LatencyMonitor;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+/// The only event sampled: the time taken to process each command.
+const EVENT: &str = "command";
+
+#[derive(Debug)]
+pub enum Latency {
+    History,
+    Latest,
+    Reset,
+}
+
+impl Latency {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "history" => {
+                let event = parse.next_string()?;
+                if event.to_lowercase() != EVENT {
+                    return Err(anyhow!("unknown latency event '{}'", event).into());
+                }
+                Ok(Latency::History)
+            }
+            "latest" => Ok(Latency::Latest),
+            "reset" => {
+                // every event name resets the one histogram there is
+                while parse.has_remaining() {
+                    parse.next_string()?;
+                }
+                Ok(Latency::Reset)
+            }
+            _ => Err(CommandError::unknown_subcommand("LATENCY", subcommand).into()),
+        }
+    }
+
+    /// LATEST replies `[event, unix time, latest µs, max µs]` per sampled event,
+    /// HISTORY `[bucket upper bound µs, samples]` per non-empty bucket, and
+    /// RESET the number of events cleared.
+    pub fn apply(self, monitor: &LatencyMonitor) -> Frame {
+        match self {
+            Latency::History => Frame::Array(
+                monitor
+                    .histogram()
+                    .into_iter()
+                    .map(|(bound, count)| {
+                        Frame::Array(vec![
+                            Frame::Integer(bound.as_micros() as i64),
+                            Frame::Integer(count as i64),
+                        ])
+                    })
+                    .collect(),
+            ),
+            Latency::Latest => Frame::Array(
+                monitor
+                    .latest()
+                    .map(|latest| {
+                        Frame::Array(vec![
+                            Frame::Bulk(Bytes::from_static(EVENT.as_bytes())),
+                            Frame::Integer(latest.at as i64),
+                            Frame::Integer(latest.latest.as_micros() as i64),
+                            Frame::Integer(latest.max.as_micros() as i64),
+                        ])
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
+            Latency::Reset => {
+                let sampled = monitor.latest().is_some();
+                monitor.reset();
+                Frame::Integer(sampled as i64)
+            }
+        }
+    }
+}
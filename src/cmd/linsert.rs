@@ -0,0 +1,55 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{Position, ShardedDb};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: Bytes,
+    position: Position,
+    pivot: Bytes,
+    value: Bytes,
+}
+
+impl LInsert {
+    pub fn new(key: impl Into<Bytes>, position: Position, pivot: Bytes, value: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            position,
+            pivot,
+            value,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let position = match &parse.next_string()?.to_uppercase()[..] {
+            "BEFORE" => Position::Before,
+            "AFTER" => Position::After,
+            _ => return Err(CommandError::Syntax.into()),
+        };
+        let pivot = parse.next_bytes()?;
+        let value = parse.next_bytes()?;
+
+        Ok(Self {
+            key,
+            position,
+            pivot,
+            value,
+        })
+    }
+
+    /// Replies with the new length, -1 when the pivot is absent and 0 when
+    /// the key does not exist.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.list_insert(&self.key, self.position, &self.pivot, self.value) {
+            Ok(len) => Frame::Integer(len),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{End, ShardedDb};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// LMOVE, and RPOPLPUSH as its right-to-left special case; `rpoplpush` only
+/// tells them apart.
+#[derive(Debug)]
+pub struct LMove {
+    source: Bytes,
+    destination: Bytes,
+    from: End,
+    to: End,
+    rpoplpush: bool,
+}
+
+impl LMove {
+    pub fn new(
+        source: impl Into<Bytes>,
+        destination: impl Into<Bytes>,
+        from: End,
+        to: End,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            destination: destination.into(),
+            from,
+            to,
+            rpoplpush: false,
+        }
+    }
+
+    pub fn source(&self) -> &Bytes {
+        &self.source
+    }
+
+    pub fn destination(&self) -> &Bytes {
+        &self.destination
+    }
+
+    pub fn rpoplpush(&self) -> bool {
+        self.rpoplpush
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let source = parse.next_bytes()?;
+        let destination = parse.next_bytes()?;
+        let from = parse_end(parse)?;
+        let to = parse_end(parse)?;
+        Ok(Self::new(source, destination, from, to))
+    }
+
+    pub(crate) fn parse_rpoplpush(parse: &mut Parse) -> Result<Self, ParseError> {
+        let source = parse.next_bytes()?;
+        let destination = parse.next_bytes()?;
+        Ok(Self {
+            rpoplpush: true,
+            ..Self::new(source, destination, End::Right, End::Left)
+        })
+    }
+
+    /// Replies with the moved element, or null when the source is missing.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.list_move(&self.source, &self.destination, self.from, self.to) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+fn parse_end(parse: &mut Parse) -> Result<End, ParseError> {
+    match &parse.next_string()?.to_uppercase()[..] {
+        "LEFT" => Ok(End::Left),
+        "RIGHT" => Ok(End::Right),
+        _ => Err(CommandError::Syntax.into()),
+    }
+}
@@ -0,0 +1,37 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::frame::Frame;
+
+const BANNER: &str = r"
+ ___ _____   __  ___        _ _
+|   \_ _\ \ / / | _ \___ __| (_)___
+| |) | | \ V /  |   / -_) _` | (_-<
+|___/___| |_|   |_|_\___\__,_|_/__/
+";
+
+/// Answers with a banner and the server's version. `VERSION` is accepted for
+/// compatibility, but there is only one banner.
+#[derive(Debug)]
+pub struct Lolwut;
+
+impl Lolwut {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        if parse.has_remaining() {
+            if parse.next_string()?.to_uppercase() != "VERSION" {
+                return Err(CommandError::Syntax.into());
+            }
+            parse.next_int()?;
+        }
+
+        Ok(Self)
+    }
+
+    pub fn apply(self) -> Frame {
+        let art = format!(
+            "{BANNER}\n{} ver. {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+        Frame::Bulk(art.into())
+    }
+}
@@ -0,0 +1,173 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+const RANK_ZERO: &str = "RANK can't be zero: use 1 to start from the first match, 2 from the \
+    second ... or use negative to start from the last match";
+
+#[derive(Debug)]
+pub struct LPos {
+    key: Bytes,
+    element: Bytes,
+    rank: i64,
+    count: Option<usize>,
+    max_len: usize,
+}
+
+impl LPos {
+    pub fn new(key: impl Into<Bytes>, element: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            element,
+            rank: 1,
+            count: None,
+            max_len: 0,
+        }
+    }
+
+    pub fn rank(mut self, rank: i64) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let mut lpos = Self::new(parse.next_bytes()?, parse.next_bytes()?);
+
+        while parse.has_remaining() {
+            match &parse.next_string()?.to_uppercase()[..] {
+                "RANK" => {
+                    lpos.rank = match parse.next_int()? {
+                        0 | i64::MIN => return Err(anyhow!(RANK_ZERO).into()),
+                        rank => rank,
+                    };
+                }
+                "COUNT" => {
+                    let count = usize::try_from(parse.next_int()?)
+                        .map_err(|_| anyhow!("COUNT can't be negative"))?;
+                    lpos.count = Some(count);
+                }
+                "MAXLEN" => {
+                    lpos.max_len = usize::try_from(parse.next_int()?)
+                        .map_err(|_| anyhow!("MAXLEN can't be negative"))?;
+                }
+                _ => return Err(CommandError::Syntax.into()),
+            }
+        }
+
+        Ok(lpos)
+    }
+
+    /// Without `COUNT` the reply is the index of the match `RANK` picks, or
+    /// null. With it the reply is an array of up to that many indices, 0
+    /// meaning every match.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let limit = match self.count {
+            Some(0) => usize::MAX,
+            Some(count) => count,
+            None => 1,
+        };
+        let positions =
+            match db.list_positions(&self.key, &self.element, self.rank, limit, self.max_len) {
+                Ok(positions) => positions,
+                Err(err) => return Frame::Error(err.to_string()),
+            };
+
+        if self.count.is_none() {
+            return positions
+                .first()
+                .map_or(Frame::Null, |&index| Frame::Integer(index as i64));
+        }
+        Frame::Array(
+            positions
+                .into_iter()
+                .map(|index| Frame::Integer(index as i64))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{LPos, Push};
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+
+    fn db_with(values: &[&str]) -> ShardedDb {
+        let mut db = ShardedDb::new();
+        let values = values
+            .iter()
+            .map(|value| value.to_string().into())
+            .collect();
+        Push::new("list", End::Right, values).apply(&mut db);
+        db
+    }
+
+    fn indices(indices: &[i64]) -> Frame {
+        Frame::Array(indices.iter().copied().map(Frame::Integer).collect())
+    }
+
+    #[test]
+    fn apply_forward_search() {
+        // Arrange
+        let db = db_with(&["a", "b", "c", "b", "b"]);
+
+        // Act
+        let first = LPos::new("list", "b".into()).apply(&db);
+        let second = LPos::new("list", "b".into()).rank(2).apply(&db);
+        let missing = LPos::new("list", "z".into()).apply(&db);
+
+        // Assert
+        assert_eq!(first, Frame::Integer(1));
+        assert_eq!(second, Frame::Integer(3));
+        assert_eq!(missing, Frame::Null);
+    }
+
+    #[test]
+    fn apply_negative_rank_searches_from_tail() {
+        // Arrange
+        let db = db_with(&["a", "b", "c", "b", "b"]);
+
+        // Act
+        let last = LPos::new("list", "b".into()).rank(-1).apply(&db);
+        let from_tail = LPos::new("list", "b".into()).rank(-2).count(2).apply(&db);
+
+        // Assert
+        assert_eq!(last, Frame::Integer(4));
+        assert_eq!(from_tail, indices(&[3, 1]));
+    }
+
+    #[test]
+    fn apply_count_zero_returns_all_matches() {
+        // Arrange
+        let db = db_with(&["b", "a", "b", "c", "b"]);
+
+        // Act
+        let all = LPos::new("list", "b".into()).count(0).apply(&db);
+        let after_first = LPos::new("list", "b".into()).rank(2).count(0).apply(&db);
+        let within = LPos::new("list", "b".into()).count(0).max_len(3).apply(&db);
+        let none = LPos::new("list", "z".into()).count(0).apply(&db);
+
+        // Assert
+        assert_eq!(all, indices(&[0, 2, 4]));
+        assert_eq!(after_first, indices(&[2, 4]));
+        assert_eq!(within, indices(&[0, 2]));
+        assert_eq!(none, indices(&[]));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct LRem {
+    key: Bytes,
+    count: i64,
+    value: Bytes,
+}
+
+impl LRem {
+    pub fn new(key: impl Into<Bytes>, count: i64, value: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            count,
+            value,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let count = parse.next_int()?;
+        let value = parse.next_bytes()?;
+        Ok(Self { key, count, value })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.list_remove(&self.key, self.count, &self.value) {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
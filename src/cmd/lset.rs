@@ -0,0 +1,39 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct LSet {
+    key: Bytes,
+    index: i64,
+    value: Bytes,
+}
+
+impl LSet {
+    pub fn new(key: impl Into<Bytes>, index: i64, value: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            index,
+            value,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let index = parse.next_int()?;
+        let value = parse.next_bytes()?;
+        Ok(Self { key, index, value })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.list_set(&self.key, self.index, self.value) {
+            Ok(()) => Frame::ok(),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub enum Memory {
+    Usage { key: Bytes },
+}
+
+impl Memory {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "usage" => {
+                let key = parse.next_bytes()?;
+                // sizes are exact, so the sample count only has to be well-formed
+                if parse.has_remaining() {
+                    if parse.next_string()?.to_uppercase() != "SAMPLES" {
+                        return Err(CommandError::Syntax.into());
+                    }
+                    parse.next_int()?;
+                }
+                Ok(Memory::Usage { key })
+            }
+            _ => Err(CommandError::unknown_subcommand("MEMORY", subcommand).into()),
+        }
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match self {
+            Memory::Usage { key } => db
+                .memory_usage(&key)
+                .map_or(Frame::Null, |size| Frame::Integer(size as i64)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Memory, Push};
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+    use bytes::Bytes;
+
+    fn usage(db: &ShardedDb, key: &'static str) -> Frame {
+        Memory::Usage { key: key.into() }.apply(db)
+    }
+
+    #[test]
+    fn apply_usage_grows_with_value() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("small", Bytes::from_static(b"x"));
+        db.insert("large", Bytes::from(vec![b'x'; 1024]));
+        Push::new("list", End::Right, vec!["x".into(); 16]).apply(&mut db);
+
+        // Act
+        let small = usage(&db, "small");
+        let large = usage(&db, "large");
+        let list = usage(&db, "list");
+        let missing = usage(&db, "missing");
+
+        // Assert
+        let (Frame::Integer(small), Frame::Integer(large), Frame::Integer(list)) =
+            (small, large, list)
+        else {
+            panic!("expected integer replies");
+        };
+        assert!(large > small);
+        assert!(list > small);
+        assert_eq!(missing, Frame::Null);
+    }
+}
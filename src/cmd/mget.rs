@@ -0,0 +1,35 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<Bytes>,
+}
+
+impl MGet {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Self { keys }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let keys = parse.remaining_bytes(1)?;
+
+        Ok(Self { keys })
+    }
+
+    /// Keys that are missing or not strings come back as null.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let values = self
+            .keys
+            .iter()
+            .map(|key| match db.get(key) {
+                Ok(Some(value)) => Frame::Bulk(value),
+                _ => Frame::Null,
+            })
+            .collect();
+
+        Frame::Array(values)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::dump;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// Bulk import of entries in the `MDUMP` format.
+#[derive(Debug)]
+pub struct MLoad {
+    payload: Bytes,
+}
+
+impl MLoad {
+    pub fn new(payload: Bytes) -> Self {
+        Self { payload }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let payload = parse.next_bytes()?;
+        Ok(Self { payload })
+    }
+
+    /// Nothing is loaded unless the whole payload is well formed and every
+    /// string fits `max_value_size`. Replies with the number of keys loaded.
+    pub fn apply(self, db: &mut ShardedDb, max_value_size: usize) -> Frame {
+        let entries = match dump::deserialize_entries(&self.payload) {
+            Ok(entries) => entries,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+        match db.load(entries, max_value_size) {
+            Ok(loaded) => Frame::Integer(loaded as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+/// Bulk export of every key, without TTLs.
+#[derive(Debug, Default)]
+pub struct MDump;
+
+impl MDump {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let mut payload = Vec::new();
+        db.for_each_entry(|key, value| dump::serialize_entry(&mut payload, key, value));
+        Frame::Bulk(payload.into())
+    }
+}
@@ -0,0 +1,743 @@
+mod append;
+mod cas;
+mod client;
+mod commands;
+mod config;
+mod custom;
+mod debug;
+mod del;
+mod dump;
+mod error;
+mod expire;
+mod flush;
+mod get;
+mod getex;
+mod hello;
+mod hexpire;
+mod hget;
+mod hgetall;
+mod hrandfield;
+mod hset;
+mod incrbyfloat;
+mod info;
+mod latency;
+mod linsert;
+mod lmove;
+mod lolwut;
+mod lpos;
+mod lrem;
+mod lset;
+mod memory;
+mod mget;
+mod mload;
+mod monitor;
+mod multi;
+mod object;
+mod parse;
+mod ping;
+mod pop;
+mod publish;
+mod push;
+mod quit;
+mod sadd;
+mod scan;
+mod select;
+mod set;
+mod setop;
+mod setrange;
+mod sintercard;
+mod sismember;
+mod smembers;
+mod sort;
+mod srandmember;
+mod srem;
+mod subscribe;
+mod swapdb;
+pub(crate) mod table;
+mod time;
+mod ttl;
+mod unknown;
+mod waitaof;
+mod watch;
+mod xadd;
+mod xlen;
+mod xrange;
+
+pub use append::Append;
+pub use cas::Cas;
+pub use client::Client;
+pub use commands::Commands;
+pub use config::Config;
+pub(crate) use custom::parse_args;
+pub use custom::{CommandHandler, HandlerFuture, Registry};
+pub use debug::Debug;
+pub use del::Del;
+pub use dump::Dump;
+pub use error::CommandError;
+pub use expire::Expire;
+pub use flush::Flush;
+pub use get::Get;
+pub use getex::GetEx;
+pub use hello::Hello;
+pub use hexpire::{HExpire, HPersist, HTtl};
+pub use hget::HGet;
+pub use hgetall::HGetAll;
+pub use hrandfield::HRandField;
+pub use hset::HSet;
+pub use incrbyfloat::{HIncrByFloat, IncrByFloat};
+pub use info::Info;
+pub use latency::Latency;
+pub use linsert::LInsert;
+pub use lmove::LMove;
+pub use lolwut::Lolwut;
+pub use lpos::LPos;
+pub use lrem::LRem;
+pub use lset::LSet;
+pub use memory::Memory;
+pub use mget::MGet;
+pub use mload::{MDump, MLoad};
+pub use monitor::Monitor;
+pub use multi::{Discard, Exec, Multi};
+pub use object::Object;
+pub use parse::ParseError;
+pub use ping::Ping;
+pub use pop::Pop;
+pub use publish::Publish;
+pub use push::Push;
+pub use quit::Quit;
+pub use sadd::SAdd;
+pub use scan::Scan;
+pub use select::Select;
+pub use set::Set;
+pub use setop::SetOperation;
+pub use setrange::SetRange;
+pub use sintercard::SInterCard;
+pub use sismember::{SIsMember, SMIsMember};
+pub use smembers::SMembers;
+pub use sort::Sort;
+pub use srandmember::SRandMember;
+pub use srem::SRem;
+pub use subscribe::{Kind, Subscribe, Unsubscribe};
+pub use swapdb::SwapDb;
+pub use time::Time;
+pub use ttl::Ttl;
+pub use unknown::Unknown;
+pub use waitaof::WaitAof;
+pub use watch::{Unwatch, Watch};
+pub use xadd::XAdd;
+pub use xlen::XLen;
+pub use xrange::XRange;
+
+use crate::cmd::parse::Parse;
+use crate::db::{End, SetOp};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub enum Command {
+    Append(Append),
+    Cas(Cas),
+    Client(Client),
+    Commands(Commands),
+    Config(Config),
+    Debug(Debug),
+    Del(Del),
+    Discard(Discard),
+    Dump(Dump),
+    Exec(Exec),
+    Expire(Expire),
+    Flush(Flush),
+    Get(Get),
+    GetEx(GetEx),
+    Hello(Hello),
+    HExpire(HExpire),
+    HGet(HGet),
+    HGetAll(HGetAll),
+    HIncrByFloat(HIncrByFloat),
+    HPersist(HPersist),
+    HRandField(HRandField),
+    HSet(HSet),
+    HTtl(HTtl),
+    IncrByFloat(IncrByFloat),
+    Info(Info),
+    Latency(Latency),
+    LInsert(LInsert),
+    LMove(LMove),
+    Lolwut(Lolwut),
+    LPos(LPos),
+    LRem(LRem),
+    LSet(LSet),
+    Memory(Memory),
+    MDump(MDump),
+    MGet(MGet),
+    MLoad(MLoad),
+    Monitor(Monitor),
+    Multi(Multi),
+    Object(Object),
+    Ping(Ping),
+    Pop(Pop),
+    Publish(Publish),
+    Push(Push),
+    Quit(Quit),
+    SAdd(SAdd),
+    Scan(Scan),
+    Select(Select),
+    Set(Set),
+    SetOperation(SetOperation),
+    SetRange(SetRange),
+    SInterCard(SInterCard),
+    SIsMember(SIsMember),
+    SMembers(SMembers),
+    SMIsMember(SMIsMember),
+    Sort(Sort),
+    SRandMember(SRandMember),
+    SRem(SRem),
+    Subscribe(Subscribe),
+    SwapDb(SwapDb),
+    Time(Time),
+    Ttl(Ttl),
+    Unknown(Unknown),
+    Unsubscribe(Unsubscribe),
+    Unwatch(Unwatch),
+    WaitAof(WaitAof),
+    Watch(Watch),
+    XAdd(XAdd),
+    XLen(XLen),
+    XRange(XRange),
+}
+
+impl Command {
+    pub fn from_frame(frame: Frame) -> Result<Self, ParseError> {
+        let mut parse = Parse::new(frame)?;
+        let given_name = parse.next_string()?;
+        let name = given_name.to_lowercase();
+
+        let command = match &name[..] {
+            "append" => Append::parse_frames(&mut parse).map(Command::Append),
+            "cas" => Cas::parse_frames(&mut parse).map(Command::Cas),
+            "client" => Client::parse_frames(&mut parse).map(Command::Client),
+            "command" => Commands::parse_frames(&mut parse).map(Command::Commands),
+            "config" => Config::parse_frames(&mut parse).map(Command::Config),
+            "debug" => Debug::parse_frames(&mut parse).map(Command::Debug),
+            "del" => Del::parse_frames(&mut parse, false).map(Command::Del),
+            "discard" => Discard::parse_frames(&mut parse).map(Command::Discard),
+            "dump" => Dump::parse_frames(&mut parse).map(Command::Dump),
+            "exec" => Exec::parse_frames(&mut parse).map(Command::Exec),
+            "expire" => Expire::parse_frames(&mut parse).map(Command::Expire),
+            "flushall" => Flush::parse_frames(&mut parse, true).map(Command::Flush),
+            "flushdb" => Flush::parse_frames(&mut parse, false).map(Command::Flush),
+            "get" => Get::parse_frames(&mut parse).map(Command::Get),
+            "getex" => GetEx::parse_frames(&mut parse).map(Command::GetEx),
+            "memory" => Memory::parse_frames(&mut parse).map(Command::Memory),
+            "mdump" => MDump::parse_frames(&mut parse).map(Command::MDump),
+            "mget" => MGet::parse_frames(&mut parse).map(Command::MGet),
+            "mload" => MLoad::parse_frames(&mut parse).map(Command::MLoad),
+            "monitor" => Monitor::parse_frames(&mut parse).map(Command::Monitor),
+            "multi" => Multi::parse_frames(&mut parse).map(Command::Multi),
+            "object" => Object::parse_frames(&mut parse).map(Command::Object),
+            "ping" => Ping::parse_frames(&mut parse).map(Command::Ping),
+            "hello" => Hello::parse_frames(&mut parse).map(Command::Hello),
+            "hexpire" => HExpire::parse_frames(&mut parse).map(Command::HExpire),
+            "hget" => HGet::parse_frames(&mut parse).map(Command::HGet),
+            "hgetall" => HGetAll::parse_frames(&mut parse).map(Command::HGetAll),
+            "hincrbyfloat" => HIncrByFloat::parse_frames(&mut parse).map(Command::HIncrByFloat),
+            "hpersist" => HPersist::parse_frames(&mut parse).map(Command::HPersist),
+            "hrandfield" => HRandField::parse_frames(&mut parse).map(Command::HRandField),
+            "hset" => HSet::parse_frames(&mut parse).map(Command::HSet),
+            "httl" => HTtl::parse_frames(&mut parse).map(Command::HTtl),
+            "incrbyfloat" => IncrByFloat::parse_frames(&mut parse).map(Command::IncrByFloat),
+            "info" => Info::parse_frames(&mut parse).map(Command::Info),
+            "latency" => Latency::parse_frames(&mut parse).map(Command::Latency),
+            "linsert" => LInsert::parse_frames(&mut parse).map(Command::LInsert),
+            "lmove" => LMove::parse_frames(&mut parse).map(Command::LMove),
+            "lolwut" => Lolwut::parse_frames(&mut parse).map(Command::Lolwut),
+            "lpos" => LPos::parse_frames(&mut parse).map(Command::LPos),
+            "lrem" => LRem::parse_frames(&mut parse).map(Command::LRem),
+            "lset" => LSet::parse_frames(&mut parse).map(Command::LSet),
+            "lpop" => Pop::parse_frames(&mut parse, End::Left).map(Command::Pop),
+            "rpop" => Pop::parse_frames(&mut parse, End::Right).map(Command::Pop),
+            "rpoplpush" => LMove::parse_rpoplpush(&mut parse).map(Command::LMove),
+            "lpush" => Push::parse_frames(&mut parse, End::Left).map(Command::Push),
+            "rpush" => Push::parse_frames(&mut parse, End::Right).map(Command::Push),
+            "psubscribe" => {
+                Subscribe::parse_frames(&mut parse, Kind::Pattern).map(Command::Subscribe)
+            }
+            "publish" => Publish::parse_frames(&mut parse).map(Command::Publish),
+            "punsubscribe" => {
+                Unsubscribe::parse_frames(&mut parse, Kind::Pattern).map(Command::Unsubscribe)
+            }
+            "quit" => Quit::parse_frames(&mut parse).map(Command::Quit),
+            "sadd" => SAdd::parse_frames(&mut parse).map(Command::SAdd),
+            "scan" => Scan::parse_frames(&mut parse).map(Command::Scan),
+            "select" => Select::parse_frames(&mut parse).map(Command::Select),
+            "set" => Set::parse_frames(&mut parse).map(Command::Set),
+            "setrange" => SetRange::parse_frames(&mut parse).map(Command::SetRange),
+            "sdiff" => SetOperation::parse_frames(&mut parse, SetOp::Diff, false)
+                .map(Command::SetOperation),
+            "sdiffstore" => {
+                SetOperation::parse_frames(&mut parse, SetOp::Diff, true).map(Command::SetOperation)
+            }
+            "sintercard" => SInterCard::parse_frames(&mut parse).map(Command::SInterCard),
+            "sinter" => SetOperation::parse_frames(&mut parse, SetOp::Inter, false)
+                .map(Command::SetOperation),
+            "sinterstore" => SetOperation::parse_frames(&mut parse, SetOp::Inter, true)
+                .map(Command::SetOperation),
+            "sunion" => SetOperation::parse_frames(&mut parse, SetOp::Union, false)
+                .map(Command::SetOperation),
+            "sunionstore" => SetOperation::parse_frames(&mut parse, SetOp::Union, true)
+                .map(Command::SetOperation),
+            "sismember" => SIsMember::parse_frames(&mut parse).map(Command::SIsMember),
+            "smembers" => SMembers::parse_frames(&mut parse).map(Command::SMembers),
+            "smismember" => SMIsMember::parse_frames(&mut parse).map(Command::SMIsMember),
+            "sort" => Sort::parse_frames(&mut parse).map(Command::Sort),
+            "srandmember" => SRandMember::parse_frames(&mut parse).map(Command::SRandMember),
+            "srem" => SRem::parse_frames(&mut parse).map(Command::SRem),
+            "subscribe" => {
+                Subscribe::parse_frames(&mut parse, Kind::Channel).map(Command::Subscribe)
+            }
+            "swapdb" => SwapDb::parse_frames(&mut parse).map(Command::SwapDb),
+            "time" => Time::parse_frames(&mut parse).map(Command::Time),
+            "ttl" => Ttl::parse_frames(&mut parse).map(Command::Ttl),
+            "unlink" => Del::parse_frames(&mut parse, true).map(Command::Del),
+            "unsubscribe" => {
+                Unsubscribe::parse_frames(&mut parse, Kind::Channel).map(Command::Unsubscribe)
+            }
+            "unwatch" => Unwatch::parse_frames(&mut parse).map(Command::Unwatch),
+            "waitaof" => WaitAof::parse_frames(&mut parse).map(Command::WaitAof),
+            "watch" => Watch::parse_frames(&mut parse).map(Command::Watch),
+            "xadd" => XAdd::parse_frames(&mut parse).map(Command::XAdd),
+            "xlen" => XLen::parse_frames(&mut parse).map(Command::XLen),
+            "xrange" => XRange::parse_frames(&mut parse).map(Command::XRange),
+            _ => {
+                let args = parse.remaining_bytes(0)?;
+                return Ok(Command::Unknown(Unknown::new(given_name, args)));
+            }
+        };
+
+        command
+            .and_then(|command| parse.finish().map(|_| command))
+            .map_err(|err| match err {
+                ParseError::EndOfStream | ParseError::Trailing => {
+                    CommandError::WrongArity(name).into()
+                }
+                err => err,
+            })
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Command::Append(_) => "append",
+            Command::Cas(_) => "cas",
+            Command::Client(_) => "client",
+            Command::Commands(_) => "command",
+            Command::Config(_) => "config",
+            Command::Debug(_) => "debug",
+            Command::Del(cmd) if cmd.lazy() => "unlink",
+            Command::Del(_) => "del",
+            Command::Discard(_) => "discard",
+            Command::Dump(_) => "dump",
+            Command::Exec(_) => "exec",
+            Command::Expire(_) => "expire",
+            Command::Flush(cmd) if cmd.all() => "flushall",
+            Command::Flush(_) => "flushdb",
+            Command::Get(_) => "get",
+            Command::GetEx(_) => "getex",
+            Command::Hello(_) => "hello",
+            Command::HExpire(_) => "hexpire",
+            Command::HGet(_) => "hget",
+            Command::HGetAll(_) => "hgetall",
+            Command::HIncrByFloat(_) => "hincrbyfloat",
+            Command::HPersist(_) => "hpersist",
+            Command::HRandField(_) => "hrandfield",
+            Command::HSet(_) => "hset",
+            Command::HTtl(_) => "httl",
+            Command::IncrByFloat(_) => "incrbyfloat",
+            Command::Info(_) => "info",
+            Command::Latency(_) => "latency",
+            Command::LInsert(_) => "linsert",
+            Command::LMove(cmd) if cmd.rpoplpush() => "rpoplpush",
+            Command::LMove(_) => "lmove",
+            Command::Lolwut(_) => "lolwut",
+            Command::LPos(_) => "lpos",
+            Command::LRem(_) => "lrem",
+            Command::LSet(_) => "lset",
+            Command::Memory(_) => "memory",
+            Command::MDump(_) => "mdump",
+            Command::MGet(_) => "mget",
+            Command::MLoad(_) => "mload",
+            Command::Monitor(_) => "monitor",
+            Command::Multi(_) => "multi",
+            Command::Object(_) => "object",
+            Command::Ping(_) => "ping",
+            Command::Pop(cmd) if cmd.end() == End::Left => "lpop",
+            Command::Pop(_) => "rpop",
+            Command::Publish(_) => "publish",
+            Command::Push(cmd) if cmd.end() == End::Left => "lpush",
+            Command::Push(_) => "rpush",
+            Command::Quit(_) => "quit",
+            Command::SAdd(_) => "sadd",
+            Command::Scan(_) => "scan",
+            Command::Select(_) => "select",
+            Command::Set(_) => "set",
+            Command::SetOperation(cmd) => match (cmd.op(), cmd.destination().is_some()) {
+                (SetOp::Diff, false) => "sdiff",
+                (SetOp::Diff, true) => "sdiffstore",
+                (SetOp::Inter, false) => "sinter",
+                (SetOp::Inter, true) => "sinterstore",
+                (SetOp::Union, false) => "sunion",
+                (SetOp::Union, true) => "sunionstore",
+            },
+            Command::SetRange(_) => "setrange",
+            Command::SInterCard(_) => "sintercard",
+            Command::SIsMember(_) => "sismember",
+            Command::SMembers(_) => "smembers",
+            Command::SMIsMember(_) => "smismember",
+            Command::Sort(_) => "sort",
+            Command::SRandMember(_) => "srandmember",
+            Command::SRem(_) => "srem",
+            Command::Subscribe(cmd) if cmd.kind() == Kind::Pattern => "psubscribe",
+            Command::Subscribe(_) => "subscribe",
+            Command::SwapDb(_) => "swapdb",
+            Command::Time(_) => "time",
+            Command::Ttl(_) => "ttl",
+            Command::Unknown(cmd) => cmd.name(),
+            Command::Unsubscribe(cmd) if cmd.kind() == Kind::Pattern => "punsubscribe",
+            Command::Unsubscribe(_) => "unsubscribe",
+            Command::Unwatch(_) => "unwatch",
+            Command::WaitAof(_) => "waitaof",
+            Command::Watch(_) => "watch",
+            Command::XAdd(_) => "xadd",
+            Command::XLen(_) => "xlen",
+            Command::XRange(_) => "xrange",
+        }
+    }
+
+    /// Whether the command can change the dataset, and so is refused when the
+    /// server is read-only. Read from its `write` flag in the command table.
+    pub fn is_write(&self) -> bool {
+        self.has_flag(table::Flags::WRITE)
+    }
+
+    /// Whether the command can add data, and so is refused once `maxmemory`
+    /// is reached and eviction can't make room. Read from its `denyoom` flag
+    /// in the command table.
+    pub fn may_grow(&self) -> bool {
+        self.has_flag(table::Flags::DENYOOM)
+    }
+
+    fn has_flag(&self, flag: table::Flags) -> bool {
+        table::lookup(self.get_name()).is_some_and(|spec| spec.flags.contains(flag))
+    }
+}
+
+/// The reply to a HELP subcommand: `lines` as simple strings.
+pub(crate) fn help(lines: &'static [&'static str]) -> Frame {
+    Frame::Array(
+        lines
+            .iter()
+            .map(|line| Frame::Simple(Bytes::from_static(line.as_bytes())))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Command;
+    use crate::frame::Frame;
+    use bytes::Bytes;
+    use claims::{assert_err, assert_ok};
+    use proptest::prelude::{any, prop_oneof, Just, Strategy};
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    fn command_frame(parts: &[&str]) -> Frame {
+        Frame::Array(
+            parts
+                .iter()
+                .map(|part| Frame::Bulk(part.to_string().into()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn from_frame_set_valid() {
+        // Arrange
+        let frame = command_frame(&["SET", "key", "value"]);
+
+        // Act
+        let command = Command::from_frame(frame);
+
+        // Assert
+        assert_ok!(&command);
+        if let Ok(Command::Set(cmd)) = command {
+            assert_eq!(cmd.key(), "key");
+            assert_eq!(cmd.value(), "value");
+        } else {
+            panic!("Expected Command::Set variant");
+        }
+    }
+
+    #[test]
+    fn from_frame_get_too_many_arguments_invalid() {
+        // Arrange
+        let frame = command_frame(&["GET", "key", "extra"]);
+
+        // Act
+        let command = Command::from_frame(frame);
+
+        // Assert
+        assert_err!(&command);
+        assert_eq!(
+            command.unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'get' command"
+        );
+    }
+
+    #[test]
+    fn from_frame_variadic_without_keys_wrong_arity() {
+        // Arrange
+        let cases = [
+            (&["DEL"][..], "del"),
+            (&["MGET"][..], "mget"),
+            (&["SUNION"][..], "sunion"),
+            (&["SADD", "key"][..], "sadd"),
+            (&["RPUSH", "key"][..], "rpush"),
+        ];
+
+        for (parts, name) in cases {
+            // Act
+            let command = Command::from_frame(command_frame(parts));
+
+            // Assert
+            assert_eq!(
+                command.unwrap_err().to_string(),
+                format!("ERR wrong number of arguments for '{name}' command")
+            );
+        }
+    }
+
+    #[test]
+    fn is_write_classifies_commands() {
+        // Arrange
+        let cases = [
+            (&["SET", "k", "v"][..], true),
+            (&["DEL", "k"][..], true),
+            (&["LPOP", "k"][..], true),
+            (&["SUNIONSTORE", "d", "k"][..], true),
+            (&["FLUSHALL"][..], true),
+            (&["GET", "k"][..], false),
+            (&["SUNION", "k"][..], false),
+            (&["SMEMBERS", "k"][..], false),
+            (&["INFO"][..], false),
+            (&["PING"][..], false),
+        ];
+
+        for (parts, expected) in cases {
+            // Act
+            let command = Command::from_frame(command_frame(parts)).unwrap();
+
+            // Assert
+            assert_eq!(command.is_write(), expected, "{parts:?}");
+        }
+    }
+
+    #[test]
+    fn may_grow_follows_denyoom_flag() {
+        // Arrange
+        let cases = [
+            (&["SET", "k", "v"][..], true),
+            (&["RPOPLPUSH", "s", "d"][..], true),
+            (&["SINTERSTORE", "d", "k"][..], true),
+            (&["DEL", "k"][..], false),
+            (&["EXPIRE", "k", "10"][..], false),
+            (&["SINTER", "k"][..], false),
+        ];
+
+        for (parts, expected) in cases {
+            // Act
+            let command = Command::from_frame(command_frame(parts)).unwrap();
+
+            // Assert
+            assert_eq!(command.may_grow(), expected, "{parts:?}");
+        }
+    }
+
+    #[test]
+    fn from_frame_unknown_command() {
+        // Arrange
+        let frame = command_frame(&["FOO", "bar"]);
+
+        // Act
+        let command = Command::from_frame(frame);
+
+        // Assert
+        assert_ok!(&command);
+        let Ok(Command::Unknown(cmd)) = command else {
+            panic!("Expected Command::Unknown variant");
+        };
+        assert_eq!(
+            cmd.apply(),
+            Frame::Error("ERR unknown command 'FOO', with args beginning with: 'bar'".to_string())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn from_frame_set_any_key_and_value(name in name_strategy("set"), key in key_strategy(), value in value_strategy()) {
+            // Arrange
+            let frame = Frame::Array(vec![name, bulk(key.as_bytes()), Frame::Bulk(value.clone())]);
+
+            // Act
+            let command = Command::from_frame(frame);
+
+            // Assert
+            let Ok(Command::Set(cmd)) = command else {
+                panic!("Expected Command::Set variant, got {command:?}");
+            };
+            prop_assert_eq!(cmd.key(), &key);
+            prop_assert_eq!(cmd.value(), &value);
+        }
+
+        #[test]
+        fn from_frame_get_any_key(name in name_strategy("get"), key in key_strategy()) {
+            // Arrange
+            let frame = Frame::Array(vec![name, bulk(key.as_bytes())]);
+
+            // Act
+            let command = Command::from_frame(frame);
+
+            // Assert
+            let Ok(Command::Get(cmd)) = command else {
+                panic!("Expected Command::Get variant, got {command:?}");
+            };
+            prop_assert_eq!(cmd.key(), &key);
+        }
+
+        #[test]
+        fn from_frame_del_any_keys(name in name_strategy("del"), keys in proptest::collection::vec(key_strategy(), 1..8)) {
+            // Arrange
+            let mut parts = vec![name];
+            parts.extend(keys.iter().map(|key| bulk(key.as_bytes())));
+
+            // Act
+            let command = Command::from_frame(Frame::Array(parts));
+
+            // Assert
+            let Ok(Command::Del(cmd)) = command else {
+                panic!("Expected Command::Del variant, got {command:?}");
+            };
+            prop_assert_eq!(cmd.keys(), &keys[..]);
+        }
+
+        #[test]
+        fn from_frame_wrong_arity_invalid((name, frame) in wrong_arity_strategy()) {
+            // Act
+            let command = Command::from_frame(frame);
+
+            // Assert
+            let Err(err) = command else {
+                panic!("Expected an error, got {command:?}");
+            };
+            prop_assert_eq!(
+                err.to_string(),
+                format!("ERR wrong number of arguments for '{name}' command")
+            );
+        }
+
+        #[test]
+        fn from_frame_non_bulk_argument_invalid(frame in non_bulk_argument_strategy()) {
+            // Act
+            let command = Command::from_frame(frame);
+
+            // Assert
+            prop_assert!(command.is_err());
+        }
+    }
+
+    // ------------------------------------------------
+    // ------------------ Strategies ------------------
+    // ------------------------------------------------
+
+    fn bulk(bytes: &[u8]) -> Frame {
+        Frame::Bulk(Bytes::copy_from_slice(bytes))
+    }
+
+    /// `name` in any mix of cases, as a bulk or a simple string.
+    fn name_strategy(name: &'static str) -> impl Strategy<Value = Frame> {
+        (
+            proptest::collection::vec(any::<bool>(), name.len()),
+            any::<bool>(),
+        )
+            .prop_map(move |(upper, simple)| {
+                let name: String = name
+                    .chars()
+                    .zip(upper)
+                    .map(|(c, upper)| if upper { c.to_ascii_uppercase() } else { c })
+                    .collect();
+                if simple {
+                    Frame::Simple(name.into())
+                } else {
+                    Frame::Bulk(name.into())
+                }
+            })
+    }
+
+    fn key_strategy() -> impl Strategy<Value = String> {
+        any::<String>()
+    }
+
+    fn value_strategy() -> impl Strategy<Value = Bytes> {
+        proptest::collection::vec(any::<u8>(), 0..64).prop_map(Bytes::from)
+    }
+
+    /// GET without its key or with extra arguments, SET missing its key or
+    /// value, or DEL with no keys, paired with the name the error reports.
+    fn wrong_arity_strategy() -> impl Strategy<Value = (&'static str, Frame)> {
+        let args = |range| proptest::collection::vec(key_strategy(), range);
+        prop_oneof![
+            (Just("get"), args(0..1)),
+            (Just("get"), args(2..6)),
+            (Just("set"), args(0..2)),
+            (Just("del"), args(0..1)),
+        ]
+        .prop_flat_map(|(name, args)| {
+            name_strategy(name).prop_map(move |name_frame| {
+                let mut parts = vec![name_frame];
+                parts.extend(args.iter().map(|arg| bulk(arg.as_bytes())));
+                (name, Frame::Array(parts))
+            })
+        })
+    }
+
+    fn non_bulk_strategy() -> impl Strategy<Value = Frame> {
+        prop_oneof![
+            any::<i64>().prop_map(Frame::Integer),
+            Just(Frame::Null),
+            Just(Frame::Error("ERR".to_string())),
+            proptest::collection::vec(value_strategy().prop_map(Frame::Bulk), 0..3)
+                .prop_map(Frame::Array),
+        ]
+    }
+
+    /// A well-formed SET, GET or DEL with one part, the name included,
+    /// swapped for a frame that isn't a string.
+    fn non_bulk_argument_strategy() -> impl Strategy<Value = Frame> {
+        prop_oneof![
+            (key_strategy(), value_strategy()).prop_map(|(key, value)| vec![
+                bulk(b"SET"),
+                bulk(key.as_bytes()),
+                Frame::Bulk(value),
+            ]),
+            key_strategy().prop_map(|key| vec![bulk(b"GET"), bulk(key.as_bytes())]),
+            proptest::collection::vec(key_strategy(), 1..4).prop_map(|keys| {
+                let mut parts = vec![bulk(b"DEL")];
+                parts.extend(keys.iter().map(|key| bulk(key.as_bytes())));
+                parts
+            }),
+        ]
+        .prop_flat_map(|parts| (0..parts.len(), non_bulk_strategy(), Just(parts)))
+        .prop_map(|(index, replacement, mut parts)| {
+            parts[index] = replacement;
+            Frame::Array(parts)
+        })
+    }
+}
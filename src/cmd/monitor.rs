@@ -0,0 +1,25 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use crate::monitor::{Feed, Monitoring};
+
+#[derive(Debug, Default)]
+pub struct Monitor;
+
+impl Monitor {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    /// Every command the server receives is visible from here, so this is
+    /// refused unless the server was started with monitoring enabled.
+    pub fn apply(self, feed: &Feed, monitoring: &mut Monitoring, enabled: bool) -> Frame {
+        if !enabled {
+            return Frame::Error(
+                "ERR MONITOR is disabled, enable it in the server configuration".to_string(),
+            );
+        }
+
+        monitoring.start(feed);
+        Frame::ok()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use crate::transaction::Transaction;
+
+#[derive(Debug)]
+pub struct Multi;
+
+#[derive(Debug)]
+pub struct Exec;
+
+#[derive(Debug)]
+pub struct Discard;
+
+impl Multi {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    pub fn apply(self, transaction: &mut Transaction) -> Frame {
+        match transaction.begin() {
+            Ok(()) => Frame::ok(),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+impl Exec {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+}
+
+impl Discard {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    pub fn apply(self, transaction: &mut Transaction) -> Frame {
+        match transaction.discard() {
+            Ok(()) => Frame::ok(),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
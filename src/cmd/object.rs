@@ -0,0 +1,254 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::{help, CommandError};
+use crate::config::EvictionPolicy;
+use crate::db::{ShardedDb, Value};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub enum Object {
+    Encoding { key: Bytes },
+    RefCount { key: Bytes },
+    IdleTime { key: Bytes },
+    Freq { key: Bytes },
+    Help,
+}
+
+const HELP: &[&str] = &[
+    "OBJECT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "ENCODING <key>",
+    "    Return the kind of internal representation used in order to store the value",
+    "    associated with a <key>.",
+    "FREQ <key>",
+    "    Return the access frequency index of the <key>. The returned integer is",
+    "    proportional to the logarithm of the recent access frequency of the key.",
+    "IDLETIME <key>",
+    "    Return the idle time of the <key>, that is the approximated number of",
+    "    seconds elapsed since the last access to the key.",
+    "REFCOUNT <key>",
+    "    Return the number of references of the value associated with the specified",
+    "    <key>.",
+    "HELP",
+    "    Print this help.",
+];
+
+impl Object {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+
+        match &subcommand[..] {
+            "encoding" => Ok(Object::Encoding {
+                key: parse.next_bytes()?,
+            }),
+            "refcount" => Ok(Object::RefCount {
+                key: parse.next_bytes()?,
+            }),
+            "idletime" => Ok(Object::IdleTime {
+                key: parse.next_bytes()?,
+            }),
+            "freq" => Ok(Object::Freq {
+                key: parse.next_bytes()?,
+            }),
+            "help" => Ok(Object::Help),
+            _ => Err(CommandError::unknown_subcommand("OBJECT", subcommand).into()),
+        }
+    }
+
+    /// FREQ is only answered under an LFU `policy`, as the counter means
+    /// little otherwise.
+    pub fn apply(self, db: &ShardedDb, policy: EvictionPolicy) -> Frame {
+        match self {
+            Object::Encoding { key } => match db.inspect(&key, Value::encoding) {
+                Some(encoding) => Frame::Bulk(Bytes::from_static(encoding.as_bytes())),
+                None => no_such_key(),
+            },
+            // values are never shared between keys, so there is only ever one reference
+            Object::RefCount { key } => match db.idle_time(&key) {
+                Some(_) => Frame::Integer(1),
+                None => no_such_key(),
+            },
+            Object::IdleTime { key } => match db.idle_time(&key) {
+                Some(idle) => Frame::Integer(idle.as_secs() as i64),
+                None => no_such_key(),
+            },
+            Object::Freq { .. } if !policy.is_lfu() => Frame::Error(
+                "ERR An LFU maxmemory policy is not selected, access frequency not tracked. \
+                 Please note that when switching between policies at runtime LRU and LFU data \
+                 will take some time to adjust."
+                    .to_string(),
+            ),
+            Object::Freq { key } => match db.frequency(&key) {
+                Some(frequency) => Frame::Integer(i64::from(frequency)),
+                None => no_such_key(),
+            },
+            Object::Help => help(HELP),
+        }
+    }
+}
+
+fn no_such_key() -> Frame {
+    Frame::Error("ERR no such key".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clients::Clients;
+    use crate::cmd::{Client, Get, Object};
+    use crate::config::EvictionPolicy;
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+    use std::time::Duration;
+
+    fn idle_time(db: &ShardedDb, key: &'static str) -> Frame {
+        Object::IdleTime { key: key.into() }.apply(db, EvictionPolicy::default())
+    }
+
+    fn freq(db: &ShardedDb, key: &'static str) -> Frame {
+        Object::Freq { key: key.into() }.apply(db, EvictionPolicy::AllKeysLfu)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn apply_idle_time_grows_and_resets_after_get() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let idle_before_get = idle_time(&db, "key");
+        db.get("key").unwrap();
+        let idle_after_get = idle_time(&db, "key");
+
+        // Assert
+        assert_eq!(idle_before_get, Frame::Integer(5));
+        assert_eq!(idle_after_get, Frame::Integer(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn apply_idle_time_kept_by_get_under_client_no_touch() {
+        // Arrange
+        let clients = Clients::new();
+        let client = clients.register("127.0.0.1:5000".parse().unwrap());
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+        Client::NoTouch(true).apply(&client, &clients, &mut db);
+
+        // Act
+        tokio::time::advance(Duration::from_secs(5)).await;
+        Get::new("key").apply(&db);
+        let idle_after_get = idle_time(&db, "key");
+        Client::NoTouch(false).apply(&client, &clients, &mut db);
+        Get::new("key").apply(&db);
+        let idle_after_touching_get = idle_time(&db, "key");
+
+        // Assert
+        assert_eq!(idle_after_get, Frame::Integer(5));
+        assert_eq!(idle_after_touching_get, Frame::Integer(0));
+    }
+
+    #[test]
+    fn apply_help_lists_subcommands() {
+        // Act
+        let help = Object::Help.apply(&ShardedDb::new(), EvictionPolicy::default());
+
+        // Assert
+        let Frame::Array(lines) = help else {
+            panic!("Expected Frame::Array variant");
+        };
+        assert!(lines.contains(&Frame::Simple("IDLETIME <key>".into())));
+    }
+
+    #[test]
+    fn apply_idle_time_missing_key_error() {
+        // Arrange
+        let db = ShardedDb::new();
+
+        // Act
+        let frame = idle_time(&db, "missing");
+
+        // Assert
+        assert!(matches!(frame, Frame::Error(_)));
+    }
+
+    #[test]
+    fn apply_encoding_by_content() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("int", "-42".into());
+        db.insert("padded", "042".into());
+        db.insert("raw", "a".repeat(45).into());
+
+        // Act
+        let encodings: Vec<_> = ["int", "padded", "raw"]
+            .into_iter()
+            .map(|key| Object::Encoding { key: key.into() }.apply(&db, EvictionPolicy::default()))
+            .collect();
+
+        // Assert
+        assert_eq!(
+            encodings,
+            vec![
+                Frame::Bulk("int".into()),
+                Frame::Bulk("embstr".into()),
+                Frame::Bulk("raw".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_ref_count_existing_key() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        let frame = Object::RefCount { key: "key".into() }.apply(&db, EvictionPolicy::default());
+
+        // Assert
+        assert_eq!(frame, Frame::Integer(1));
+    }
+
+    #[test]
+    fn apply_freq_rises_with_access() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+        let initial = freq(&db, "key");
+
+        // Act
+        let mut samples = Vec::new();
+        for _ in 0..10 {
+            for _ in 0..100 {
+                db.get("key").unwrap();
+            }
+            samples.push(freq(&db, "key"));
+        }
+
+        // Assert
+        assert_eq!(initial, Frame::Integer(5));
+        let samples: Vec<i64> = samples
+            .into_iter()
+            .map(|frame| match frame {
+                Frame::Integer(freq) => freq,
+                frame => panic!("expected an integer, got {frame:?}"),
+            })
+            .collect();
+        assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(samples[9] > 5);
+    }
+
+    #[test]
+    fn apply_freq_missing_key_or_not_lfu_error() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        let missing = freq(&db, "missing");
+        let not_lfu = Object::Freq { key: "key".into() }.apply(&db, EvictionPolicy::AllKeysLru);
+
+        // Assert
+        assert_eq!(missing, Frame::Error("ERR no such key".to_string()));
+        assert!(matches!(not_lfu, Frame::Error(message) if message.contains("LFU")));
+    }
+}
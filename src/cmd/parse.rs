@@ -0,0 +1,120 @@
+use crate::cmd::CommandError;
+use crate::db::parse_float;
+use crate::frame::Frame;
+use crate::parse_int::parse_i64;
+use anyhow::anyhow;
+use bytes::Bytes;
+use std::vec;
+
+pub(crate) struct Parse {
+    parts: vec::IntoIter<Frame>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("protocol error; unexpected end of frame")]
+    EndOfStream,
+    #[error("protocol error; expected end of frame, but there was more")]
+    Trailing,
+    #[error(transparent)]
+    Command(#[from] CommandError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl Parse {
+    pub(crate) fn new(frame: Frame) -> Result<Self, ParseError> {
+        match frame {
+            Frame::Array(parts) => Ok(Self {
+                parts: parts.into_iter(),
+            }),
+            frame => Err(anyhow!("protocol error; expected array, got {:?}", frame).into()),
+        }
+    }
+
+    fn next(&mut self) -> Result<Frame, ParseError> {
+        self.parts.next().ok_or(ParseError::EndOfStream)
+    }
+
+    pub(crate) fn next_string(&mut self) -> Result<String, ParseError> {
+        match self.next()? {
+            Frame::Simple(content) | Frame::Bulk(content) => String::from_utf8(content.to_vec())
+                .map_err(|_| anyhow!("protocol error; invalid string").into()),
+            frame => Err(anyhow!(
+                "protocol error; expected simple frame or bulk frame, got {:?}",
+                frame
+            )
+            .into()),
+        }
+    }
+
+    pub(crate) fn next_bytes(&mut self) -> Result<Bytes, ParseError> {
+        match self.next()? {
+            Frame::Simple(content) | Frame::Bulk(content) => Ok(content),
+            frame => Err(anyhow!(
+                "protocol error; expected simple frame or bulk frame, got {:?}",
+                frame
+            )
+            .into()),
+        }
+    }
+
+    pub(crate) fn next_int(&mut self) -> Result<i64, ParseError> {
+        match self.next()? {
+            Frame::Integer(num) => Ok(num),
+            Frame::Simple(content) | Frame::Bulk(content) => {
+                parse_i64(&content).map_err(|_| CommandError::NotAnInteger.into())
+            }
+            _ => Err(CommandError::NotAnInteger.into()),
+        }
+    }
+
+    pub(crate) fn next_float(&mut self) -> Result<f64, ParseError> {
+        const MSG: &str = "value is not a valid float";
+
+        match self.next()? {
+            Frame::Integer(num) => Ok(num as f64),
+            Frame::Simple(content) | Frame::Bulk(content) => {
+                parse_float(&content).ok_or(anyhow!(MSG).into())
+            }
+            _ => Err(anyhow!(MSG).into()),
+        }
+    }
+
+    /// Every remaining element as a string, for commands taking any number of
+    /// keys. Fewer than `min` is `EndOfStream`, reported as a wrong arity.
+    pub(crate) fn remaining_strings(&mut self, min: usize) -> Result<Vec<String>, ParseError> {
+        if self.parts.len() < min {
+            return Err(ParseError::EndOfStream);
+        }
+        let mut strings = Vec::with_capacity(self.parts.len());
+        while self.has_remaining() {
+            strings.push(self.next_string()?);
+        }
+        Ok(strings)
+    }
+
+    /// `remaining_strings` for binary-safe arguments such as values and
+    /// members.
+    pub(crate) fn remaining_bytes(&mut self, min: usize) -> Result<Vec<Bytes>, ParseError> {
+        if self.parts.len() < min {
+            return Err(ParseError::EndOfStream);
+        }
+        let mut bytes = Vec::with_capacity(self.parts.len());
+        while self.has_remaining() {
+            bytes.push(self.next_bytes()?);
+        }
+        Ok(bytes)
+    }
+
+    pub(crate) fn has_remaining(&self) -> bool {
+        self.parts.len() > 0
+    }
+
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        match self.parts.next() {
+            None => Ok(()),
+            Some(_) => Err(ParseError::Trailing),
+        }
+    }
+}
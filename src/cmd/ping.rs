@@ -0,0 +1,42 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<Bytes>,
+}
+
+impl Ping {
+    pub fn new(message: Option<Bytes>) -> Self {
+        Self { message }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let message = if parse.has_remaining() {
+            Some(parse.next_bytes()?)
+        } else {
+            None
+        };
+
+        Ok(Self { message })
+    }
+
+    /// `PONG`, or the message echoed back as a bulk.
+    pub fn apply(self) -> Frame {
+        match self.message {
+            Some(message) => Frame::Bulk(message),
+            None => Frame::pong(),
+        }
+    }
+
+    /// A RESP2 connection in subscriber mode gets `["pong", message]` instead,
+    /// with an empty message when none was given, shaped like the pub/sub
+    /// messages around it.
+    pub fn apply_subscribed(self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"pong")),
+            Frame::Bulk(self.message.unwrap_or_default()),
+        ])
+    }
+}
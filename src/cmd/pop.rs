@@ -0,0 +1,116 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{End, ShardedDb};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Pop {
+    key: Bytes,
+    end: End,
+    count: Option<usize>,
+}
+
+impl Pop {
+    pub fn new(key: impl Into<Bytes>, end: End, count: Option<usize>) -> Self {
+        Self {
+            key: key.into(),
+            end,
+            count,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub fn end(&self) -> End {
+        self.end
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, end: End) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let count = if parse.has_remaining() {
+            let count = usize::try_from(parse.next_int()?)
+                .map_err(|_| anyhow!("value is out of range, must be positive"))?;
+            Some(count)
+        } else {
+            None
+        };
+
+        Ok(Self { key, end, count })
+    }
+
+    /// Without a count the reply is a single bulk, with one it is an array.
+    /// A missing key is null either way.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        let popped = db.list_pop(&self.key, self.end, self.count.unwrap_or(1));
+
+        match (popped, self.count) {
+            (Ok(None), _) => Frame::Null,
+            (Ok(Some(mut values)), None) => values.pop().map_or(Frame::Null, Frame::Bulk),
+            (Ok(Some(values)), Some(_)) => {
+                Frame::Array(values.into_iter().map(Frame::Bulk).collect())
+            }
+            (Err(err), _) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Pop, Push};
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+
+    fn db_with_list() -> ShardedDb {
+        let mut db = ShardedDb::new();
+        Push::new("list", End::Right, vec!["a".into(), "b".into(), "c".into()]).apply(&mut db);
+        db
+    }
+
+    #[test]
+    fn apply_without_count_single_bulk() {
+        // Arrange
+        let mut db = db_with_list();
+
+        // Act
+        let frame = Pop::new("list", End::Right, None).apply(&mut db);
+
+        // Assert
+        assert_eq!(frame, Frame::Bulk("c".into()));
+    }
+
+    #[test]
+    fn apply_with_count_array() {
+        // Arrange
+        let mut db = db_with_list();
+
+        // Act
+        let frame = Pop::new("list", End::Left, Some(5)).apply(&mut db);
+
+        // Assert
+        assert_eq!(
+            frame,
+            Frame::Array(vec![
+                Frame::Bulk("a".into()),
+                Frame::Bulk("b".into()),
+                Frame::Bulk("c".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn apply_missing_key_null() {
+        // Arrange
+        let mut db = ShardedDb::new();
+
+        // Act
+        let without_count = Pop::new("missing", End::Left, None).apply(&mut db);
+        let with_count = Pop::new("missing", End::Left, Some(2)).apply(&mut db);
+
+        // Assert
+        assert_eq!(without_count, Frame::Null);
+        assert_eq!(with_count, Frame::Null);
+    }
+}
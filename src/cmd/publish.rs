@@ -0,0 +1,31 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use crate::pubsub::PubSub;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Publish {
+    channel: String,
+    message: Bytes,
+}
+
+impl Publish {
+    pub fn new(channel: impl ToString, message: Bytes) -> Self {
+        Self {
+            channel: channel.to_string(),
+            message,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let channel = parse.next_string()?;
+        let message = parse.next_bytes()?;
+
+        Ok(Self { channel, message })
+    }
+
+    /// Replies with the number of exact and pattern subscribers reached.
+    pub fn apply(self, pubsub: &PubSub) -> Frame {
+        Frame::Integer(pubsub.publish(&self.channel, self.message) as i64)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{End, ShardedDb};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Push {
+    key: Bytes,
+    end: End,
+    values: Vec<Bytes>,
+}
+
+impl Push {
+    pub fn new(key: impl Into<Bytes>, end: End, values: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            end,
+            values,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub fn end(&self) -> End {
+        self.end
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, end: End) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let values = parse.remaining_bytes(1)?;
+
+        Ok(Self { key, end, values })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.list_push(&self.key, self.end, self.values) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+
+/// Asks the server to close the connection once the reply is written.
+#[derive(Debug)]
+pub struct Quit;
+
+impl Quit {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        // like Redis, any arguments are ignored
+        while parse.has_remaining() {
+            parse.next_bytes()?;
+        }
+        Ok(Self)
+    }
+
+    pub fn apply(self) -> Frame {
+        Frame::ok()
+    }
+}
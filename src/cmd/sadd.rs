@@ -0,0 +1,37 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SAdd {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl SAdd {
+    pub fn new(key: impl Into<Bytes>, members: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            members,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let members = parse.remaining_bytes(1)?;
+
+        Ok(Self { key, members })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.set_add(&self.key, self.members) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
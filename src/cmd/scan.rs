@@ -0,0 +1,286 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use crate::glob;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+const DEFAULT_COUNT: usize = 10;
+
+/// Walks the keyspace a shard at a time, each shard in the order of its keys'
+/// hashes. The cursor is the hash of the next key to visit, which also tells
+/// the shard it lives in, so keys coming and going between calls never shift
+/// the others: a key present for the whole iteration is returned. Each call
+/// walks about `COUNT` keys before handing a cursor back. `MATCH` and `TYPE`
+/// only filter what is returned.
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: usize,
+    type_name: Option<String>,
+}
+
+impl Scan {
+    pub fn new(cursor: u64) -> Self {
+        Self {
+            cursor,
+            pattern: None,
+            count: DEFAULT_COUNT,
+            type_name: None,
+        }
+    }
+
+    pub fn pattern(mut self, pattern: impl Into<Bytes>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn type_name(mut self, type_name: impl Into<String>) -> Self {
+        self.type_name = Some(type_name.into());
+        self
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let cursor = parse
+            .next_string()?
+            .parse()
+            .map_err(|_| anyhow!("invalid cursor"))?;
+        let mut scan = Self::new(cursor);
+
+        while parse.has_remaining() {
+            match &parse.next_string()?.to_uppercase()[..] {
+                "MATCH" => scan.pattern = Some(parse.next_bytes()?),
+                "COUNT" => {
+                    scan.count = usize::try_from(parse.next_int()?)
+                        .ok()
+                        .filter(|&count| count > 0)
+                        .ok_or(CommandError::Syntax)?;
+                }
+                "TYPE" => scan.type_name = Some(parse.next_string()?.to_lowercase()),
+                _ => return Err(CommandError::Syntax.into()),
+            }
+        }
+
+        Ok(scan)
+    }
+
+    /// Replies with the next cursor, 0 once every shard was walked, and the
+    /// keys found along the way.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let shards = db.num_shards() as u64;
+        let mut shard = self.cursor % shards;
+        let mut from = self.cursor;
+        let mut remaining = self.count;
+        let mut keys = Vec::new();
+
+        let next = loop {
+            let (walked, resume) = db
+                .scan_shard(shard as usize, from, remaining, |key, value| {
+                    let matches_pattern = self
+                        .pattern
+                        .as_ref()
+                        .is_none_or(|pattern| glob::matches(pattern, key));
+                    let matches_type = self
+                        .type_name
+                        .as_ref()
+                        .is_none_or(|type_name| type_name == value.type_name());
+                    if matches_pattern && matches_type {
+                        keys.push(Frame::Bulk(Bytes::copy_from_slice(key)));
+                    }
+                })
+                .unwrap_or((0, None));
+            if let Some(resume) = resume {
+                break resume;
+            }
+
+            shard += 1;
+            if shard == shards {
+                break 0;
+            }
+            // a shard's keys all hash to its index modulo the shard count, so
+            // none hashes below the index itself
+            from = shard;
+            if walked >= remaining {
+                break from;
+            }
+            remaining -= walked;
+        };
+
+        Frame::Array(vec![
+            Frame::Bulk(next.to_string().into()),
+            Frame::Array(keys),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Scan;
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+    use bytes::Bytes;
+    use std::collections::HashSet;
+
+    /// Follows the cursor of `scan` back to 0, collecting every key returned.
+    fn scan_all(db: &ShardedDb, scan: impl Fn(u64) -> Scan) -> Vec<Bytes> {
+        let mut cursor = 0;
+        let mut keys = Vec::new();
+        loop {
+            let Frame::Array(reply) = scan(cursor).apply(db) else {
+                panic!("Expected Frame::Array variant");
+            };
+            let [Frame::Bulk(next), Frame::Array(batch)] = &reply[..] else {
+                panic!("Expected a cursor and an array of keys");
+            };
+            keys.extend(batch.iter().map(|key| match key {
+                Frame::Bulk(key) => key.clone(),
+                _ => panic!("Expected Frame::Bulk variant"),
+            }));
+            cursor = std::str::from_utf8(next).unwrap().parse().unwrap();
+            if cursor == 0 {
+                keys.sort_unstable();
+                return keys;
+            }
+        }
+    }
+
+    fn populate() -> ShardedDb {
+        let mut db = ShardedDb::new();
+        for index in 0..20 {
+            db.insert(format!("string:{index}"), "value".into());
+            db.list_push(format!("list:{index}"), End::Right, vec!["a".into()])
+                .unwrap();
+            db.set_add(format!("set:{index}"), vec!["a".into()])
+                .unwrap();
+        }
+        db.list_push("other", End::Right, vec!["a".into()]).unwrap();
+        db
+    }
+
+    fn keys(prefix: &str, extra: &[&str]) -> Vec<Bytes> {
+        let mut keys: Vec<Bytes> = (0..20)
+            .map(|index| format!("{prefix}:{index}").into())
+            .chain(extra.iter().map(|key| key.to_string().into()))
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn apply_visits_every_key_once() {
+        // Arrange
+        let db = populate();
+
+        // Act
+        let all = scan_all(&db, |cursor| Scan::new(cursor).count(1));
+
+        // Assert
+        assert_eq!(all.len(), db.len());
+        let mut deduplicated = all.clone();
+        deduplicated.dedup();
+        assert_eq!(deduplicated, all);
+    }
+
+    #[test]
+    fn apply_count_bounds_each_batch() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        for index in 0..100 {
+            db.insert(format!("key:{index}"), "value".into());
+        }
+
+        // Act
+        let reply = Scan::new(0).count(10).apply(&db);
+
+        // Assert
+        let Frame::Array(reply) = reply else {
+            panic!("Expected Frame::Array variant");
+        };
+        let [Frame::Bulk(next), Frame::Array(batch)] = &reply[..] else {
+            panic!("Expected a cursor and an array of keys");
+        };
+        assert_eq!(batch.len(), 10);
+        assert_ne!(next, "0");
+    }
+
+    #[test]
+    fn apply_returns_surviving_keys_despite_changes_mid_scan() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(4);
+        for index in 0..200 {
+            db.insert(format!("key:{index}"), "value".into());
+        }
+        let mut returned = HashSet::new();
+        let mut cursor = 0;
+        let mut round = 0;
+
+        // Act
+        loop {
+            let Frame::Array(reply) = Scan::new(cursor).count(10).apply(&db) else {
+                panic!("Expected Frame::Array variant");
+            };
+            let [Frame::Bulk(next), Frame::Array(batch)] = &reply[..] else {
+                panic!("Expected a cursor and an array of keys");
+            };
+            returned.extend(batch.iter().map(|key| match key {
+                Frame::Bulk(key) => key.clone(),
+                _ => panic!("Expected Frame::Bulk variant"),
+            }));
+            db.remove(format!("key:{round}"));
+            if round < 3 {
+                // enough new keys to make the shards rehash
+                for index in 0..100 {
+                    db.insert(format!("new:{round}:{index}"), "value".into());
+                }
+            }
+            round += 1;
+            cursor = std::str::from_utf8(next).unwrap().parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        // Assert
+        for index in round..200 {
+            let key = Bytes::from(format!("key:{index}"));
+            assert!(returned.contains(&key), "key:{index} was skipped");
+        }
+    }
+
+    #[test]
+    fn apply_type_returns_only_that_type() {
+        // Arrange
+        let db = populate();
+
+        // Act
+        let lists = scan_all(&db, |cursor| Scan::new(cursor).type_name("list"));
+
+        // Assert
+        assert_eq!(lists, keys("list", &["other"]));
+    }
+
+    #[test]
+    fn apply_match_and_type_compose() {
+        // Arrange
+        let db = populate();
+
+        // Act
+        let lists = scan_all(&db, |cursor| {
+            Scan::new(cursor).pattern("*:*").type_name("list")
+        });
+        let strings_as_lists = scan_all(&db, |cursor| {
+            Scan::new(cursor).pattern("string:*").type_name("list")
+        });
+
+        // Assert
+        assert_eq!(lists, keys("list", &[]));
+        assert!(strings_as_lists.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+
+/// Switches the connection to another of the server's databases.
+#[derive(Debug)]
+pub struct Select {
+    index: i64,
+}
+
+impl Select {
+    pub fn new(index: i64) -> Self {
+        Self { index }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let index = parse.next_int()?;
+        Ok(Self { index })
+    }
+
+    /// Points `db` at the chosen one of `dbs` and `db_index` at its number.
+    /// The connection's NO-TOUCH setting carries over.
+    pub fn apply(self, dbs: &[ShardedDb], db: &mut ShardedDb, db_index: &mut usize) -> Frame {
+        let Some((index, selected)) = usize::try_from(self.index)
+            .ok()
+            .and_then(|index| Some((index, dbs.get(index)?)))
+        else {
+            return Frame::Error("ERR DB index is out of range".to_string());
+        };
+
+        let touch = db.touches();
+        *db = selected.clone();
+        db.set_touch(touch);
+        *db_index = index;
+        Frame::ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Select;
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_switches_within_range() {
+        // Arrange
+        let dbs = [ShardedDb::new(), ShardedDb::new()];
+        dbs[1].clone().insert("key", "value".into());
+        let mut db = dbs[0].clone();
+        db.set_touch(false);
+        let mut db_index = 0;
+
+        // Act
+        let selected = Select::new(1).apply(&dbs, &mut db, &mut db_index);
+        let past_end = Select::new(2).apply(&dbs, &mut db, &mut db_index);
+        let negative = Select::new(-1).apply(&dbs, &mut db, &mut db_index);
+
+        // Assert
+        let out_of_range = Frame::Error("ERR DB index is out of range".to_string());
+        assert_eq!(selected, Frame::ok());
+        assert_eq!(past_end, out_of_range);
+        assert_eq!(negative, out_of_range);
+        assert_eq!(db_index, 1);
+        assert_eq!(db.get("key").unwrap(), Some("value".into()));
+        assert!(!db.touches());
+    }
+}
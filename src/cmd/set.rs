@@ -0,0 +1,126 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::{self, Expiry, SetCondition, ShardedDb};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct Set {
+    key: Bytes,
+    value: Bytes,
+    /// `None` when no option was given, leaving the key to the default TTL.
+    expiry: Option<Expiry>,
+    condition: Option<SetCondition>,
+    /// `GET`: reply with the previous value instead of `OK`.
+    get: bool,
+}
+
+impl Set {
+    pub fn new(key: impl Into<Bytes>, value: Bytes, expire: Option<Duration>) -> Self {
+        Self {
+            key: key.into(),
+            value,
+            expiry: expire.map(Expiry::In),
+            condition: None,
+            get: false,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+
+    pub fn expire(&self) -> Option<Duration> {
+        match self.expiry {
+            Some(Expiry::In(ttl)) => Some(ttl),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let value = parse.next_bytes()?;
+        let mut set = Self::new(key, value, None);
+
+        while parse.has_remaining() {
+            let option = parse.next_string()?.to_uppercase();
+            let to_duration: fn(u64) -> Duration = match &option[..] {
+                "NX" | "XX" => {
+                    let condition = if option == "NX" {
+                        SetCondition::Nx
+                    } else {
+                        SetCondition::Xx
+                    };
+                    if set.condition.is_some_and(|current| current != condition) {
+                        return Err(CommandError::Syntax.into());
+                    }
+                    set.condition = Some(condition);
+                    continue;
+                }
+                "GET" => {
+                    set.get = true;
+                    continue;
+                }
+                _ if set.expiry.is_some() => return Err(CommandError::Syntax.into()),
+                "EX" => Duration::from_secs,
+                "PX" => Duration::from_millis,
+                "PERSIST" => {
+                    set.expiry = Some(Expiry::Persist);
+                    continue;
+                }
+                "KEEPTTL" => {
+                    set.expiry = Some(Expiry::Keep);
+                    continue;
+                }
+                _ => return Err(CommandError::Syntax.into()),
+            };
+
+            let amount = parse.next_int()?;
+            let expiry = Expiry::In(to_duration(amount.max(0) as u64));
+            if amount <= 0 || !expiry.is_valid() {
+                return Err(anyhow!("invalid expire time in 'set' command").into());
+            }
+            set.expiry = Some(expiry);
+        }
+
+        Ok(set)
+    }
+
+    /// Keys SET without EX, PX, PERSIST or KEEPTTL expire after `default_ttl`,
+    /// if any. `written` is called with the key when it is set: with `GET`
+    /// the reply is the previous value either way, so it can't tell.
+    pub fn apply(
+        self,
+        db: &mut ShardedDb,
+        max_value_size: usize,
+        default_ttl: Option<Duration>,
+        written: impl FnOnce(&Bytes),
+    ) -> Frame {
+        if self.value.len() > max_value_size {
+            return Frame::Error(db::Error::ValueTooLarge.to_string());
+        }
+
+        let expiry = self
+            .expiry
+            .unwrap_or(default_ttl.map_or(Expiry::Persist, Expiry::In));
+        match db.insert_if(&self.key, self.value, expiry, self.condition, self.get) {
+            Ok((wrote, previous)) => {
+                if wrote {
+                    written(&self.key);
+                }
+                match (self.get, wrote) {
+                    (true, _) => previous.map_or(Frame::Null, Frame::Bulk),
+                    (false, true) => Frame::ok(),
+                    (false, false) => Frame::Null,
+                }
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{SetOp, ShardedDb};
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// SINTER/SUNION/SDIFF and their STORE variants, which take a destination key
+/// before the sources.
+#[derive(Debug)]
+pub struct SetOperation {
+    op: SetOp,
+    destination: Option<Bytes>,
+    keys: Vec<Bytes>,
+}
+
+impl SetOperation {
+    pub fn new(op: SetOp, destination: Option<Bytes>, keys: Vec<Bytes>) -> Self {
+        Self {
+            op,
+            destination,
+            keys,
+        }
+    }
+
+    pub fn op(&self) -> SetOp {
+        self.op
+    }
+
+    pub fn destination(&self) -> Option<&Bytes> {
+        self.destination.as_ref()
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+        op: SetOp,
+        store: bool,
+    ) -> Result<Self, ParseError> {
+        let destination = if store {
+            Some(parse.next_bytes()?)
+        } else {
+            None
+        };
+        let keys = parse.remaining_bytes(1)?;
+
+        Ok(Self {
+            op,
+            destination,
+            keys,
+        })
+    }
+
+    /// Replies with the resulting set, or its cardinality when storing.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        let result = match &self.destination {
+            Some(destination) => db
+                .set_combine_store(self.op, destination, &self.keys)
+                .map(|len| Frame::Integer(len as i64)),
+            None => db
+                .set_combine(self.op, &self.keys)
+                .map(|set| Frame::Set(set.into_iter().map(Frame::Bulk).collect())),
+        };
+
+        result.unwrap_or_else(|err| Frame::Error(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{SAdd, SetOperation};
+    use crate::db::{SetOp, ShardedDb};
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_store_replies_cardinality() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        SAdd::new("a", vec!["x".into(), "y".into()]).apply(&mut db);
+        SAdd::new("b", vec!["y".into()]).apply(&mut db);
+        let keys = vec!["a".into(), "b".into()];
+
+        // Act
+        let stored = SetOperation::new(SetOp::Inter, Some("dest".into()), keys).apply(&mut db);
+        let members = SetOperation::new(SetOp::Union, None, vec!["dest".into()]).apply(&mut db);
+
+        // Assert
+        assert_eq!(stored, Frame::Integer(1));
+        assert_eq!(members, Frame::Set(vec![Frame::Bulk("y".into())]));
+    }
+}
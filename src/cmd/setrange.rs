@@ -0,0 +1,41 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+}
+
+impl SetRange {
+    pub fn new(key: impl Into<Bytes>, offset: usize, value: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            offset,
+            value,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let offset =
+            usize::try_from(parse.next_int()?).map_err(|_| anyhow!("offset is out of range"))?;
+        let value = parse.next_bytes()?;
+        Ok(Self { key, offset, value })
+    }
+
+    pub fn apply(self, db: &mut ShardedDb, max_value_size: usize) -> Frame {
+        match db.set_range(&self.key, self.offset, &self.value, max_value_size) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
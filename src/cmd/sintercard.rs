@@ -0,0 +1,59 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SInterCard {
+    keys: Vec<Bytes>,
+    limit: usize,
+}
+
+impl SInterCard {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Self { keys, limit: 0 }
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let num_keys = match parse.next_int()? {
+            num_keys if num_keys <= 0 => {
+                return Err(anyhow!("numkeys should be greater than 0").into())
+            }
+            num_keys => num_keys as usize,
+        };
+
+        let mut keys = Vec::new();
+        for _ in 0..num_keys {
+            if !parse.has_remaining() {
+                return Err(anyhow!("Number of keys can't be greater than number of args").into());
+            }
+            keys.push(parse.next_bytes()?);
+        }
+        let mut sintercard = Self::new(keys);
+
+        if parse.has_remaining() {
+            if parse.next_string()?.to_uppercase() != "LIMIT" {
+                return Err(CommandError::Syntax.into());
+            }
+            sintercard.limit = usize::try_from(parse.next_int()?)
+                .map_err(|_| anyhow!("LIMIT can't be negative"))?;
+        }
+
+        Ok(sintercard)
+    }
+
+    /// A `LIMIT` of 0, like none at all, counts the whole intersection.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.set_inter_card(&self.keys, self.limit) {
+            Ok(card) => Frame::Integer(card as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
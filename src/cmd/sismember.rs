@@ -0,0 +1,66 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SIsMember {
+    key: Bytes,
+    member: Bytes,
+}
+
+impl SIsMember {
+    pub fn new(key: impl Into<Bytes>, member: Bytes) -> Self {
+        Self {
+            key: key.into(),
+            member,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let member = parse.next_bytes()?;
+        Ok(Self { key, member })
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.set_is_member(&self.key, &self.member) {
+            Ok(is_member) => Frame::Integer(is_member as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SMIsMember {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl SMIsMember {
+    pub fn new(key: impl Into<Bytes>, members: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            members,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let members = parse.remaining_bytes(1)?;
+        Ok(Self { key, members })
+    }
+
+    /// Replies with 1 or 0 for each member, in the order they were given.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.set_mismember(&self.key, &self.members) {
+            Ok(found) => Frame::Array(
+                found
+                    .into_iter()
+                    .map(|is_member| Frame::Integer(is_member as i64))
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SMembers {
+    key: Bytes,
+}
+
+impl SMembers {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        Ok(Self { key })
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.set_members(&self.key) {
+            Ok(members) => Frame::Set(members.into_iter().map(Frame::Bulk).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::CommandError;
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Sort {
+    key: Bytes,
+    alpha: bool,
+    descending: bool,
+    limit: Option<(i64, i64)>,
+}
+
+impl Sort {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            alpha: false,
+            descending: false,
+            limit: None,
+        }
+    }
+
+    pub fn alpha(mut self) -> Self {
+        self.alpha = true;
+        self
+    }
+
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    pub fn limit(mut self, offset: i64, count: i64) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let mut sort = Self::new(parse.next_bytes()?);
+
+        while parse.has_remaining() {
+            match &parse.next_string()?.to_uppercase()[..] {
+                "ALPHA" => sort.alpha = true,
+                "ASC" => sort.descending = false,
+                "DESC" => sort.descending = true,
+                "LIMIT" => sort.limit = Some((parse.next_int()?, parse.next_int()?)),
+                _ => return Err(CommandError::Syntax.into()),
+            }
+        }
+
+        Ok(sort)
+    }
+
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match self.elements(db) {
+            Ok(elements) => self.sort(elements),
+            Err(response) => response,
+        }
+    }
+
+    /// Copies the elements out, holding the shard lock no longer than that,
+    /// so the sort itself can run elsewhere.
+    pub(crate) fn elements(&self, db: &ShardedDb) -> Result<Vec<Bytes>, Frame> {
+        db.collection_elements(&self.key)
+            .map_err(|err| Frame::Error(err.to_string()))
+    }
+
+    /// Sorts numerically unless `ALPHA` is given, in which case elements are
+    /// compared byte by byte. `LIMIT` is clamped to the elements there are: a
+    /// negative offset starts from the first and a negative count takes the
+    /// rest.
+    pub(crate) fn sort(self, elements: Vec<Bytes>) -> Frame {
+        let mut sorted = if self.alpha {
+            let mut elements = elements;
+            elements.sort();
+            elements
+        } else {
+            match sort_numeric(elements) {
+                Some(sorted) => sorted,
+                None => {
+                    return Frame::Error(
+                        "ERR One or more scores can't be converted into double".to_string(),
+                    )
+                }
+            }
+        };
+        if self.descending {
+            sorted.reverse();
+        }
+
+        let (offset, count) = self.limit.unwrap_or((0, -1));
+        let offset = usize::try_from(offset).unwrap_or(0).min(sorted.len());
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+
+        let page = sorted.into_iter().skip(offset).take(count);
+        Frame::Array(page.map(Frame::Bulk).collect())
+    }
+}
+
+/// `None` when an element isn't a number.
+fn sort_numeric(elements: Vec<Bytes>) -> Option<Vec<Bytes>> {
+    let mut scored = elements
+        .into_iter()
+        .map(|element| {
+            let score = std::str::from_utf8(&element)
+                .ok()?
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|score| !score.is_nan())?;
+            Some((score, element))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    scored.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    Some(scored.into_iter().map(|(_, element)| element).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Push, SAdd, Sort};
+    use crate::db::{End, ShardedDb};
+    use crate::frame::Frame;
+
+    fn db_with(values: &[&str]) -> ShardedDb {
+        let mut db = ShardedDb::new();
+        let values = values
+            .iter()
+            .map(|value| value.to_string().into())
+            .collect();
+        Push::new("list", End::Right, values).apply(&mut db);
+        db
+    }
+
+    fn array(values: &[&str]) -> Frame {
+        Frame::Array(
+            values
+                .iter()
+                .map(|value| Frame::Bulk(value.to_string().into()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn apply_numeric() {
+        // Arrange
+        let db = db_with(&["10", "2", "-1.5", "3"]);
+
+        // Act
+        let sorted = Sort::new("list").apply(&db);
+
+        // Assert
+        assert_eq!(sorted, array(&["-1.5", "2", "3", "10"]));
+    }
+
+    #[test]
+    fn apply_alpha_on_set() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        SAdd::new("set", vec!["b".into(), "10".into(), "a".into(), "2".into()]).apply(&mut db);
+
+        // Act
+        let sorted = Sort::new("set").alpha().apply(&db);
+
+        // Assert
+        assert_eq!(sorted, array(&["10", "2", "a", "b"]));
+    }
+
+    #[test]
+    fn apply_descending_with_limit() {
+        // Arrange
+        let db = db_with(&["1", "2", "3", "4", "5"]);
+
+        // Act
+        let page = Sort::new("list").descending().limit(1, 2).apply(&db);
+        let rest = Sort::new("list").limit(-3, -1).apply(&db);
+        let past_end = Sort::new("list").limit(10, 2).apply(&db);
+
+        // Assert
+        assert_eq!(page, array(&["4", "3"]));
+        assert_eq!(rest, array(&["1", "2", "3", "4", "5"]));
+        assert_eq!(past_end, array(&[]));
+    }
+
+    #[test]
+    fn apply_non_numeric_without_alpha_error() {
+        // Arrange
+        let db = db_with(&["1", "two"]);
+
+        // Act
+        let sorted = Sort::new("list").apply(&db);
+
+        // Assert
+        assert_eq!(
+            sorted,
+            Frame::Error("ERR One or more scores can't be converted into double".to_string())
+        );
+    }
+}
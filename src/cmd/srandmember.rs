@@ -0,0 +1,142 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{ShardedDb, MAX_SAMPLE};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SRandMember {
+    key: Bytes,
+    count: Option<i64>,
+}
+
+impl SRandMember {
+    pub fn new(key: impl Into<Bytes>, count: Option<i64>) -> Self {
+        Self {
+            key: key.into(),
+            count,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let count = if parse.has_remaining() {
+            Some(parse_count(parse)?)
+        } else {
+            None
+        };
+
+        Ok(Self { key, count })
+    }
+
+    /// Without a count the reply is a single bulk, null for a missing key. With
+    /// one it is an array, see `ShardedDb::set_random_members`.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.set_random_members(&self.key, self.count.unwrap_or(1)) {
+            Ok(mut members) if self.count.is_none() => {
+                members.pop().map_or(Frame::Null, Frame::Bulk)
+            }
+            Ok(members) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+/// A sample size, rejected when its magnitude is above `MAX_SAMPLE`.
+pub(crate) fn parse_count(parse: &mut Parse) -> Result<i64, ParseError> {
+    let count = parse.next_int()?;
+    if count.unsigned_abs() > MAX_SAMPLE {
+        return Err(anyhow!("value is out of range").into());
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{SAdd, SRandMember};
+    use crate::db::{ShardedDb, MAX_SAMPLE};
+    use crate::frame::Frame;
+    use std::collections::HashSet;
+
+    fn db_with_set() -> ShardedDb {
+        let mut db = ShardedDb::new();
+        SAdd::new("set", vec!["a".into(), "b".into(), "c".into()]).apply(&mut db);
+        db
+    }
+
+    fn members(frame: Frame) -> Vec<Frame> {
+        match frame {
+            Frame::Array(members) => members,
+            frame => panic!("expected an array, got {frame:?}"),
+        }
+    }
+
+    #[test]
+    fn apply_positive_count_distinct_and_capped() {
+        // Arrange
+        let db = db_with_set();
+
+        // Act
+        let two = members(SRandMember::new("set", Some(2)).apply(&db));
+        let all = members(SRandMember::new("set", Some(10)).apply(&db));
+
+        // Assert
+        assert_eq!(two.len(), 2);
+        assert_ne!(two[0], two[1]);
+        assert_eq!(all.len(), 3);
+        let distinct: HashSet<String> = all.iter().map(|frame| format!("{frame:?}")).collect();
+        assert_eq!(distinct.len(), 3);
+    }
+
+    #[test]
+    fn apply_negative_count_repeats() {
+        // Arrange
+        let db = db_with_set();
+
+        // Act
+        let picks = members(SRandMember::new("set", Some(-20)).apply(&db));
+
+        // Assert
+        assert_eq!(picks.len(), 20);
+        let valid = [
+            Frame::Bulk("a".into()),
+            Frame::Bulk("b".into()),
+            Frame::Bulk("c".into()),
+        ];
+        assert!(picks.iter().all(|pick| valid.contains(pick)));
+    }
+
+    #[test]
+    fn apply_counts_bounded_by_max_sample() {
+        // Arrange
+        let db = db_with_set();
+
+        // Act
+        let distinct = members(SRandMember::new("set", Some(MAX_SAMPLE as i64)).apply(&db));
+        let repeats = members(SRandMember::new("set", Some(-(MAX_SAMPLE as i64))).apply(&db));
+        let past = SRandMember::new("set", Some(i64::MIN)).apply(&db);
+
+        // Assert
+        assert_eq!(distinct.len(), 3);
+        assert_eq!(repeats.len(), MAX_SAMPLE as usize);
+        assert_eq!(past, Frame::Error("ERR value is out of range".to_string()));
+    }
+
+    #[test]
+    fn apply_missing_key() {
+        // Arrange
+        let db = ShardedDb::new();
+
+        // Act
+        let single = SRandMember::new("missing", None).apply(&db);
+        let counted = SRandMember::new("missing", Some(-3)).apply(&db);
+
+        // Assert
+        assert_eq!(single, Frame::Null);
+        assert_eq!(counted, Frame::Array(vec![]));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use crate::pubsub::{PubSub, Subscriptions};
+use bytes::Bytes;
+
+/// Whether a (un)subscription names exact channels or glob patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Channel,
+    Pattern,
+}
+
+#[derive(Debug)]
+pub struct Subscribe {
+    kind: Kind,
+    targets: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    kind: Kind,
+    targets: Vec<String>,
+}
+
+impl Subscribe {
+    pub fn new(kind: Kind, targets: Vec<String>) -> Self {
+        Self { kind, targets }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, kind: Kind) -> Result<Self, ParseError> {
+        let mut targets = vec![parse.next_string()?];
+        while parse.has_remaining() {
+            targets.push(parse.next_string()?);
+        }
+
+        Ok(Self { kind, targets })
+    }
+
+    /// One confirmation per target, each carrying the connection's running
+    /// subscription count.
+    pub fn apply(self, pubsub: &PubSub, subscriptions: &mut Subscriptions) -> Vec<Frame> {
+        self.targets
+            .into_iter()
+            .map(|target| {
+                match self.kind {
+                    Kind::Channel => subscriptions.subscribe(pubsub, target.clone()),
+                    Kind::Pattern => subscriptions.psubscribe(pubsub, target.clone()),
+                }
+                confirmation(self.kind.subscribe_name(), Some(target), subscriptions)
+            })
+            .collect()
+    }
+}
+
+impl Unsubscribe {
+    pub fn new(kind: Kind, targets: Vec<String>) -> Self {
+        Self { kind, targets }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse, kind: Kind) -> Result<Self, ParseError> {
+        let mut targets = Vec::new();
+        while parse.has_remaining() {
+            targets.push(parse.next_string()?);
+        }
+
+        Ok(Self { kind, targets })
+    }
+
+    /// Without targets every subscription of this kind is dropped. If there
+    /// was nothing to drop a single confirmation with a null target is sent.
+    pub fn apply(self, subscriptions: &mut Subscriptions) -> Vec<Frame> {
+        let name = self.kind.unsubscribe_name();
+        let targets = match (self.targets.is_empty(), self.kind) {
+            (false, _) => self.targets,
+            (true, Kind::Channel) => subscriptions.channels(),
+            (true, Kind::Pattern) => subscriptions.patterns(),
+        };
+
+        if targets.is_empty() {
+            return vec![confirmation(name, None, subscriptions)];
+        }
+
+        targets
+            .into_iter()
+            .map(|target| {
+                match self.kind {
+                    Kind::Channel => subscriptions.unsubscribe(&target),
+                    Kind::Pattern => subscriptions.punsubscribe(&target),
+                }
+                confirmation(name, Some(target), subscriptions)
+            })
+            .collect()
+    }
+}
+
+impl Kind {
+    fn subscribe_name(self) -> &'static str {
+        match self {
+            Kind::Channel => "subscribe",
+            Kind::Pattern => "psubscribe",
+        }
+    }
+
+    fn unsubscribe_name(self) -> &'static str {
+        match self {
+            Kind::Channel => "unsubscribe",
+            Kind::Pattern => "punsubscribe",
+        }
+    }
+}
+
+fn confirmation(
+    name: &'static str,
+    target: Option<String>,
+    subscriptions: &Subscriptions,
+) -> Frame {
+    Frame::Array(vec![
+        Frame::Bulk(Bytes::from_static(name.as_bytes())),
+        target.map_or(Frame::Null, |target| Frame::Bulk(Bytes::from(target))),
+        Frame::Integer(subscriptions.count() as i64),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::subscribe::{Kind, Subscribe, Unsubscribe};
+    use crate::frame::Frame;
+    use crate::pubsub::{PubSub, Subscriptions};
+
+    #[test]
+    fn apply_counts_channels_and_patterns_together() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        Subscribe::new(Kind::Channel, vec!["a".into(), "b".into()])
+            .apply(&pubsub, &mut subscriptions);
+
+        // Act
+        let replies =
+            Subscribe::new(Kind::Pattern, vec!["c.*".into()]).apply(&pubsub, &mut subscriptions);
+
+        // Assert
+        assert_eq!(
+            replies,
+            vec![Frame::Array(vec![
+                Frame::Bulk("psubscribe".into()),
+                Frame::Bulk("c.*".into()),
+                Frame::Integer(3),
+            ])]
+        );
+    }
+
+    #[test]
+    fn unsubscribe_without_targets_drops_only_its_kind() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        Subscribe::new(Kind::Channel, vec!["a".into()]).apply(&pubsub, &mut subscriptions);
+        Subscribe::new(Kind::Pattern, vec!["b.*".into()]).apply(&pubsub, &mut subscriptions);
+
+        // Act
+        let replies = Unsubscribe::new(Kind::Pattern, vec![]).apply(&mut subscriptions);
+        let nothing_left = Unsubscribe::new(Kind::Pattern, vec![]).apply(&mut subscriptions);
+
+        // Assert
+        assert_eq!(subscriptions.channels(), vec!["a".to_string()]);
+        assert_eq!(
+            replies,
+            vec![Frame::Array(vec![
+                Frame::Bulk("punsubscribe".into()),
+                Frame::Bulk("b.*".into()),
+                Frame::Integer(1),
+            ])]
+        );
+        assert_eq!(
+            nothing_left,
+            vec![Frame::Array(vec![
+                Frame::Bulk("punsubscribe".into()),
+                Frame::Null,
+                Frame::Integer(1),
+            ])]
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+
+/// Exchanges the contents of two databases, so connections on either one see
+/// the other's keys from then on.
+#[derive(Debug)]
+pub struct SwapDb {
+    first: i64,
+    second: i64,
+}
+
+impl SwapDb {
+    pub fn new(first: i64, second: i64) -> Self {
+        Self { first, second }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let first = parse.next_int()?;
+        let second = parse.next_int()?;
+        Ok(Self { first, second })
+    }
+
+    pub fn apply(self, dbs: &[ShardedDb]) -> Frame {
+        let index = |index| {
+            usize::try_from(index)
+                .ok()
+                .filter(|index| *index < dbs.len())
+        };
+        let (Some(first), Some(second)) = (index(self.first), index(self.second)) else {
+            return Frame::Error("ERR DB index is out of range".to_string());
+        };
+
+        dbs[first.min(second)].swap(&dbs[first.max(second)]);
+        Frame::ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::SwapDb;
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_exchanges_databases_within_range() {
+        // Arrange
+        let dbs = [
+            ShardedDb::new_seeded(4, 7),
+            ShardedDb::new_seeded(4, 7),
+            ShardedDb::new_seeded(4, 7),
+        ];
+        let (mut first, mut second) = (dbs[0].clone(), dbs[2].clone());
+        first.insert("first", "1".into());
+        second.insert("second", "2".into());
+
+        // Act
+        let swapped = SwapDb::new(2, 0).apply(&dbs);
+        let same = SwapDb::new(1, 1).apply(&dbs);
+        let past_end = SwapDb::new(0, 3).apply(&dbs);
+
+        // Assert
+        assert_eq!(swapped, Frame::ok());
+        assert_eq!(same, Frame::ok());
+        assert_eq!(
+            past_end,
+            Frame::Error("ERR DB index is out of range".to_string())
+        );
+        assert_eq!(first.get("second").unwrap(), Some("2".into()));
+        assert_eq!(first.get("first").unwrap(), None);
+        assert_eq!(second.get("first").unwrap(), Some("1".into()));
+    }
+}
@@ -0,0 +1,263 @@
+/// Flags reported by `COMMAND INFO`, named as Redis names them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Flags(u8);
+
+impl Flags {
+    pub const NONE: Self = Self(0);
+    pub const WRITE: Self = Self(1 << 0);
+    pub const READONLY: Self = Self(1 << 1);
+    pub const DENYOOM: Self = Self(1 << 2);
+    pub const ADMIN: Self = Self(1 << 3);
+    pub const PUBSUB: Self = Self(1 << 4);
+    pub const FAST: Self = Self(1 << 5);
+
+    const NAMES: [(&'static str, Self); 6] = [
+        ("write", Self::WRITE),
+        ("readonly", Self::READONLY),
+        ("denyoom", Self::DENYOOM),
+        ("admin", Self::ADMIN),
+        ("pubsub", Self::PUBSUB),
+        ("fast", Self::FAST),
+    ];
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(move |(_, flag)| self.contains(*flag))
+            .map(|(name, _)| name)
+    }
+}
+
+/// The static description of a command: its arity, counting the name, with a
+/// negative value meaning "at least", its flags and where its keys sit.
+#[derive(Debug)]
+pub struct Spec {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: Flags,
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+}
+
+impl Spec {
+    const fn new(name: &'static str, arity: i64, flags: Flags, keys: (i64, i64, i64)) -> Self {
+        Spec {
+            name,
+            arity,
+            flags,
+            first_key: keys.0,
+            last_key: keys.1,
+            step: keys.2,
+        }
+    }
+}
+
+/// Every command the server knows, sorted by name.
+pub const SPECS: &[Spec] = &[
+    Spec::new(
+        "append",
+        3,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new(
+        "cas",
+        4,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("client", -2, Flags::ADMIN, (0, 0, 0)),
+    Spec::new("command", -1, Flags::NONE, (0, 0, 0)),
+    Spec::new("config", -2, Flags::ADMIN, (0, 0, 0)),
+    Spec::new("debug", -2, Flags::ADMIN, (0, 0, 0)),
+    Spec::new("del", -2, Flags::WRITE, (1, -1, 1)),
+    Spec::new("discard", 1, Flags::FAST, (0, 0, 0)),
+    Spec::new("dump", 2, Flags::READONLY, (1, 1, 1)),
+    Spec::new("exec", 1, Flags::NONE, (0, 0, 0)),
+    Spec::new("expire", 3, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("flushall", -1, Flags::WRITE, (0, 0, 0)),
+    Spec::new("flushdb", -1, Flags::WRITE, (0, 0, 0)),
+    Spec::new("get", 2, Flags::READONLY.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("getex", -2, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("hello", -1, Flags::FAST, (0, 0, 0)),
+    Spec::new("hexpire", -6, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("hget", 3, Flags::READONLY.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("hgetall", 2, Flags::READONLY, (1, 1, 1)),
+    Spec::new(
+        "hincrbyfloat",
+        4,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("hpersist", -5, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("hrandfield", -2, Flags::READONLY, (1, 1, 1)),
+    Spec::new(
+        "hset",
+        -4,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("httl", -5, Flags::READONLY.union(Flags::FAST), (1, 1, 1)),
+    Spec::new(
+        "incrbyfloat",
+        3,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("info", -1, Flags::NONE, (0, 0, 0)),
+    Spec::new("latency", -2, Flags::ADMIN, (0, 0, 0)),
+    Spec::new("linsert", 5, Flags::WRITE.union(Flags::DENYOOM), (1, 1, 1)),
+    Spec::new("lmove", 5, Flags::WRITE.union(Flags::DENYOOM), (1, 2, 1)),
+    Spec::new("lolwut", -1, Flags::READONLY.union(Flags::FAST), (0, 0, 0)),
+    Spec::new("lpop", -2, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("lpos", -3, Flags::READONLY, (1, 1, 1)),
+    Spec::new(
+        "lpush",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("lrem", 4, Flags::WRITE, (1, 1, 1)),
+    Spec::new("lset", 4, Flags::WRITE.union(Flags::DENYOOM), (1, 1, 1)),
+    Spec::new("mdump", 1, Flags::READONLY, (0, 0, 0)),
+    Spec::new("memory", -2, Flags::READONLY, (0, 0, 0)),
+    Spec::new("mget", -2, Flags::READONLY.union(Flags::FAST), (1, -1, 1)),
+    Spec::new("mload", 2, Flags::WRITE.union(Flags::DENYOOM), (0, 0, 0)),
+    Spec::new("monitor", 1, Flags::ADMIN, (0, 0, 0)),
+    Spec::new("multi", 1, Flags::FAST, (0, 0, 0)),
+    Spec::new("object", -2, Flags::READONLY, (2, 2, 1)),
+    Spec::new("ping", -1, Flags::FAST, (0, 0, 0)),
+    Spec::new("psubscribe", -2, Flags::PUBSUB, (0, 0, 0)),
+    Spec::new("publish", 3, Flags::PUBSUB.union(Flags::FAST), (0, 0, 0)),
+    Spec::new("punsubscribe", -1, Flags::PUBSUB, (0, 0, 0)),
+    Spec::new("quit", -1, Flags::FAST, (0, 0, 0)),
+    Spec::new("rpop", -2, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new(
+        "rpoplpush",
+        3,
+        Flags::WRITE.union(Flags::DENYOOM),
+        (1, 2, 1),
+    ),
+    Spec::new(
+        "rpush",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new(
+        "sadd",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("scan", -2, Flags::READONLY, (0, 0, 0)),
+    Spec::new("sdiff", -2, Flags::READONLY, (1, -1, 1)),
+    Spec::new(
+        "sdiffstore",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM),
+        (1, -1, 1),
+    ),
+    Spec::new("select", 2, Flags::FAST, (0, 0, 0)),
+    Spec::new("set", -3, Flags::WRITE.union(Flags::DENYOOM), (1, 1, 1)),
+    Spec::new("setrange", 4, Flags::WRITE.union(Flags::DENYOOM), (1, 1, 1)),
+    Spec::new("sinter", -2, Flags::READONLY, (1, -1, 1)),
+    Spec::new("sintercard", -3, Flags::READONLY, (0, 0, 0)),
+    Spec::new(
+        "sinterstore",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM),
+        (1, -1, 1),
+    ),
+    Spec::new(
+        "sismember",
+        3,
+        Flags::READONLY.union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("smembers", 2, Flags::READONLY, (1, 1, 1)),
+    Spec::new(
+        "smismember",
+        -3,
+        Flags::READONLY.union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("sort", -2, Flags::READONLY, (1, 1, 1)),
+    Spec::new("srandmember", -2, Flags::READONLY, (1, 1, 1)),
+    Spec::new("srem", -3, Flags::WRITE.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("subscribe", -2, Flags::PUBSUB, (0, 0, 0)),
+    Spec::new("sunion", -2, Flags::READONLY, (1, -1, 1)),
+    Spec::new(
+        "sunionstore",
+        -3,
+        Flags::WRITE.union(Flags::DENYOOM),
+        (1, -1, 1),
+    ),
+    Spec::new("swapdb", 3, Flags::WRITE.union(Flags::FAST), (0, 0, 0)),
+    Spec::new("time", 1, Flags::READONLY.union(Flags::FAST), (0, 0, 0)),
+    Spec::new("ttl", 2, Flags::READONLY.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("unlink", -2, Flags::WRITE.union(Flags::FAST), (1, -1, 1)),
+    Spec::new("unsubscribe", -1, Flags::PUBSUB, (0, 0, 0)),
+    Spec::new("unwatch", 1, Flags::FAST, (0, 0, 0)),
+    Spec::new("waitaof", 4, Flags::NONE, (0, 0, 0)),
+    Spec::new("watch", -2, Flags::FAST, (1, -1, 1)),
+    Spec::new(
+        "xadd",
+        -5,
+        Flags::WRITE.union(Flags::DENYOOM).union(Flags::FAST),
+        (1, 1, 1),
+    ),
+    Spec::new("xlen", 2, Flags::READONLY.union(Flags::FAST), (1, 1, 1)),
+    Spec::new("xrange", -4, Flags::READONLY, (1, 1, 1)),
+];
+
+/// Finds the spec for a lowercase command name.
+pub fn lookup(name: &str) -> Option<&'static Spec> {
+    position(name).map(|index| &SPECS[index])
+}
+
+/// Where a lowercase command name sits in `SPECS`.
+pub fn position(name: &str) -> Option<usize> {
+    SPECS.binary_search_by(|spec| spec.name.cmp(name)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::table::{lookup, Flags, SPECS};
+    use claims::{assert_none, assert_some};
+
+    #[test]
+    fn specs_are_sorted_for_lookup() {
+        // Arrange
+        let names: Vec<_> = SPECS.iter().map(|spec| spec.name).collect();
+
+        // Act
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+
+        // Assert
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn lookup_finds_get_and_set() {
+        // Act
+        let get = assert_some!(lookup("get"));
+        let set = assert_some!(lookup("set"));
+
+        // Assert
+        assert_eq!(get.arity, 2);
+        assert!(get.flags.contains(Flags::READONLY));
+        assert!(set.flags.contains(Flags::WRITE));
+        assert_none!(lookup("nosuchcommand"));
+    }
+}
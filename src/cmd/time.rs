@@ -0,0 +1,25 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Answers with the server's clock as Unix seconds and the microseconds into
+/// the current second, both as bulk strings.
+#[derive(Debug)]
+pub struct Time;
+
+impl Time {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    pub fn apply(self) -> Frame {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Frame::Array(vec![
+            Frame::Bulk(now.as_secs().to_string().into()),
+            Frame::Bulk(now.subsec_micros().to_string().into()),
+        ])
+    }
+}
@@ -0,0 +1,82 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: Bytes,
+}
+
+impl Ttl {
+    pub fn new(key: impl Into<Bytes>) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        Ok(Self { key })
+    }
+
+    /// `-2` for a missing key, `-1` for a key without a deadline, otherwise
+    /// the remaining seconds rounded to the nearest second.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        match db.ttl(&self.key) {
+            None => Frame::Integer(-2),
+            Some(None) => Frame::Integer(-1),
+            Some(Some(ttl)) => Frame::Integer(((ttl.as_millis() + 500) / 1000) as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Expire, Ttl};
+    use crate::db::ShardedDb;
+    use crate::frame::Frame;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn apply_reports_remaining_seconds() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+        Expire::new("key", 10).apply(&mut db);
+        tokio::time::advance(Duration::from_secs(3)).await;
+
+        // Act
+        let frame = Ttl::new("key").apply(&db);
+
+        // Assert
+        assert_eq!(frame, Frame::Integer(7));
+    }
+
+    #[test]
+    fn apply_sentinels() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("persistent", "value".into());
+
+        // Act
+        let missing = Ttl::new("missing").apply(&db);
+        let persistent = Ttl::new("persistent").apply(&db);
+
+        // Assert
+        assert_eq!(missing, Frame::Integer(-2));
+        assert_eq!(persistent, Frame::Integer(-1));
+    }
+
+    #[test]
+    fn expire_non_positive_deletes_key() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        let frame = Expire::new("key", 0).apply(&mut db);
+
+        // Assert
+        assert_eq!(frame, Frame::Integer(1));
+        assert!(db.is_empty());
+    }
+}
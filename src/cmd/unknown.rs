@@ -0,0 +1,99 @@
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// How much of the name, and of the arguments together, the error echoes back.
+const PREVIEW_LEN: usize = 128;
+
+#[derive(Debug)]
+pub struct Unknown {
+    name: String,
+    args: Vec<Bytes>,
+}
+
+impl Unknown {
+    pub fn new(name: impl ToString, args: Vec<Bytes>) -> Self {
+        Self {
+            name: name.to_string(),
+            args,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Names the command as it was sent and previews its arguments, cut to
+    /// `PREVIEW_LEN` bytes and with line breaks blanked so the error stays on
+    /// one line.
+    pub fn apply(self) -> Frame {
+        let mut args = Vec::new();
+        let mut budget = PREVIEW_LEN;
+        for arg in &self.args {
+            if budget == 0 {
+                break;
+            }
+            let arg = preview(&String::from_utf8_lossy(arg), budget);
+            budget = budget.saturating_sub(arg.len());
+            args.push(format!("'{arg}'"));
+        }
+
+        Frame::Error(format!(
+            "ERR unknown command '{}', with args beginning with: {}",
+            preview(&self.name, PREVIEW_LEN),
+            args.join(", ")
+        ))
+    }
+}
+
+/// The longest prefix of `text` that fits in `limit` bytes without splitting
+/// a character.
+fn preview(text: &str, limit: usize) -> String {
+    let mut end = text.len().min(limit);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Unknown;
+    use crate::frame::Frame;
+    use bytes::Bytes;
+
+    #[test]
+    fn apply_previews_args() {
+        // Arrange
+        let unknown = Unknown::new("FOO", vec!["bar".into(), "baz".into()]);
+
+        // Act
+        let response = unknown.apply();
+
+        // Assert
+        assert_eq!(
+            response,
+            Frame::Error(
+                "ERR unknown command 'FOO', with args beginning with: 'bar', 'baz'".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn apply_truncates_long_and_binary_args() {
+        // Arrange
+        let long = Bytes::from("é".repeat(100));
+        let binary = Bytes::from_static(b"\xff\r\n");
+        let unknown = Unknown::new("foo", vec![binary, long, "never".into()]);
+
+        // Act
+        let response = unknown.apply();
+
+        // Assert
+        let Frame::Error(message) = response else {
+            panic!("Expected Frame::Error variant");
+        };
+        let (_, args) = message.split_once(": ").unwrap();
+        assert!(args.starts_with("'\u{fffd}  ', 'éé"));
+        assert!(!args.contains("never"));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::frame::Frame;
+use anyhow::anyhow;
+
+/// `WAITAOF numlocal numreplicas timeout`. There is no AOF and there are no
+/// replicas, so nothing is ever fsynced and the command never blocks.
+#[derive(Debug)]
+pub struct WaitAof {
+    num_local: u64,
+}
+
+impl WaitAof {
+    pub fn new(num_local: u64) -> Self {
+        Self { num_local }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let num_local = parse.next_int()?;
+        let num_replicas = parse.next_int()?;
+        let timeout = parse.next_int()?;
+        if timeout < 0 {
+            return Err(anyhow!("timeout is negative").into());
+        }
+        if num_local < 0 || num_replicas < 0 {
+            return Err(anyhow!("value is out of range, must be positive").into());
+        }
+
+        Ok(Self {
+            num_local: num_local as u64,
+        })
+    }
+
+    /// Replies `[local fsyncs, replica fsyncs]`, always `[0, 0]`, unless a
+    /// local fsync is asked for, which Redis refuses without appendonly too.
+    pub fn apply(self) -> Frame {
+        if self.num_local > 0 {
+            return Frame::Error(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled."
+                    .to_string(),
+            );
+        }
+
+        Frame::Array(vec![Frame::Integer(0), Frame::Integer(0)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::WaitAof;
+    use crate::frame::Frame;
+
+    #[test]
+    fn apply_without_appendonly() {
+        // Act
+        let local = WaitAof::new(1).apply();
+        let replicas_only = WaitAof::new(0).apply();
+
+        // Assert
+        assert!(
+            matches!(local, Frame::Error(message) if message.contains("appendonly is disabled"))
+        );
+        assert_eq!(
+            replicas_only,
+            Frame::Array(vec![Frame::Integer(0), Frame::Integer(0)])
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::ShardedDb;
+use crate::frame::Frame;
+use crate::transaction::Transaction;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Watch {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Unwatch;
+
+impl Watch {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Self { keys }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let mut keys = vec![parse.next_bytes()?];
+        while parse.has_remaining() {
+            keys.push(parse.next_bytes()?);
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Watches the keys in the database at `db_index`, the selected one.
+    pub fn apply(self, dbs: &[ShardedDb], db_index: usize, transaction: &mut Transaction) -> Frame {
+        match transaction.watch(dbs, db_index, self.keys) {
+            Ok(()) => Frame::ok(),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+impl Unwatch {
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> Result<Self, ParseError> {
+        Ok(Self)
+    }
+
+    pub fn apply(self, transaction: &mut Transaction) -> Frame {
+        transaction.unwatch();
+        Frame::ok()
+    }
+}
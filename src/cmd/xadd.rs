@@ -0,0 +1,58 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::db::{NewStreamId, ShardedDb, StreamId};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+pub(crate) const INVALID_ID: &str = "Invalid stream ID specified as stream command argument";
+
+#[derive(Debug)]
+pub struct XAdd {
+    key: Bytes,
+    id: NewStreamId,
+    fields: Vec<(Bytes, Bytes)>,
+}
+
+impl XAdd {
+    pub fn new(key: impl Into<Bytes>, id: NewStreamId, fields: Vec<(Bytes, Bytes)>) -> Self {
+        Self {
+            key: key.into(),
+            id,
+            fields,
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        &self.key
+    }
+
+    /// Takes `*` for an id generated from the clock, `<ms>-*` for one
+    /// generated within `<ms>`, and otherwise an id in full, a bare `<ms>`
+    /// meaning `<ms>-0`.
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let id = parse.next_string()?;
+        let id = if id == "*" {
+            NewStreamId::Auto
+        } else if let Some(ms) = id.strip_suffix("-*") {
+            NewStreamId::AutoSeq(ms.parse().map_err(|_| anyhow!(INVALID_ID))?)
+        } else {
+            NewStreamId::Explicit(StreamId::parse(&id, 0).ok_or(anyhow!(INVALID_ID))?)
+        };
+
+        let mut fields = vec![(parse.next_bytes()?, parse.next_bytes()?)];
+        while parse.has_remaining() {
+            fields.push((parse.next_bytes()?, parse.next_bytes()?));
+        }
+
+        Ok(Self { key, id, fields })
+    }
+
+    /// Replies with the id the entry was added under.
+    pub fn apply(self, db: &mut ShardedDb) -> Frame {
+        match db.stream_add(&self.key, self.id, self.fields) {
+            Ok(id) => Frame::Bulk(id.to_string().into()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
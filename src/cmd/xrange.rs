@@ -0,0 +1,123 @@
+use crate::cmd::parse::{Parse, ParseError};
+use crate::cmd::xadd::INVALID_ID;
+use crate::cmd::CommandError;
+use crate::db::{ShardedDb, StreamId};
+use crate::frame::Frame;
+use anyhow::anyhow;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct XRange {
+    key: Bytes,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+}
+
+impl XRange {
+    pub fn new(key: impl Into<Bytes>, start: StreamId, end: StreamId) -> Self {
+        Self {
+            key: key.into(),
+            start,
+            end,
+            count: None,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Bounds are inclusive. `-` and `+` stand for the smallest and greatest
+    /// ids, and a bare `<ms>` covers that whole millisecond.
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Self, ParseError> {
+        let key = parse.next_bytes()?;
+        let start = match &parse.next_string()?[..] {
+            "-" => StreamId::MIN,
+            start => StreamId::parse(start, 0).ok_or(anyhow!(INVALID_ID))?,
+        };
+        let end = match &parse.next_string()?[..] {
+            "+" => StreamId::MAX,
+            end => StreamId::parse(end, u64::MAX).ok_or(anyhow!(INVALID_ID))?,
+        };
+        let mut xrange = Self::new(key, start, end);
+
+        if parse.has_remaining() {
+            if parse.next_string()?.to_uppercase() != "COUNT" {
+                return Err(CommandError::Syntax.into());
+            }
+            // Like Redis, a negative count is taken as no entries.
+            xrange.count = Some(usize::try_from(parse.next_int()?).unwrap_or(0));
+        }
+
+        Ok(xrange)
+    }
+
+    /// Replies with each entry as its id followed by its fields and values.
+    pub fn apply(self, db: &ShardedDb) -> Frame {
+        let count = self.count.unwrap_or(usize::MAX);
+        match db.stream_range(&self.key, self.start, self.end, count) {
+            Ok(entries) => Frame::Array(
+                entries
+                    .into_iter()
+                    .map(|(id, fields)| {
+                        let fields = fields
+                            .into_iter()
+                            .flat_map(|(field, value)| [Frame::Bulk(field), Frame::Bulk(value)])
+                            .collect();
+                        Frame::Array(vec![
+                            Frame::Bulk(id.to_string().into()),
+                            Frame::Array(fields),
+                        ])
+                    })
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{XAdd, XRange};
+    use crate::db::{NewStreamId, ShardedDb, StreamId};
+    use crate::frame::Frame;
+
+    fn entry(id: &str, field: &str, value: &str) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(id.to_string().into()),
+            Frame::Array(vec![
+                Frame::Bulk(field.to_string().into()),
+                Frame::Bulk(value.to_string().into()),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn apply_bounds_by_whole_millisecond() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        for (ms, seq) in [(1, 0), (2, 0), (2, 1), (3, 0)] {
+            let id = NewStreamId::Explicit(StreamId::new(ms, seq));
+            XAdd::new("stream", id, vec![("f".into(), "v".into())]).apply(&mut db);
+        }
+        let ms = |ms| StreamId::new(ms, 0);
+        let ms_end = |ms| StreamId::new(ms, u64::MAX);
+
+        // Act
+        let within = XRange::new("stream", ms(2), ms_end(2)).apply(&db);
+        let counted = XRange::new("stream", StreamId::MIN, StreamId::MAX)
+            .count(1)
+            .apply(&db);
+        let missing = XRange::new("missing", StreamId::MIN, StreamId::MAX).apply(&db);
+
+        // Assert
+        assert_eq!(
+            within,
+            Frame::Array(vec![entry("2-0", "f", "v"), entry("2-1", "f", "v")])
+        );
+        assert_eq!(counted, Frame::Array(vec![entry("1-0", "f", "v")]));
+        assert_eq!(missing, Frame::Array(vec![]));
+    }
+}
@@ -0,0 +1,482 @@
+use crate::cmd::Registry;
+use crate::frame::MAX_BULK_LEN;
+use crate::glob;
+use crate::notify::NotifyFlags;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("Unknown option or number of arguments for CONFIG SET - '{0}'")]
+    UnknownParameter(String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - can't set immutable config")]
+    Immutable(String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - invalid argument '{1}'")]
+    InvalidArgument(String, String),
+}
+
+const PARAMETERS: &[&str] = &[
+    "appendonly",
+    "databases",
+    "lazyfree-threshold",
+    "max-value-size",
+    "maxmemory",
+    "maxmemory-policy",
+    "notify-keyspace-events",
+    "save",
+    "tcp-backlog",
+    "tcp-keepalive",
+    "timeout",
+];
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub socket: SocketOptions,
+    pub maxmemory: u64,
+    pub maxmemory_policy: EvictionPolicy,
+    pub notify_keyspace_events: NotifyFlags,
+    /// Longest string value SET/APPEND/SETRANGE may store, 0 for no limit
+    /// beyond the protocol's bulk cap.
+    pub max_value_size: usize,
+    /// How long a pub/sub message may take to reach a subscriber before the
+    /// subscriber is considered too slow and disconnected.
+    pub pubsub_write_timeout: Duration,
+    /// Whether clients sending malformed frames are told what was wrong with
+    /// them, or just get a generic protocol error.
+    pub verbose_protocol_errors: bool,
+    /// Refuses every command that would change the dataset, as a replica does.
+    pub read_only: bool,
+    /// Whether MONITOR is allowed. It shows every command from every client,
+    /// so it is off unless asked for.
+    pub enable_monitor: bool,
+    /// Accepts a bare `\n` where RESP requires `\r\n`, for clients such as
+    /// telnet that send one.
+    pub lenient_newlines: bool,
+    /// Closes connections that have sent nothing for this long, zero for
+    /// never.
+    pub timeout: Duration,
+    /// Lowercase names of commands refused before they are parsed.
+    pub disabled_commands: HashSet<String>,
+    /// Whether disabled commands are refused as unknown, hiding that they
+    /// exist, rather than as disabled.
+    pub hide_disabled_commands: bool,
+    /// Where to serve the HTTP health check, if anywhere.
+    pub health_addr: Option<SocketAddr>,
+    /// Expiry given to keys SET without EX, PX or PERSIST.
+    pub default_ttl: Option<Duration>,
+    /// Seeds the hash that places keys in shards, random when unset. Fixing
+    /// it makes placement reproducible, and predictable to anyone who knows it.
+    pub hash_seed: Option<u64>,
+    /// Pipelined commands a connection runs back to back before yielding to
+    /// the others on its worker, 0 for no limit.
+    pub max_pipeline_burst: usize,
+    /// How many databases SELECT can choose from, allocated at startup. One
+    /// leaves only database 0.
+    pub databases: usize,
+    /// Estimated size above which UNLINK and FLUSHALL/FLUSHDB ASYNC free a
+    /// value on a background task instead of inline.
+    pub lazyfree_threshold: usize,
+    /// Commands added by the embedding application, taken by `server::run`
+    /// when it starts.
+    pub commands: Registry,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            socket: SocketOptions::default(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::default(),
+            notify_keyspace_events: NotifyFlags::default(),
+            max_value_size: 0,
+            pubsub_write_timeout: Duration::from_secs(60),
+            verbose_protocol_errors: false,
+            read_only: false,
+            enable_monitor: false,
+            lenient_newlines: false,
+            timeout: Duration::ZERO,
+            disabled_commands: HashSet::new(),
+            hide_disabled_commands: false,
+            health_addr: None,
+            default_ttl: None,
+            hash_seed: None,
+            max_pipeline_burst: 128,
+            databases: 16,
+            lazyfree_threshold: 64 * 1024,
+            commands: Registry::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn value_size_limit(&self) -> usize {
+        match self.max_value_size {
+            0 => MAX_BULK_LEN,
+            size => size.min(MAX_BULK_LEN),
+        }
+    }
+
+    pub fn is_disabled(&self, command: &str) -> bool {
+        self.disabled_commands.contains(&command.to_lowercase())
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let value = match &name.to_lowercase()[..] {
+            "appendonly" => "no".to_string(),
+            "databases" => self.databases.to_string(),
+            "lazyfree-threshold" => self.lazyfree_threshold.to_string(),
+            "max-value-size" => self.max_value_size.to_string(),
+            "maxmemory" => self.maxmemory.to_string(),
+            "maxmemory-policy" => self.maxmemory_policy.to_string(),
+            "notify-keyspace-events" => self.notify_keyspace_events.to_string(),
+            "save" => String::new(),
+            "tcp-backlog" => self.socket.backlog.to_string(),
+            "tcp-keepalive" => self
+                .socket
+                .keepalive
+                .map_or(0, |keepalive| keepalive.as_secs())
+                .to_string(),
+            "timeout" => self.timeout.as_secs().to_string(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// All parameters whose name matches the glob `pattern`, as name/value pairs.
+    pub fn matching(&self, pattern: &str) -> Vec<(&'static str, String)> {
+        let pattern = pattern.to_lowercase();
+        PARAMETERS
+            .iter()
+            .filter(|name| glob::matches(pattern.as_bytes(), name.as_bytes()))
+            .filter_map(|name| self.get(name).map(|value| (*name, value)))
+            .collect()
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let name = name.to_lowercase();
+        let invalid = || Error::InvalidArgument(name.clone(), value.to_string());
+
+        match &name[..] {
+            "lazyfree-threshold" => {
+                self.lazyfree_threshold = parse_memory(value)
+                    .and_then(|size| usize::try_from(size).ok())
+                    .ok_or_else(invalid)?
+            }
+            "max-value-size" => {
+                self.max_value_size = parse_memory(value)
+                    .and_then(|size| usize::try_from(size).ok())
+                    .ok_or_else(invalid)?
+            }
+            "maxmemory" => self.maxmemory = parse_memory(value).ok_or_else(invalid)?,
+            "maxmemory-policy" => self.maxmemory_policy = value.parse().map_err(|_| invalid())?,
+            "notify-keyspace-events" => {
+                self.notify_keyspace_events = value.parse().map_err(|_| invalid())?
+            }
+            "timeout" => self.timeout = Duration::from_secs(value.parse().map_err(|_| invalid())?),
+            name if PARAMETERS.contains(&name) => return Err(Error::Immutable(name.to_string())),
+            _ => return Err(Error::UnknownParameter(name)),
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EvictionPolicy {
+    #[default]
+    NoEviction,
+    AllKeysLru,
+    AllKeysLfu,
+    AllKeysRandom,
+    VolatileLru,
+    VolatileLfu,
+    VolatileRandom,
+    VolatileTtl,
+}
+
+impl EvictionPolicy {
+    pub fn is_lfu(self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            EvictionPolicy::NoEviction => "noeviction",
+            EvictionPolicy::AllKeysLru => "allkeys-lru",
+            EvictionPolicy::AllKeysLfu => "allkeys-lfu",
+            EvictionPolicy::AllKeysRandom => "allkeys-random",
+            EvictionPolicy::VolatileLru => "volatile-lru",
+            EvictionPolicy::VolatileLfu => "volatile-lfu",
+            EvictionPolicy::VolatileRandom => "volatile-random",
+            EvictionPolicy::VolatileTtl => "volatile-ttl",
+        }
+    }
+}
+
+impl Display for EvictionPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let policy = match &s.to_lowercase()[..] {
+            "noeviction" => EvictionPolicy::NoEviction,
+            "allkeys-lru" => EvictionPolicy::AllKeysLru,
+            "allkeys-lfu" => EvictionPolicy::AllKeysLfu,
+            "allkeys-random" => EvictionPolicy::AllKeysRandom,
+            "volatile-lru" => EvictionPolicy::VolatileLru,
+            "volatile-lfu" => EvictionPolicy::VolatileLfu,
+            "volatile-random" => EvictionPolicy::VolatileRandom,
+            "volatile-ttl" => EvictionPolicy::VolatileTtl,
+            _ => return Err(()),
+        };
+
+        Ok(policy)
+    }
+}
+
+/// Parses a byte count with an optional `kb`/`mb`/`gb` (or `k`/`m`/`g`) unit,
+/// like `maxmemory 100mb`.
+fn parse_memory(value: &str) -> Option<u64> {
+    let value = value.to_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+
+    let multiplier = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[derive(Clone, Debug)]
+pub struct SocketOptions {
+    pub nodelay: bool,
+    pub keepalive: Option<Duration>,
+    /// How many connections may wait to be accepted before the OS starts
+    /// refusing them.
+    pub backlog: u32,
+}
+
+impl SocketOptions {
+    pub fn apply(&self, socket: &TcpStream) -> io::Result<()> {
+        socket.set_nodelay(self.nodelay)?;
+
+        if let Some(time) = self.keepalive {
+            let keepalive = socket2::TcpKeepalive::new().with_time(time);
+            socket2::SockRef::from(socket).set_tcp_keepalive(&keepalive)?;
+        }
+
+        Ok(())
+    }
+
+    /// Binds a listener on `addr` with `backlog` as its accept queue length.
+    pub fn listen(&self, addr: SocketAddr) -> io::Result<TcpListener> {
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        #[cfg(not(windows))]
+        socket.set_reuseaddr(true)?;
+        socket.bind(addr)?;
+        socket.listen(self.backlog)
+    }
+}
+
+/// Nagle's algorithm only delays our small replies, so nodelay is on by default.
+/// Keepalive stays off, matching the OS default, and the backlog is the 1024
+/// `TcpListener::bind` uses.
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            keepalive: None,
+            backlog: 1024,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{EvictionPolicy, ServerConfig, SocketOptions};
+    use claims::{assert_err, assert_ok};
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time;
+
+    async fn accepted_socket() -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+        socket
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn listen_applies_backlog() {
+        // Arrange
+        let options = SocketOptions {
+            backlog: 1,
+            ..SocketOptions::default()
+        };
+
+        // Act
+        let listener = options.listen("127.0.0.1:0".parse().unwrap()).unwrap();
+
+        // Assert
+        let addr = listener.local_addr().unwrap();
+        let mut queued = Vec::new();
+        for _ in 0..2 {
+            queued.push(TcpStream::connect(addr).await.unwrap());
+        }
+        let overflow = time::timeout(Duration::from_millis(200), TcpStream::connect(addr)).await;
+        assert_err!(overflow);
+    }
+
+    #[tokio::test]
+    async fn apply_nodelay_enabled() {
+        // Arrange
+        let socket = accepted_socket().await;
+        let options = SocketOptions {
+            nodelay: true,
+            keepalive: Some(Duration::from_secs(60)),
+            ..SocketOptions::default()
+        };
+
+        // Act
+        let result = options.apply(&socket);
+
+        // Assert
+        assert_ok!(&result);
+        assert!(socket.nodelay().unwrap());
+        assert!(socket2::SockRef::from(&socket).keepalive().unwrap());
+    }
+
+    #[tokio::test]
+    async fn apply_nodelay_disabled() {
+        // Arrange
+        let socket = accepted_socket().await;
+        let options = SocketOptions {
+            nodelay: false,
+            keepalive: None,
+            ..SocketOptions::default()
+        };
+
+        // Act
+        let result = options.apply(&socket);
+
+        // Assert
+        assert_ok!(&result);
+        assert!(!socket.nodelay().unwrap());
+        assert!(!socket2::SockRef::from(&socket).keepalive().unwrap());
+    }
+
+    #[test]
+    fn matching_glob_pattern() {
+        // Arrange
+        let config = ServerConfig::default();
+
+        // Act
+        let matching = config.matching("maxmemory*");
+
+        // Assert
+        assert_eq!(
+            matching,
+            vec![
+                ("maxmemory", "0".to_string()),
+                ("maxmemory-policy", "noeviction".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn matching_unknown_parameter_empty() {
+        // Arrange
+        let config = ServerConfig::default();
+
+        // Act
+        let matching = config.matching("no-such-parameter");
+
+        // Assert
+        assert!(matching.is_empty());
+    }
+
+    #[test]
+    fn set_maxmemory_with_unit_valid() {
+        // Arrange
+        let mut config = ServerConfig::default();
+
+        // Act
+        let result = config.set("maxmemory", "100mb");
+
+        // Assert
+        assert_ok!(&result);
+        assert_eq!(config.maxmemory, 100 * 1024 * 1024);
+    }
+
+    #[test]
+    fn set_timeout_in_seconds() {
+        // Arrange
+        let mut config = ServerConfig::default();
+
+        // Act
+        let result = config.set("timeout", "30");
+
+        // Assert
+        assert_ok!(&result);
+        assert_eq!(config.timeout, Duration::from_secs(30));
+        assert_eq!(config.get("timeout"), Some("30".to_string()));
+    }
+
+    #[test]
+    fn set_maxmemory_policy_invalid() {
+        // Arrange
+        let mut config = ServerConfig::default();
+
+        // Act
+        let result = config.set("maxmemory-policy", "sometimes-lru");
+
+        // Assert
+        assert_err!(&result);
+        assert_eq!(config.maxmemory_policy, EvictionPolicy::NoEviction);
+    }
+
+    #[test]
+    fn set_immutable_parameter_invalid() {
+        // Arrange
+        let mut config = ServerConfig::default();
+
+        // Act
+        let result = config.set("save", "900 1");
+
+        // Assert
+        assert_err!(&result);
+        assert_eq!(config.get("save"), Some(String::new()));
+    }
+}
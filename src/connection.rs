@@ -1,11 +1,249 @@
-pub struct Connection {
-    // stream: TcpStream,
+use crate::frame::{self, Frame, Newlines, Protocol};
+use bytes::{Buf, Bytes, BytesMut};
+use std::io::{self, Cursor};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+use tokio::net::TcpStream;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Bulk replies with payloads longer than this are written straight from the
+/// frame, `WRITE_CHUNK` bytes at a time, rather than copied into one buffer.
+const STREAM_BULK_LEN: usize = 64 * 1024;
+const WRITE_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("connection reset by peer")]
+    ConnectionReset,
+    #[error(transparent)]
+    Frame(#[from] frame::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub struct Connection<S = TcpStream> {
+    stream: S,
+    buffer: BytesMut,
+    protocol: Protocol,
+    newlines: Newlines,
 }
 
-// impl Connection {
-//     pub async fn read_frame(&mut self) -> Result<Option<Frame>> {
-//         self.stream.read()
-//     }
-//
-//     pub async fn write_frame(&mut self) -> Result<()> {}
-// }
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buffer: BytesMut::with_capacity(4 * 1024),
+            protocol: Protocol::default(),
+            newlines: Newlines::default(),
+        }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Replies written after this are encoded for `protocol`.
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Frames read after this accept line terminators as `newlines` says.
+    pub fn set_newlines(&mut self, newlines: Newlines) {
+        self.newlines = newlines;
+    }
+
+    /// Whether input is already buffered past the frames read so far, as when
+    /// the peer pipelines.
+    pub fn has_buffered_input(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Returns `Ok(None)` when the peer closes the stream between frames, and
+    /// `Error::ConnectionReset` when it goes away with a partial frame buffered.
+    pub async fn read_frame(&mut self) -> Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            if 0 == self.stream.read_buf(&mut self.buffer).await? {
+                return if self.buffer.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(Error::ConnectionReset)
+                };
+            }
+        }
+    }
+
+    pub async fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        if let Frame::Bulk(content) = frame {
+            if content.len() > STREAM_BULK_LEN {
+                return self.write_bulk_streamed(content).await;
+            }
+        }
+
+        let mut encoded = Vec::new();
+        frame.encode_with(&mut encoded, self.protocol);
+        self.stream.write_all(&encoded).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Writes all `frames` with a single flush, for pipelined requests.
+    pub async fn write_frames(&mut self, frames: &[Frame]) -> Result<()> {
+        let mut encoded = Vec::new();
+        for frame in frames {
+            frame.encode_with(&mut encoded, self.protocol);
+        }
+        self.stream.write_all(&encoded).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    async fn write_bulk_streamed(&mut self, content: &Bytes) -> Result<()> {
+        let header = format!("${}\r\n", content.len());
+        self.stream.write_all(header.as_bytes()).await?;
+        for chunk in content.chunks(WRITE_CHUNK) {
+            self.stream.write_all(chunk).await?;
+        }
+        self.stream.write_all(b"\r\n").await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    fn parse_frame(&mut self) -> Result<Option<Frame>> {
+        let mut buff = Cursor::new(&self.buffer[..]);
+
+        match frame::parse_with(&mut buff, self.newlines) {
+            Ok(frame) => {
+                let len = buff.position() as usize;
+                self.buffer.advance(len);
+                Ok(Some(frame))
+            }
+            Err(frame::Error::Incomplete) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Reads the next frame from `reader`, consuming only its bytes, for replaying
+/// RESP from a file or a captured session. Returns `Ok(None)` when the input
+/// ends between frames, and an `UnexpectedEof` error when it ends mid-frame.
+pub async fn read_frame_from<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Frame>> {
+    let mut partial = BytesMut::new();
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return if partial.is_empty() {
+                Ok(None)
+            } else {
+                Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+            };
+        }
+        let carried = partial.len();
+        let read = available.len();
+        partial.extend_from_slice(available);
+
+        let mut buff = Cursor::new(&partial[..]);
+        match frame::parse(&mut buff) {
+            Ok(frame) => {
+                reader.consume(buff.position() as usize - carried);
+                return Ok(Some(frame));
+            }
+            Err(frame::Error::Incomplete) => reader.consume(read),
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connection::{read_frame_from, Connection, Error};
+    use crate::frame::Frame;
+    use claims::{assert_err, assert_ok};
+    use std::io;
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn read_frame_clean_close_returns_none() {
+        // Arrange
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut connection = Connection::new(server);
+        client.write_all(b"+OK\r\n").await.unwrap();
+        drop(client);
+
+        // Act
+        let first = connection.read_frame().await;
+        let second = connection.read_frame().await;
+
+        // Assert
+        assert_eq!(first.unwrap(), Some(Frame::ok()));
+        assert_ok!(&second);
+        assert!(second.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn read_frame_close_mid_frame_connection_reset() {
+        // Arrange
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut connection = Connection::new(server);
+        client.write_all(b"$5\r\nhel").await.unwrap();
+        drop(client);
+
+        // Act
+        let frame = connection.read_frame().await;
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::ConnectionReset)));
+    }
+
+    #[tokio::test]
+    async fn read_frame_from_reads_concatenated_frames() {
+        // Arrange
+        let input: &[u8] = b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n+OK\r\n:42\r\n$5\r\nhel";
+        let mut reader = BufReader::with_capacity(4, input);
+
+        // Act
+        let command = read_frame_from(&mut reader).await.unwrap();
+        let simple = read_frame_from(&mut reader).await.unwrap();
+        let integer = read_frame_from(&mut reader).await.unwrap();
+        let truncated = read_frame_from(&mut reader).await;
+        let end = read_frame_from(&mut reader).await;
+
+        // Assert
+        assert_eq!(
+            command,
+            Some(Frame::Array(vec![
+                Frame::Bulk("GET".into()),
+                Frame::Bulk("key".into())
+            ]))
+        );
+        assert_eq!(simple, Some(Frame::ok()));
+        assert_eq!(integer, Some(Frame::Integer(42)));
+        assert!(
+            matches!(truncated, Err(Error::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof)
+        );
+        assert!(end.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn write_frame_streams_large_bulk_intact() {
+        // Arrange
+        let (client, server) = tokio::io::duplex(1024);
+        let mut writer = Connection::new(server);
+        let mut reader = Connection::new(client);
+        let payload: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let frame = Frame::Bulk(payload.into());
+
+        // Act
+        let (written, read) = tokio::join!(writer.write_frame(&frame), reader.read_frame());
+
+        // Assert
+        assert_ok!(written);
+        assert_eq!(read.unwrap(), Some(frame));
+    }
+}
@@ -1,57 +1,4167 @@
-use bytes::Bytes;
-use std::collections::HashMap;
-use std::hash::{DefaultHasher, Hash, Hasher};
+use crate::config::EvictionPolicy;
+use bytes::{Bytes, BytesMut};
+use rand::seq::SliceRandom;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hash as _, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::{self, JoinHandle};
+use tokio::time::Instant;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value exceeds maximum size")]
+    ValueTooLarge,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("OOM command not allowed when used memory > 'maxmemory'.")]
+    OutOfMemory,
+    #[error("ERR value is not a valid float")]
+    NotAFloat,
+    #[error("ERR hash value is not a float")]
+    HashNotAFloat,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("ERR The ID specified in XADD is equal or smaller than the target stream top item")]
+    StreamIdTooSmall,
+    #[error("ERR The ID specified in XADD must be greater than 0-0")]
+    StreamIdZero,
+    #[error("ERR value is out of range")]
+    ValueOutOfRange,
+    #[error("ERR number of shards must be positive")]
+    NoShards,
+    #[error("ERR database has other handles")]
+    SharedHandle,
+}
+
+/// How many shards `ShardedDb::new` splits keys over.
+pub const DEFAULT_SHARDS: usize = 8;
+
+/// Most keys `purge_expired` deletes from one shard per call, so a burst of
+/// deadlines passing together is spread over several expire cycles instead
+/// of holding the shard lock for all of them.
+const ACTIVE_EXPIRE_LIMIT: usize = 200;
+
+/// The largest sample SRANDMEMBER and HRANDFIELD may ask for, so a negative
+/// count can't make the reply allocate an absurd number of repeats. A larger
+/// count is refused with `Error::ValueOutOfRange`.
+pub const MAX_SAMPLE: u64 = 1 << 20;
 
 #[derive(Clone)]
 pub struct ShardedDb {
     inner: Arc<Vec<Mutex<InnerDb>>>,
+    /// Whether `purge_expired` deletes anything, so tests can watch lazy
+    /// expiry alone.
+    active_expire: Arc<AtomicBool>,
+    /// The `ListLimit` lists are kept packed under.
+    list_limit: Arc<AtomicI64>,
+    /// What every shard's `used_memory` adds up to.
+    used_memory: Arc<AtomicUsize>,
+    /// Mixed into every shard hash, so keys crafted to pile into one shard
+    /// only do so against a known seed. The maps inside each shard are keyed
+    /// randomly by the standard library already.
+    seed: u64,
+    /// Whether reads through this handle count as accesses for LRU and LFU.
+    /// Each connection has its own handle, so CLIENT NO-TOUCH can turn it off.
+    touch: bool,
 }
 
 struct InnerDb {
-    db: HashMap<String, Bytes>,
+    db: HashMap<Bytes, Entry>,
+    expired_keys: u64,
+    evicted_keys: u64,
+    deleted_keys: DeletedKeys,
+    /// Running total of `entry_size` over every entry, adjusted by each write
+    /// rather than recomputed.
+    used_memory: usize,
+    /// The database-wide total of `used_memory`, adjusted along with it so
+    /// reading it takes no shard lock.
+    total_memory: Arc<AtomicUsize>,
+    /// Every key, and every key with a deadline, for drawing eviction
+    /// candidates at random.
+    keys: KeyPool,
+    volatile: KeyPool,
+    /// Every key with a deadline, ordered by it, so active expiry only looks
+    /// at keys that are due instead of scanning the whole shard.
+    deadlines: BTreeSet<(Instant, Bytes)>,
+    /// Every key by its `key_hash`, the order SCAN walks the shard in, so a
+    /// cursor stays valid however keys come and go or the map rehashes.
+    order: BTreeSet<(u64, Bytes)>,
+    /// The seed `key_hash` is taken with, the database's.
+    seed: u64,
 }
 
-impl ShardedDb {
-    pub fn new() -> Self {
-        Self::new_sized(8)
+impl InnerDb {
+    /// Looks up `key`, lazily deleting it if its deadline has passed, along
+    /// with any hash fields whose own deadline has.
+    fn live(&mut self, key: &[u8]) -> Option<&mut Entry> {
+        self.remove_if_expired(key);
+        self.remove_expired_fields(key);
+        self.db.get_mut(key)
     }
 
-    pub fn new_sized(num_shards: usize) -> Self {
-        let mut db_shards = Vec::with_capacity(num_shards);
-        for _ in 0..num_shards {
-            db_shards.push(Mutex::new(InnerDb { db: HashMap::new() }));
+    /// Like `live`, but creates the entry with `value` when it is missing.
+    fn live_or_insert_with(&mut self, key: &[u8], value: impl FnOnce() -> Value) -> &mut Entry {
+        self.remove_if_expired(key);
+        self.remove_expired_fields(key);
+        if !self.db.contains_key(key) {
+            self.insert_entry(key, Entry::new(value()));
         }
+        self.db.get_mut(key).expect("inserted above")
+    }
 
-        ShardedDb {
-            inner: Arc::new(db_shards),
+    fn insert_entry(&mut self, key: &[u8], entry: Entry) -> Option<Entry> {
+        self.insert_owned(Bytes::copy_from_slice(key), entry)
+    }
+
+    fn insert_owned(&mut self, key: Bytes, entry: Entry) -> Option<Entry> {
+        let deadline = entry.expires_at;
+        let size = entry_size(&key, &entry.value);
+        let previous = self.db.insert(key.clone(), entry);
+        match &previous {
+            Some(previous) => self.resized(entry_size(&key, &previous.value), size),
+            None => {
+                self.resized(0, size);
+                self.order.insert((key_hash(self.seed, &key), key.clone()));
+                self.keys.insert(&key);
+            }
         }
+        self.track_deadline(
+            &key,
+            previous.as_ref().and_then(|entry| entry.expires_at),
+            deadline,
+        );
+        previous
     }
 
-    pub fn get(&self, key: &str) -> Option<Bytes> {
-        let guard = self.guard(key);
-        guard.db.get(key).cloned()
+    fn remove_entry(&mut self, key: &[u8]) -> Option<Entry> {
+        let (key, entry) = self.db.remove_entry(key)?;
+        self.resized(entry_size(&key, &entry.value), 0);
+        self.track_deadline(&key, entry.expires_at, None);
+        self.keys.remove(&key);
+        self.order.remove(&(key_hash(self.seed, &key), key));
+        Some(entry)
     }
 
-    pub fn insert(&mut self, key: &str, value: Bytes) -> Option<Bytes> {
-        let mut guard = self.guard(key);
-        guard.db.insert(key.to_string(), value)
+    /// Empties the shard, handing back its entries.
+    fn take(&mut self) -> HashMap<Bytes, Entry> {
+        self.resized(self.used_memory, 0);
+        self.deadlines.clear();
+        self.order.clear();
+        self.keys = KeyPool::default();
+        self.volatile = KeyPool::default();
+        std::mem::take(&mut self.db)
+    }
+
+    /// Keeps `deadlines` in step with the deadline of `key` going from
+    /// `before` to `after`.
+    fn track_deadline(&mut self, key: &Bytes, before: Option<Instant>, after: Option<Instant>) {
+        if before == after {
+            return;
+        }
+        match before {
+            Some(before) => {
+                self.deadlines.remove(&(before, key.clone()));
+            }
+            None => self.volatile.insert(key),
+        }
+        match after {
+            Some(after) => {
+                self.deadlines.insert((after, key.clone()));
+            }
+            None => self.volatile.remove(key),
+        }
+    }
+
+    /// Accounts for a value changing in place from `before` to `after` bytes.
+    fn resized(&mut self, before: usize, after: usize) {
+        self.used_memory = self.used_memory + after - before;
+        if after >= before {
+            self.total_memory
+                .fetch_add(after - before, Ordering::Relaxed);
+        } else {
+            self.total_memory
+                .fetch_sub(before - after, Ordering::Relaxed);
+        }
+    }
+
+    fn remove_if_expired(&mut self, key: &[u8]) {
+        if self.db.get(key).is_some_and(Entry::is_expired) {
+            self.remove_entry(key);
+            self.expired_keys += 1;
+        }
+    }
+
+    /// Drops expired fields of the hash at `key`, and the key itself once no
+    /// fields are left.
+    fn remove_expired_fields(&mut self, key: &[u8]) {
+        let Some(entry) = self.db.get_mut(key) else {
+            return;
+        };
+        let Value::Hash(hash) = &mut entry.value else {
+            return;
+        };
+
+        let before = hash.heap_size();
+        if hash.remove_expired() == 0 {
+            return;
+        }
+        let (after, is_empty) = (hash.heap_size(), hash.is_empty());
+        entry.modified();
+        self.resized(before, after);
+        if is_empty {
+            self.remove_entry(key);
+        }
+    }
+
+    /// Fails unless `key` is missing or holds a list.
+    fn check_list(&mut self, key: &[u8]) -> Result<()> {
+        match self.live(key).map(|entry| &entry.value) {
+            None | Some(Value::List(_)) => Ok(()),
+            Some(_) => Err(Error::WrongType),
+        }
+    }
+
+    /// Pops one element from `end` of the list at `key`, removing the key once
+    /// the list is empty.
+    fn list_pop_one(&mut self, key: &[u8], end: End, limit: ListLimit) -> Result<Option<Bytes>> {
+        let Some(entry) = self.live(key) else {
+            return Ok(None);
+        };
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = list.heap_size();
+        let Some(popped) = list.pop(end) else {
+            return Ok(None);
+        };
+        list.fit(limit);
+        let (after, is_empty) = (list.heap_size(), list.is_empty());
+        entry.modified();
+        self.resized(before, after);
+        if is_empty {
+            self.remove_entry(key);
+        }
+        Ok(Some(popped))
     }
 
-    fn guard(&self, key: &str) -> MutexGuard<InnerDb> {
-        let shard = Self::shard(key, self.inner.len());
-        self.inner[shard].lock().unwrap()
+    /// Pushes `value` onto `end` of the list at `key`, creating it if needed.
+    /// The key must have passed `check_list`.
+    fn list_push_one(&mut self, key: &[u8], end: End, value: Bytes, limit: ListLimit) {
+        let entry = self.live_or_insert_with(key, || Value::List(List::default()));
+        let Value::List(list) = &mut entry.value else {
+            unreachable!("checked to hold a list");
+        };
+
+        let before = list.heap_size();
+        list.push(end, value);
+        list.fit(limit);
+        let after = list.heap_size();
+        entry.modified();
+        self.resized(before, after);
     }
 
-    fn shard(key: &str, num_shards: usize) -> usize {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        (hasher.finish() % num_shards as u64) as usize
+    /// Picks the key `policy` would evict first among a handful of candidates
+    /// drawn at random, the way Redis samples rather than keeping keys
+    /// ordered. The volatile policies draw from the keys with a deadline.
+    fn eviction_candidate(&self, policy: EvictionPolicy) -> Option<Bytes> {
+        const SAMPLES: usize = 5;
+
+        if policy == EvictionPolicy::NoEviction {
+            return None;
+        }
+        let volatile = matches!(
+            policy,
+            EvictionPolicy::VolatileLru
+                | EvictionPolicy::VolatileLfu
+                | EvictionPolicy::VolatileRandom
+                | EvictionPolicy::VolatileTtl
+        );
+        let pool = if volatile { &self.volatile } else { &self.keys };
+        let mut candidates = pool
+            .sample(SAMPLES)
+            .filter_map(|key| self.db.get_key_value(key));
+
+        let (key, _) = match policy {
+            EvictionPolicy::NoEviction => unreachable!("returned above"),
+            EvictionPolicy::AllKeysRandom | EvictionPolicy::VolatileRandom => candidates.next()?,
+            EvictionPolicy::VolatileTtl => candidates.min_by_key(|(_, entry)| entry.expires_at)?,
+            EvictionPolicy::AllKeysLru | EvictionPolicy::VolatileLru => {
+                candidates.min_by_key(|(_, entry)| entry.last_access)?
+            }
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu => {
+                candidates.min_by_key(|(_, entry)| entry.frequency())?
+            }
+        };
+        Some(key.clone())
     }
 }
 
-impl Default for ShardedDb {
+/// Evicts keys chosen by `policy` from any of `dbs` until their `used_memory`
+/// adds up to at most `limit`, returning how many went: `maxmemory` caps the
+/// server as a whole rather than each database. Fails when nothing more can
+/// be evicted while still over the limit.
+pub fn evict(dbs: &[ShardedDb], limit: usize, policy: EvictionPolicy) -> Result<usize> {
+    let used = || dbs.iter().map(ShardedDb::used_memory).sum::<usize>();
+    if policy == EvictionPolicy::NoEviction {
+        return if used() > limit {
+            Err(Error::OutOfMemory)
+        } else {
+            Ok(0)
+        };
+    }
+
+    let shards: Vec<&Mutex<InnerDb>> = dbs.iter().flat_map(|db| db.inner.iter()).collect();
+    let mut evicted = 0;
+    let mut exhausted = 0;
+    let mut shard = 0;
+
+    while used() > limit {
+        if exhausted == shards.len() {
+            return Err(Error::OutOfMemory);
+        }
+
+        let mut guard = shards[shard].lock().unwrap();
+        match guard.eviction_candidate(policy) {
+            Some(key) => {
+                guard.remove_entry(&key);
+                guard.evicted_keys += 1;
+                evicted += 1;
+                exhausted = 0;
+            }
+            None => exhausted += 1,
+        }
+        shard = (shard + 1) % shards.len();
+    }
+
+    Ok(evicted)
+}
+
+/// Keys kept in a vector alongside their positions in it, so that one can be
+/// drawn at random, or removed, in constant time.
+#[derive(Default)]
+struct KeyPool {
+    keys: Vec<Bytes>,
+    positions: HashMap<Bytes, usize>,
+}
+
+impl KeyPool {
+    fn insert(&mut self, key: &Bytes) {
+        if self.positions.contains_key(key) {
+            return;
+        }
+        self.positions.insert(key.clone(), self.keys.len());
+        self.keys.push(key.clone());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        let Some(position) = self.positions.remove(key) else {
+            return;
+        };
+        self.keys.swap_remove(position);
+        if let Some(moved) = self.keys.get(position) {
+            self.positions.insert(moved.clone(), position);
+        }
+    }
+
+    /// Up to `amount` distinct keys, drawn at random.
+    fn sample(&self, amount: usize) -> impl Iterator<Item = &Bytes> {
+        let amount = amount.min(self.keys.len());
+        rand::seq::index::sample(&mut rand::thread_rng(), self.keys.len(), amount)
+            .into_iter()
+            .map(|index| &self.keys[index])
+    }
+}
+
+/// Estimated bytes used by an entry: the key, the entry bookkeeping and the
+/// value.
+fn entry_size(key: &[u8], value: &Value) -> usize {
+    key.len() + std::mem::size_of::<Entry>() + value.heap_size()
+}
+
+fn set_member_size(value: &Bytes) -> usize {
+    std::mem::size_of::<Bytes>() + 1 + value.len()
+}
+
+fn hash_field_size(field: &Bytes, value: &Bytes) -> usize {
+    2 * std::mem::size_of::<Bytes>() + 1 + field.len() + value.len()
+}
+
+const HASH_DEADLINE_SIZE: usize = std::mem::size_of::<(Bytes, Instant)>() + 1;
+
+fn stream_entry_size(fields: &[(Bytes, Bytes)]) -> usize {
+    std::mem::size_of::<StreamEntry>()
+        + fields
+            .iter()
+            .map(|(field, value)| 2 * std::mem::size_of::<Bytes>() + field.len() + value.len())
+            .sum::<usize>()
+}
+
+/// Source of entry versions. Shared by every database so that a key deleted
+/// and recreated never comes back with a version seen before.
+static VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Redis's LFU defaults: new keys start at 5 so they aren't evicted before
+/// they get a chance to be read, each increment is about ten times less
+/// likely than the last, and the counter drops by one per idle minute.
+const LFU_INIT_VAL: u8 = 5;
+const LFU_LOG_FACTOR: f64 = 10.0;
+const LFU_DECAY_PERIOD: Duration = Duration::from_secs(60);
+
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+    last_access: Instant,
+    /// Logarithmic access counter for the LFU policies, as of `last_access`.
+    frequency: u8,
+    version: u64,
+}
+
+impl Entry {
+    fn new(value: Value) -> Self {
+        Self {
+            value,
+            expires_at: None,
+            last_access: Instant::now(),
+            frequency: LFU_INIT_VAL,
+            version: VERSION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Records a read or write: decays the access counter for the time since
+    /// the last one, then increments it with a probability that falls as it
+    /// grows, so 255 takes around a million accesses.
+    fn accessed(&mut self) {
+        let mut frequency = self.frequency();
+        if frequency < u8::MAX {
+            let base = f64::from(frequency.saturating_sub(LFU_INIT_VAL));
+            if rand::random::<f64>() < 1.0 / (base * LFU_LOG_FACTOR + 1.0) {
+                frequency += 1;
+            }
+        }
+        self.frequency = frequency;
+        self.last_access = Instant::now();
+    }
+
+    /// Records a write, bumping the version WATCH compares against.
+    fn modified(&mut self) {
+        self.accessed();
+        self.version = VERSION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The access counter decayed to now.
+    fn frequency(&self) -> u8 {
+        let periods = self.last_access.elapsed().as_secs() / LFU_DECAY_PERIOD.as_secs();
+        self.frequency
+            .saturating_sub(u8::try_from(periods).unwrap_or(u8::MAX))
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(Bytes),
+    List(List),
+    Set(HashSet<Bytes>),
+    Hash(Hash),
+    Stream(Stream),
+}
+
+/// Hash fields, any of which may carry a deadline of its own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hash {
+    fields: HashMap<Bytes, Bytes>,
+    deadlines: HashMap<Bytes, Instant>,
+    /// `heap_size`, kept current by every change.
+    size: usize,
+}
+
+impl Hash {
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        self.fields.get(field)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        self.fields.iter()
+    }
+
+    pub fn expires_at(&self, field: &[u8]) -> Option<Instant> {
+        self.deadlines.get(field).copied()
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.size
+    }
+
+    /// Sets `field`, clearing any deadline it had. Returns the previous value.
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> Option<Bytes> {
+        self.persist(&field);
+        self.size += hash_field_size(&field, &value);
+        let previous = self.fields.insert(field.clone(), value);
+        if let Some(previous) = &previous {
+            self.size -= hash_field_size(&field, previous);
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, field: &[u8]) -> Option<Bytes> {
+        self.persist(field);
+        let (field, value) = self.fields.remove_entry(field)?;
+        self.size -= hash_field_size(&field, &value);
+        Some(value)
+    }
+
+    /// Returns false when there is no such field.
+    pub fn set_expires_at(&mut self, field: &[u8], deadline: Instant) -> bool {
+        let Some((field, _)) = self.fields.get_key_value(field) else {
+            return false;
+        };
+        if self.deadlines.insert(field.clone(), deadline).is_none() {
+            self.size += HASH_DEADLINE_SIZE;
+        }
+        true
+    }
+
+    /// Clears the deadline of `field`, returning whether it had one.
+    pub fn persist(&mut self, field: &[u8]) -> bool {
+        if self.deadlines.remove(field).is_none() {
+            return false;
+        }
+        self.size -= HASH_DEADLINE_SIZE;
+        true
+    }
+
+    /// Removes every field whose deadline has passed, returning how many.
+    fn remove_expired(&mut self) -> usize {
+        if self.deadlines.is_empty() {
+            return 0;
+        }
+
+        let now = Instant::now();
+        let expired: Vec<Bytes> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(field, _)| field.clone())
+            .collect();
+        for field in &expired {
+            self.remove(field);
+        }
+        expired.len()
+    }
+}
+
+/// How large a list may grow and stay packed, read the way Redis reads
+/// `list-max-listpack-size`: a positive count of elements, or a size class
+/// from -1 (4 KB) to -5 (64 KB) capping the packed bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ListLimit(i64);
+
+impl ListLimit {
+    pub const DEFAULT: Self = Self(-2);
+
+    pub fn new(limit: i64) -> Option<Self> {
+        (limit != 0 && limit >= -5).then_some(Self(limit))
+    }
+
+    pub fn get(self) -> i64 {
+        self.0
+    }
+
+    fn allows(self, len: usize, packed_size: usize) -> bool {
+        match usize::try_from(self.0) {
+            Ok(max_len) => len <= max_len,
+            Err(_) => packed_size <= 4096 << (self.0.unsigned_abs() - 1),
+        }
+    }
+}
+
+/// List elements, packed into a single buffer while the list is small and
+/// held in a deque once it outgrows its `ListLimit`, as Redis moves from a
+/// listpack to a quicklist.
+#[derive(Clone, Debug, Default)]
+pub struct List {
+    elements: ListElements,
+    /// Total length of the elements, whichever way they are held.
+    payload: usize,
+}
+
+#[derive(Clone, Debug)]
+enum ListElements {
+    /// Every element back to back, each behind its length as a `u32`.
+    Packed {
+        buf: Vec<u8>,
+        len: usize,
+    },
+    Deque(VecDeque<Bytes>),
+}
+
+impl Default for ListElements {
     fn default() -> Self {
-        Self::new()
+        ListElements::Packed {
+            buf: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl List {
+    pub fn len(&self) -> usize {
+        match &self.elements {
+            ListElements::Packed { len, .. } => *len,
+            ListElements::Deque(deque) => deque.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &[u8]> + '_> {
+        match &self.elements {
+            ListElements::Packed { buf, .. } => {
+                let mut rest = &buf[..];
+                Box::new(std::iter::from_fn(move || {
+                    let (element, next) = split_packed(rest)?;
+                    rest = next;
+                    Some(element)
+                }))
+            }
+            ListElements::Deque(deque) => Box::new(deque.iter().map(|element| &element[..])),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match &self.elements {
+            ListElements::Packed { .. } => "listpack",
+            ListElements::Deque(_) => "quicklist",
+        }
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.payload
+            + self.len()
+                * match &self.elements {
+                    ListElements::Packed { .. } => PACKED_HEADER_SIZE,
+                    ListElements::Deque(_) => std::mem::size_of::<Bytes>(),
+                }
+    }
+
+    pub fn push(&mut self, end: End, value: Bytes) {
+        let index = match end {
+            End::Left => 0,
+            End::Right => self.len(),
+        };
+        self.insert(index, value);
+    }
+
+    pub fn pop(&mut self, end: End) -> Option<Bytes> {
+        let index = match end {
+            End::Left => 0,
+            End::Right => self.len().checked_sub(1)?,
+        };
+        self.remove(index)
+    }
+
+    /// Inserts `value` before the element at `index`, or at the tail when
+    /// `index` is the length.
+    pub fn insert(&mut self, index: usize, value: Bytes) {
+        self.payload += value.len();
+        match &mut self.elements {
+            ListElements::Packed { buf, len } => {
+                let offset = if index == *len {
+                    buf.len()
+                } else {
+                    packed_offset(buf, index)
+                };
+                let header = (value.len() as u32).to_le_bytes();
+                buf.splice(offset..offset, header.into_iter().chain(value));
+                *len += 1;
+            }
+            ListElements::Deque(deque) => deque.insert(index, value),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Bytes> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let removed = match &mut self.elements {
+            ListElements::Packed { buf, len } => {
+                let offset = packed_offset(buf, index);
+                let (element, _) = split_packed(&buf[offset..]).expect("index is in range");
+                let removed = Bytes::copy_from_slice(element);
+                buf.drain(offset..offset + PACKED_HEADER_SIZE + removed.len());
+                *len -= 1;
+                removed
+            }
+            ListElements::Deque(deque) => deque.remove(index)?,
+        };
+        self.payload -= removed.len();
+        Some(removed)
+    }
+
+    /// Removes up to `limit` elements equal to `value` in one pass, the first
+    /// ones or, `from_tail`, the last ones. Returns how many went.
+    pub fn remove_matching(&mut self, value: &[u8], limit: usize, from_tail: bool) -> usize {
+        // from the tail, the matches before the last `limit` are kept
+        let mut skip = if from_tail {
+            let matches = self.iter().filter(|element| *element == value).count();
+            matches.saturating_sub(limit)
+        } else {
+            0
+        };
+        let mut remaining = limit;
+        let mut removes = |element: &[u8]| {
+            if element != value {
+                false
+            } else if skip > 0 {
+                skip -= 1;
+                false
+            } else if remaining > 0 {
+                remaining -= 1;
+                true
+            } else {
+                false
+            }
+        };
+
+        let removed = match &mut self.elements {
+            ListElements::Packed { buf, len } => {
+                let mut kept = Vec::with_capacity(buf.len());
+                let mut removed = 0;
+                let mut rest = &buf[..];
+                while let Some((element, next)) = split_packed(rest) {
+                    if removes(element) {
+                        removed += 1;
+                    } else {
+                        kept.extend_from_slice(&rest[..rest.len() - next.len()]);
+                    }
+                    rest = next;
+                }
+                *buf = kept;
+                *len -= removed;
+                removed
+            }
+            ListElements::Deque(deque) => {
+                let before = deque.len();
+                deque.retain(|element| !removes(element));
+                before - deque.len()
+            }
+        };
+        self.payload -= removed * value.len();
+        removed
+    }
+
+    /// Replaces the element at `index`, returning the one it held.
+    pub fn set(&mut self, index: usize, value: Bytes) -> Option<Bytes> {
+        let previous = self.remove(index)?;
+        self.insert(index, value);
+        Some(previous)
+    }
+
+    /// Switches to the deque once the packed elements exceed `limit`, and back
+    /// once they would fit twice over, so a list hovering at the limit does
+    /// not flip on every push and pop.
+    pub fn fit(&mut self, limit: ListLimit) {
+        let (len, packed_size) = (self.len(), self.payload + self.len() * PACKED_HEADER_SIZE);
+        match &mut self.elements {
+            ListElements::Packed { .. } if !limit.allows(len, packed_size) => {
+                let deque = self.iter().map(Bytes::copy_from_slice).collect();
+                self.elements = ListElements::Deque(deque);
+            }
+            ListElements::Deque(deque) if limit.allows(len * 2, packed_size * 2) => {
+                let mut buf = Vec::with_capacity(packed_size);
+                for element in deque.drain(..) {
+                    buf.extend_from_slice(&(element.len() as u32).to_le_bytes());
+                    buf.extend_from_slice(&element);
+                }
+                self.elements = ListElements::Packed { buf, len };
+            }
+            _ => {}
+        }
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+/// Packs the elements if they fit the default limit.
+impl FromIterator<Bytes> for List {
+    fn from_iter<T: IntoIterator<Item = Bytes>>(iter: T) -> Self {
+        let mut list = List::default();
+        for element in iter {
+            list.push(End::Right, element);
+            list.fit(ListLimit::DEFAULT);
+        }
+        list
+    }
+}
+
+const PACKED_HEADER_SIZE: usize = 4;
+
+/// Splits the first packed element off `buf`.
+fn split_packed(buf: &[u8]) -> Option<(&[u8], &[u8])> {
+    let header = buf.get(..PACKED_HEADER_SIZE)?;
+    let len = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+    Some(buf[PACKED_HEADER_SIZE..].split_at(len))
+}
+
+/// Where the element at `index` starts in `buf`, or its end for the length.
+fn packed_offset(buf: &[u8], index: usize) -> usize {
+    let mut rest = buf;
+    for _ in 0..index {
+        let (_, next) = split_packed(rest).expect("index is in range");
+        rest = next;
+    }
+    buf.len() - rest.len()
+}
+
+/// Identifies a stream entry: the millisecond it was added at, and a sequence
+/// number ordering the entries added within the same millisecond.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: Self = Self { ms: 0, seq: 0 };
+    pub const MAX: Self = Self {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    /// Parses `<ms>-<seq>`, or a bare `<ms>` taking `missing_seq` as its
+    /// sequence number.
+    pub fn parse(text: &str, missing_seq: u64) -> Option<Self> {
+        let (ms, seq) = match text.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse().ok()?),
+            None => (text, missing_seq),
+        };
+        Some(Self::new(ms.parse().ok()?, seq))
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// The id XADD asks for: generated from the clock, generated within a given
+/// millisecond, or given in full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NewStreamId {
+    Auto,
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+/// A stream entry's id and its field/value pairs.
+pub type StreamEntry = (StreamId, Vec<(Bytes, Bytes)>);
+
+/// Entries ordered by id, each a list of field/value pairs kept in the order
+/// they were given.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, Vec<(Bytes, Bytes)>>,
+    last_id: StreamId,
+    /// `heap_size`, kept current by every change.
+    size: usize,
+}
+
+impl Stream {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&StreamId, &Vec<(Bytes, Bytes)>)> {
+        self.entries.iter()
+    }
+
+    /// Entries with ids from `start` to `end`, both inclusive.
+    pub fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+    ) -> impl Iterator<Item = (&StreamId, &Vec<(Bytes, Bytes)>)> {
+        // `BTreeMap::range` panics on a reversed range.
+        (start <= end)
+            .then(|| self.entries.range(start..=end))
+            .into_iter()
+            .flatten()
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.size
+    }
+
+    /// Appends an entry under the id `id` resolves to, which must be greater
+    /// than every id before it. `now_ms` is the clock reading auto ids start
+    /// from; when the clock is behind the last id, the last id's millisecond
+    /// is reused so ids keep increasing.
+    pub fn add(
+        &mut self,
+        id: NewStreamId,
+        fields: Vec<(Bytes, Bytes)>,
+        now_ms: u64,
+    ) -> Result<StreamId> {
+        let last = self.last_id;
+        let next_in = |ms: u64| match ms.cmp(&last.ms) {
+            std::cmp::Ordering::Greater => Ok(StreamId::new(ms, 0)),
+            std::cmp::Ordering::Equal => last
+                .seq
+                .checked_add(1)
+                .map(|seq| StreamId::new(ms, seq))
+                .ok_or(Error::StreamIdTooSmall),
+            std::cmp::Ordering::Less => Err(Error::StreamIdTooSmall),
+        };
+        let id = match id {
+            NewStreamId::Auto => next_in(now_ms.max(last.ms))?,
+            NewStreamId::AutoSeq(ms) => next_in(ms)?,
+            NewStreamId::Explicit(StreamId::MIN) => return Err(Error::StreamIdZero),
+            NewStreamId::Explicit(id) if id <= last => return Err(Error::StreamIdTooSmall),
+            NewStreamId::Explicit(id) => id,
+        };
+
+        self.size += stream_entry_size(&fields);
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(id)
+    }
+}
+
+/// What a command does to the deadline of the key it writes or reads, as
+/// GETEX's options and SET's KEEPTTL and PERSIST say.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expiry {
+    Keep,
+    Persist,
+    In(Duration),
+    /// A wall-clock deadline; one already past expires the key at once.
+    At(SystemTime),
+}
+
+/// The conditions HEXPIRE (and EXPIRE in Redis) may put on replacing a
+/// deadline. A missing deadline counts as infinitely far away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpireCondition {
+    /// Only when there is no deadline yet.
+    Nx,
+    /// Only when there already is one.
+    Xx,
+    /// Only when the new deadline is later.
+    Gt,
+    /// Only when the new deadline is earlier.
+    Lt,
+}
+
+/// SET's NX and XX: whether it may write depends on the key existing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetCondition {
+    /// Only when the key is missing.
+    Nx,
+    /// Only when it already exists.
+    Xx,
+}
+
+impl Expiry {
+    /// Whether the deadline can be represented. Commands reply with an
+    /// invalid expire time for the rest, as Redis does when it overflows.
+    pub fn is_valid(self) -> bool {
+        match self {
+            Expiry::Keep | Expiry::Persist => true,
+            Expiry::In(ttl) => deadline_in(ttl).is_some(),
+            Expiry::At(at) => at
+                .duration_since(SystemTime::now())
+                .map_or(true, |ttl| deadline_in(ttl).is_some()),
+        }
+    }
+}
+
+impl ExpireCondition {
+    fn allows(self, current: Option<Instant>, new: Instant) -> bool {
+        match (self, current) {
+            (ExpireCondition::Nx, current) => current.is_none(),
+            (ExpireCondition::Xx, current) => current.is_some(),
+            (ExpireCondition::Gt, Some(current)) => new > current,
+            (ExpireCondition::Gt, None) => false,
+            (ExpireCondition::Lt, Some(current)) => new < current,
+            (ExpireCondition::Lt, None) => true,
+        }
+    }
+}
+
+impl Value {
+    /// Mirrors Redis's encodings: strings are `int` when they round-trip as an
+    /// i64, `embstr` when short and `raw` otherwise.
+    pub fn encoding(&self) -> &'static str {
+        const EMBSTR_SIZE_LIMIT: usize = 44;
+
+        match self {
+            Value::String(value) => {
+                let is_int = value.len() <= 20
+                    && std::str::from_utf8(value)
+                        .ok()
+                        .and_then(|value| value.parse::<i64>().ok())
+                        .is_some_and(|num| num.to_string().as_bytes() == value.as_ref());
+
+                if is_int {
+                    "int"
+                } else if value.len() <= EMBSTR_SIZE_LIMIT {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            Value::List(list) => list.encoding(),
+            Value::Set(_) | Value::Hash(_) => "hashtable",
+            Value::Stream(_) => "stream",
+        }
+    }
+
+    /// The name Redis gives the value's type, as SCAN's TYPE option takes it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::Hash(_) => "hash",
+            Value::Stream(_) => "stream",
+        }
+    }
+
+    /// Estimated bytes owned by the value: the payload of every string plus a
+    /// `Bytes` handle per collection element, and a control byte per set slot.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Value::String(value) => value.len(),
+            Value::List(list) => list.heap_size(),
+            Value::Set(set) => set.iter().map(set_member_size).sum(),
+            Value::Hash(hash) => hash.heap_size(),
+            Value::Stream(stream) => stream.heap_size(),
+        }
+    }
+}
+
+/// Entries taken out of the database by `ShardedDb::flush`, freed whenever
+/// this is dropped.
+pub struct Flushed {
+    entries: Vec<HashMap<Bytes, Entry>>,
+    size: usize,
+}
+
+impl Flushed {
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Estimated bytes the entries held, as counted in `used_memory`.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Drops `value` on a blocking thread if `size`, its estimated footprint, is
+/// above `threshold`, and right here otherwise, since handing off a small
+/// value costs more than freeing it. Returns the background drop, if any.
+pub fn free_lazily<T: Send + 'static>(
+    value: T,
+    size: usize,
+    threshold: usize,
+) -> Option<JoinHandle<()>> {
+    if size <= threshold {
+        drop(value);
+        return None;
+    }
+
+    Some(task::spawn_blocking(move || drop(value)))
+}
+
+/// Keys removed by DEL, counted by the type of value they held.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DeletedKeys {
+    pub strings: u64,
+    pub lists: u64,
+    pub sets: u64,
+    pub hashes: u64,
+    pub streams: u64,
+}
+
+impl DeletedKeys {
+    fn record(&mut self, value: &Value) {
+        let counter = match value {
+            Value::String(_) => &mut self.strings,
+            Value::List(_) => &mut self.lists,
+            Value::Set(_) => &mut self.sets,
+            Value::Hash(_) => &mut self.hashes,
+            Value::Stream(_) => &mut self.streams,
+        };
+        *counter += 1;
+    }
+
+    /// Adds `other`'s counts to these, to total several shards or databases.
+    pub fn add(&mut self, other: DeletedKeys) {
+        self.strings += other.strings;
+        self.lists += other.lists;
+        self.sets += other.sets;
+        self.hashes += other.hashes;
+        self.streams += other.streams;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum End {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Position {
+    Before,
+    After,
+}
+
+impl ShardedDb {
+    pub fn new() -> Self {
+        Self::new_sized(DEFAULT_SHARDS)
+    }
+
+    pub fn new_sized(num_shards: usize) -> Self {
+        Self::new_seeded(num_shards, rand::random())
+    }
+
+    /// Like `new_sized`, with a fixed shard hash seed rather than a random
+    /// one, so key placement can be reproduced.
+    pub fn new_seeded(num_shards: usize, seed: u64) -> Self {
+        Self::with_capacity(num_shards, seed, 0)
+    }
+
+    /// An empty database split and seeded like this one, sharing its
+    /// active-expire and list-limit settings, so a server's databases can be
+    /// configured as one.
+    pub fn sibling(&self) -> Self {
+        Self {
+            active_expire: self.active_expire.clone(),
+            list_limit: self.list_limit.clone(),
+            ..Self::new_seeded(self.inner.len(), self.seed)
+        }
+    }
+
+    /// Like `new_seeded`, reserving room in each shard for its share of
+    /// `expected_keys` up front, so filling it to that size never stalls an
+    /// insert on a rehash.
+    pub fn with_capacity(num_shards: usize, seed: u64, expected_keys: usize) -> Self {
+        let per_shard = expected_keys.div_ceil(num_shards.max(1));
+        let used_memory = Arc::new(AtomicUsize::new(0));
+        let mut db_shards = Vec::with_capacity(num_shards);
+        for _ in 0..num_shards {
+            db_shards.push(Mutex::new(InnerDb {
+                db: HashMap::with_capacity(per_shard),
+                expired_keys: 0,
+                evicted_keys: 0,
+                deleted_keys: DeletedKeys::default(),
+                used_memory: 0,
+                total_memory: used_memory.clone(),
+                keys: KeyPool::default(),
+                volatile: KeyPool::default(),
+                deadlines: BTreeSet::new(),
+                order: BTreeSet::new(),
+                seed,
+            }));
+        }
+
+        ShardedDb {
+            inner: Arc::new(db_shards),
+            active_expire: Arc::new(AtomicBool::new(true)),
+            list_limit: Arc::new(AtomicI64::new(ListLimit::DEFAULT.get())),
+            used_memory,
+            seed,
+            touch: true,
+        }
+    }
+
+    pub fn touches(&self) -> bool {
+        self.touch
+    }
+
+    pub fn set_touch(&mut self, touch: bool) {
+        self.touch = touch;
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .iter()
+            .map(|shard| shard.lock().unwrap().db.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(None);
+        };
+
+        self.touch(entry);
+        match &entry.value {
+            Value::String(value) => Ok(Some(value.clone())),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    /// Like `get`, also changing the key's deadline as `expiry` says, under the
+    /// same lock.
+    pub fn get_ex(&mut self, key: impl AsRef<[u8]>, expiry: Expiry) -> Result<Option<Bytes>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(None);
+        };
+        let Value::String(value) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+        let value = value.clone();
+
+        self.touch(entry);
+        let expires_at = match expiry {
+            Expiry::Keep => return Ok(Some(value)),
+            Expiry::Persist => None,
+            Expiry::In(ttl) => deadline_in(ttl),
+            Expiry::At(at) => match at.duration_since(SystemTime::now()) {
+                Ok(ttl) => deadline_in(ttl),
+                Err(_) => {
+                    guard.remove_entry(key);
+                    return Ok(Some(value));
+                }
+            },
+        };
+        let before = std::mem::replace(&mut entry.expires_at, expires_at);
+        entry.modified();
+        guard.track_deadline(&Bytes::copy_from_slice(key), before, expires_at);
+        Ok(Some(value))
+    }
+
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, value: Bytes) -> Option<Value> {
+        let key = key.as_ref();
+        self.insert_with_ttl(key, value, None)
+    }
+
+    /// Overwrites `key` with a string, replacing any previous TTL with `ttl`.
+    pub fn insert_with_ttl(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        value: Bytes,
+        ttl: Option<Duration>,
+    ) -> Option<Value> {
+        let key = key.as_ref();
+        self.insert_with_expiry(key, value, ttl.map_or(Expiry::Persist, Expiry::In))
+    }
+
+    /// Overwrites `key` with a string, its deadline set as `expiry` says.
+    /// `Expiry::Keep` carries over the deadline of the value overwritten.
+    pub fn insert_with_expiry(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        value: Bytes,
+        expiry: Expiry,
+    ) -> Option<Value> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.remove_if_expired(key);
+        let entry = string_entry(guard.db.get(key), value, expiry);
+        guard.insert_entry(key, entry).map(|entry| entry.value)
+    }
+
+    /// Like `insert_with_expiry`, but only when `condition` (if any) holds,
+    /// checked under the same lock. Returns whether it wrote and the string it
+    /// found there; with `get`, a value of another type is `WrongType` and
+    /// left alone rather than overwritten.
+    pub fn insert_if(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        value: Bytes,
+        expiry: Expiry,
+        condition: Option<SetCondition>,
+        get: bool,
+    ) -> Result<(bool, Option<Bytes>)> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let (exists, previous) = match guard.live(key).map(|entry| &entry.value) {
+            Some(Value::String(current)) => (true, Some(current.clone())),
+            Some(_) if get => return Err(Error::WrongType),
+            Some(_) => (true, None),
+            None => (false, None),
+        };
+
+        let write = match condition {
+            Some(SetCondition::Nx) => !exists,
+            Some(SetCondition::Xx) => exists,
+            None => true,
+        };
+        if write {
+            let entry = string_entry(guard.db.get(key), value, expiry);
+            guard.insert_entry(key, entry);
+        }
+        Ok((write, previous))
+    }
+
+    /// Appends `value` to the string at `key`, creating it if needed. Returns
+    /// the new length.
+    pub fn append(&mut self, key: impl AsRef<[u8]>, value: &[u8], max_len: usize) -> Result<usize> {
+        let key = key.as_ref();
+        self.splice_string(key, None, value, max_len)
+    }
+
+    /// Adds `increment` to the float stored at `key`, a missing key counting
+    /// as zero, and returns the new value as stored. Keeps any TTL.
+    pub fn incr_by_float(&mut self, key: impl AsRef<[u8]>, increment: f64) -> Result<Bytes> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let (base, current_len) = match guard.live(key).map(|entry| &entry.value) {
+            Some(Value::String(current)) => {
+                (parse_float(current).ok_or(Error::NotAFloat)?, current.len())
+            }
+            Some(_) => return Err(Error::WrongType),
+            None => (0.0, 0),
+        };
+
+        let updated = Bytes::from(format_float(base + increment)?);
+        match guard.db.get_mut(key) {
+            Some(entry) => {
+                entry.value = Value::String(updated.clone());
+                entry.modified();
+                guard.resized(current_len, updated.len());
+            }
+            None => {
+                guard.insert_entry(key, Entry::new(Value::String(updated.clone())));
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Overwrites the string at `key` from `offset` on, zero-padding any gap.
+    /// Returns the new length. An empty `value` never creates the key.
+    pub fn set_range(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        offset: usize,
+        value: &[u8],
+        max_len: usize,
+    ) -> Result<usize> {
+        let key = key.as_ref();
+        self.splice_string(key, Some(offset), value, max_len)
+    }
+
+    /// Replaces the string at `key` with `new` only if it currently equals
+    /// `expected`, keeping any TTL. A missing key never matches. `new` may be
+    /// at most `max_len` bytes.
+    pub fn compare_and_swap(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        expected: &[u8],
+        new: Bytes,
+        max_len: usize,
+    ) -> Result<bool> {
+        if new.len() > max_len {
+            return Err(Error::ValueTooLarge);
+        }
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(false);
+        };
+        let Value::String(current) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        if current != expected {
+            self.touch(entry);
+            return Ok(false);
+        }
+
+        let (before, after) = (current.len(), new.len());
+        *current = new;
+        entry.modified();
+        guard.resized(before, after);
+        Ok(true)
+    }
+
+    /// Sets a deadline on an existing key. Returns whether the key exists.
+    pub fn expire(&mut self, key: impl AsRef<[u8]>, ttl: Duration) -> bool {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return false;
+        };
+        let deadline = deadline_in(ttl);
+        let before = std::mem::replace(&mut entry.expires_at, deadline);
+        entry.modified();
+        guard.track_deadline(&Bytes::copy_from_slice(key), before, deadline);
+        true
+    }
+
+    /// Remaining time to live, `Some(None)` for a key without a deadline and
+    /// `None` for a missing key.
+    pub fn ttl(&self, key: impl AsRef<[u8]>) -> Option<Option<Duration>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let entry = guard.live(key)?;
+        Some(
+            entry
+                .expires_at
+                .map(|expires_at| expires_at.saturating_duration_since(Instant::now())),
+        )
+    }
+
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Option<Value> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.remove_if_expired(key);
+        guard.remove_entry(key).map(|entry| entry.value)
+    }
+
+    /// Like `remove`, but counted in `deleted_keys`, as DEL is.
+    pub fn delete(&mut self, key: impl AsRef<[u8]>) -> Option<Value> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.remove_if_expired(key);
+        let value = guard.remove_entry(key)?.value;
+        guard.deleted_keys.record(&value);
+        Some(value)
+    }
+
+    /// Pushes `values` one at a time onto `end`, creating the list if needed.
+    /// Returns the length of the list afterwards.
+    pub fn list_push(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        end: End,
+        values: Vec<Bytes>,
+    ) -> Result<usize> {
+        let key = key.as_ref();
+        let limit = self.list_limit();
+        let mut guard = self.guard(key);
+        let entry = guard.live_or_insert_with(key, || Value::List(List::default()));
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = list.heap_size();
+        for value in values {
+            list.push(end, value);
+            list.fit(limit);
+        }
+
+        let (len, after) = (list.len(), list.heap_size());
+        entry.modified();
+        guard.resized(before, after);
+        Ok(len)
+    }
+
+    /// Pops up to `count` elements from `end`, removing the key once the list
+    /// is empty. Returns `None` when the key does not exist.
+    pub fn list_pop(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        end: End,
+        count: usize,
+    ) -> Result<Option<Vec<Bytes>>> {
+        let key = key.as_ref();
+        let limit = self.list_limit();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(None);
+        };
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = list.heap_size();
+        let count = count.min(list.len());
+        let popped: Vec<Bytes> = (0..count).filter_map(|_| list.pop(end)).collect();
+        list.fit(limit);
+
+        let (after, is_empty) = (list.heap_size(), list.is_empty());
+        if count > 0 {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(before, after);
+        if is_empty {
+            guard.remove_entry(key);
+        }
+
+        Ok(Some(popped))
+    }
+
+    /// Pops an element from `from` of the list at `source` and pushes it onto
+    /// `to` of the list at `destination`, returning it, or `None` when the
+    /// source is missing. The same key for both rotates the list.
+    ///
+    /// Both shards stay locked throughout so no one sees the element in
+    /// neither list or both. They are locked in shard order, so moves in
+    /// opposite directions can't deadlock, and only once when they coincide.
+    pub fn list_move(
+        &mut self,
+        source: impl AsRef<[u8]>,
+        destination: impl AsRef<[u8]>,
+        from: End,
+        to: End,
+    ) -> Result<Option<Bytes>> {
+        let source = source.as_ref();
+        let destination = destination.as_ref();
+        let source_shard = self.shard(source);
+        let destination_shard = self.shard(destination);
+        let limit = self.list_limit();
+
+        if source_shard == destination_shard {
+            let mut guard = self.inner[source_shard].lock().unwrap();
+            return move_list_element(&mut guard, None, source, destination, (from, to), limit);
+        }
+
+        let low = source_shard.min(destination_shard);
+        let high = source_shard.max(destination_shard);
+        let mut low = self.inner[low].lock().unwrap();
+        let mut high = self.inner[high].lock().unwrap();
+        let (source_db, destination_db) = if source_shard < destination_shard {
+            (&mut *low, &mut *high)
+        } else {
+            (&mut *high, &mut *low)
+        };
+        move_list_element(
+            source_db,
+            Some(destination_db),
+            source,
+            destination,
+            (from, to),
+            limit,
+        )
+    }
+
+    /// Replaces the element at `index`, negative indices counting from the tail.
+    pub fn list_set(&mut self, key: impl AsRef<[u8]>, index: i64, value: Bytes) -> Result<()> {
+        let key = key.as_ref();
+        let limit = self.list_limit();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Err(Error::NoSuchKey);
+        };
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let index = list_index(list.len(), index).ok_or(Error::IndexOutOfRange)?;
+        let before = list.heap_size();
+        list.set(index, value);
+        list.fit(limit);
+        let after = list.heap_size();
+        entry.modified();
+        guard.resized(before, after);
+        Ok(())
+    }
+
+    /// Inserts `value` next to the first element equal to `pivot`. Returns the
+    /// new length, -1 when the pivot is absent and 0 when the key is missing.
+    pub fn list_insert(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        position: Position,
+        pivot: &[u8],
+        value: Bytes,
+    ) -> Result<i64> {
+        let key = key.as_ref();
+        let limit = self.list_limit();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(0);
+        };
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let Some(found) = list.iter().position(|element| element == pivot) else {
+            self.touch(entry);
+            return Ok(-1);
+        };
+        let index = match position {
+            Position::Before => found,
+            Position::After => found + 1,
+        };
+        let before = list.heap_size();
+        list.insert(index, value);
+        list.fit(limit);
+
+        let (len, after) = (list.len(), list.heap_size());
+        entry.modified();
+        guard.resized(before, after);
+        Ok(len as i64)
+    }
+
+    /// Indices of elements equal to `value`, skipping the first `|rank| - 1`
+    /// matches and keeping at most `limit`. A negative rank scans from the
+    /// tail. `max_len` caps how many elements are compared, 0 meaning all.
+    pub fn list_positions(
+        &self,
+        key: impl AsRef<[u8]>,
+        value: &[u8],
+        rank: i64,
+        limit: usize,
+        max_len: usize,
+    ) -> Result<Vec<usize>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(Vec::new());
+        };
+        let Value::List(list) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let list: Vec<&[u8]> = list.iter().collect();
+        let skip = usize::try_from(rank.unsigned_abs() - 1).unwrap_or(usize::MAX);
+        let scanned = match max_len {
+            0 => list.len(),
+            max_len => max_len.min(list.len()),
+        };
+        let indices: Box<dyn Iterator<Item = usize>> = if rank < 0 {
+            Box::new((list.len() - scanned..list.len()).rev())
+        } else {
+            Box::new(0..scanned)
+        };
+        let positions = indices
+            .filter(|&index| list[index] == value)
+            .skip(skip)
+            .take(limit)
+            .collect();
+
+        self.touch(entry);
+        Ok(positions)
+    }
+
+    /// Removes up to `count` elements equal to `value`, scanning from the head
+    /// for a positive count and from the tail for a negative one. 0 removes
+    /// every match. The key is deleted once the list is empty.
+    pub fn list_remove(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        count: i64,
+        value: &[u8],
+    ) -> Result<usize> {
+        let key = key.as_ref();
+        let list_limit = self.list_limit();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(0);
+        };
+        let Value::List(list) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let limit = match count {
+            0 => usize::MAX,
+            count => usize::try_from(count.unsigned_abs()).unwrap_or(usize::MAX),
+        };
+        let before = list.heap_size();
+        let removed = list.remove_matching(value, limit, count < 0);
+        list.fit(list_limit);
+
+        let (after, is_empty) = (list.heap_size(), list.is_empty());
+        if removed > 0 {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(before, after);
+        if is_empty {
+            guard.remove_entry(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// Returns how many of `members` were not already in the set.
+    pub fn set_add(&mut self, key: impl AsRef<[u8]>, members: Vec<Bytes>) -> Result<usize> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let entry = guard.live_or_insert_with(key, || Value::Set(HashSet::new()));
+        let Value::Set(set) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let (added, size): (usize, usize) = members
+            .into_iter()
+            .filter(|member| set.insert(member.clone()))
+            .fold((0, 0), |(added, size), member| {
+                (added + 1, size + set_member_size(&member))
+            });
+        if added > 0 {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(0, size);
+        Ok(added)
+    }
+
+    /// Returns how many of `members` were removed, removing the key once the
+    /// set is empty.
+    pub fn set_remove(&mut self, key: impl AsRef<[u8]>, members: &[Bytes]) -> Result<usize> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(0);
+        };
+        let Value::Set(set) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let (removed, freed): (usize, usize) = members
+            .iter()
+            .filter(|member| set.remove(*member))
+            .fold((0, 0), |(removed, freed), member| {
+                (removed + 1, freed + set_member_size(member))
+            });
+
+        let is_empty = set.is_empty();
+        if removed > 0 {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(freed, 0);
+        if is_empty {
+            guard.remove_entry(key);
+        }
+
+        Ok(removed)
+    }
+
+    pub fn set_is_member(&self, key: impl AsRef<[u8]>, member: &[u8]) -> Result<bool> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(false);
+        };
+        let Value::Set(set) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let is_member = set.contains(member);
+        self.touch(entry);
+        Ok(is_member)
+    }
+
+    /// Membership of each of `members`, in the order given, checked under a
+    /// single lock.
+    pub fn set_mismember(
+        &self,
+        key: impl AsRef<[u8]>,
+        members: &[impl AsRef<[u8]>],
+    ) -> Result<Vec<bool>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![false; members.len()]);
+        };
+        let Value::Set(set) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let found = members
+            .iter()
+            .map(|member| set.contains(member.as_ref()))
+            .collect();
+        self.touch(entry);
+        Ok(found)
+    }
+
+    pub fn set_members(&self, key: impl AsRef<[u8]>) -> Result<Vec<Bytes>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![]);
+        };
+        let Value::Set(set) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let members = set.iter().cloned().collect();
+        self.touch(entry);
+        Ok(members)
+    }
+
+    /// A copy of the elements of the list or set at `key`, in list order or
+    /// arbitrary set order. A missing key has no elements.
+    pub fn collection_elements(&self, key: impl AsRef<[u8]>) -> Result<Vec<Bytes>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![]);
+        };
+
+        let elements = match &entry.value {
+            Value::List(list) => list.iter().map(Bytes::copy_from_slice).collect(),
+            Value::Set(set) => set.iter().cloned().collect(),
+            _ => return Err(Error::WrongType),
+        };
+        self.touch(entry);
+        Ok(elements)
+    }
+
+    /// Up to `count` random members: distinct and at most the cardinality
+    /// when `count` is positive, `|count|` picks that may repeat when negative.
+    pub fn set_random_members(&self, key: impl AsRef<[u8]>, count: i64) -> Result<Vec<Bytes>> {
+        check_sample_size(count)?;
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![]);
+        };
+        let Value::Set(set) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let members = sample(set.iter(), count).into_iter().cloned().collect();
+        self.touch(entry);
+        Ok(members)
+    }
+
+    /// Sets each field to its value, returning how many fields are new.
+    pub fn hash_set(&mut self, key: impl AsRef<[u8]>, pairs: Vec<(Bytes, Bytes)>) -> Result<usize> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let entry = guard.live_or_insert_with(key, || Value::Hash(Hash::default()));
+        let Value::Hash(hash) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = hash.heap_size();
+        let added = pairs
+            .into_iter()
+            .filter(|(field, value)| hash.insert(field.clone(), value.clone()).is_none())
+            .count();
+        let after = hash.heap_size();
+
+        entry.modified();
+        guard.resized(before, after);
+        Ok(added)
+    }
+
+    /// Adds `increment` to the float in `field`, a missing field counting as
+    /// zero, and returns the new value as stored. Keeps the field's deadline.
+    pub fn hash_incr_by_float(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        field: Bytes,
+        increment: f64,
+    ) -> Result<Bytes> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let entry = guard.live_or_insert_with(key, || Value::Hash(Hash::default()));
+        let Value::Hash(hash) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let base = match hash.get(&field) {
+            Some(current) => parse_float(current).ok_or(Error::HashNotAFloat)?,
+            None => 0.0,
+        };
+        let updated = Bytes::from(format_float(base + increment)?);
+
+        let before = hash.heap_size();
+        let deadline = hash.expires_at(&field);
+        hash.insert(field.clone(), updated.clone());
+        if let Some(deadline) = deadline {
+            hash.set_expires_at(&field, deadline);
+        }
+        let after = hash.heap_size();
+
+        entry.modified();
+        guard.resized(before, after);
+        Ok(updated)
+    }
+
+    pub fn hash_get(&self, key: impl AsRef<[u8]>, field: &[u8]) -> Result<Option<Bytes>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(None);
+        };
+        let Value::Hash(hash) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let value = hash.get(field).cloned();
+        self.touch(entry);
+        Ok(value)
+    }
+
+    pub fn hash_get_all(&self, key: impl AsRef<[u8]>) -> Result<Vec<(Bytes, Bytes)>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![]);
+        };
+        let Value::Hash(hash) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let pairs = hash
+            .iter()
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect();
+        self.touch(entry);
+        Ok(pairs)
+    }
+
+    /// Gives each of `fields` the deadline `deadline`, replying per field as
+    /// HEXPIRE does: -2 for a missing field (or key), 0 when `condition`
+    /// vetoed it, 1 when set and 2 when the deadline had already passed and the
+    /// field was deleted. The key goes once its last field does.
+    pub fn hash_expire(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        deadline: Instant,
+        condition: Option<ExpireCondition>,
+        fields: &[Bytes],
+    ) -> Result<Vec<i64>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![-2; fields.len()]);
+        };
+        let Value::Hash(hash) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = hash.heap_size();
+        let replies: Vec<i64> = fields
+            .iter()
+            .map(|field| {
+                if hash.get(field).is_none() {
+                    return -2;
+                }
+                if condition
+                    .is_some_and(|condition| !condition.allows(hash.expires_at(field), deadline))
+                {
+                    return 0;
+                }
+                if deadline <= Instant::now() {
+                    hash.remove(field);
+                    return 2;
+                }
+                hash.set_expires_at(field, deadline);
+                1
+            })
+            .collect();
+        let (after, is_empty) = (hash.heap_size(), hash.is_empty());
+
+        if replies.iter().any(|reply| *reply > 0) {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(before, after);
+        if is_empty {
+            guard.remove_entry(key);
+        }
+
+        Ok(replies)
+    }
+
+    /// Remaining time to live per field, `Some(None)` for a field without a
+    /// deadline and `None` for a missing field or key.
+    pub fn hash_ttl(
+        &self,
+        key: impl AsRef<[u8]>,
+        fields: &[Bytes],
+    ) -> Result<Vec<Option<Option<Duration>>>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+        let Value::Hash(hash) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let now = Instant::now();
+        let ttls = fields
+            .iter()
+            .map(|field| {
+                hash.get(field)?;
+                Some(
+                    hash.expires_at(field)
+                        .map(|deadline| deadline.saturating_duration_since(now)),
+                )
+            })
+            .collect();
+        Ok(ttls)
+    }
+
+    /// Clears the deadline of each of `fields`, replying per field as HPERSIST
+    /// does: -2 for a missing field (or key), -1 when it had no deadline and 1
+    /// when it was cleared.
+    pub fn hash_persist(&mut self, key: impl AsRef<[u8]>, fields: &[Bytes]) -> Result<Vec<i64>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![-2; fields.len()]);
+        };
+        let Value::Hash(hash) = &mut entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let before = hash.heap_size();
+        let replies: Vec<i64> = fields
+            .iter()
+            .map(|field| {
+                if hash.get(field).is_none() {
+                    -2
+                } else if hash.persist(field) {
+                    1
+                } else {
+                    -1
+                }
+            })
+            .collect();
+        let after = hash.heap_size();
+
+        if replies.contains(&1) {
+            entry.modified();
+        } else {
+            self.touch(entry);
+        }
+        guard.resized(before, after);
+
+        Ok(replies)
+    }
+
+    /// Random field/value pairs, with the same `count` rules as
+    /// `set_random_members`.
+    pub fn hash_random_fields(
+        &self,
+        key: impl AsRef<[u8]>,
+        count: i64,
+    ) -> Result<Vec<(Bytes, Bytes)>> {
+        check_sample_size(count)?;
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(vec![]);
+        };
+        let Value::Hash(hash) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let fields = sample(hash.iter(), count)
+            .into_iter()
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect();
+        self.touch(entry);
+        Ok(fields)
+    }
+
+    /// Combines the sets at `keys`, the first key being the one others are
+    /// subtracted from for `SetOp::Diff`. Missing keys count as empty sets.
+    /// Each source is copied under its own shard lock, so the result is not a
+    /// snapshot across shards.
+    pub fn set_combine(&self, op: SetOp, keys: &[impl AsRef<[u8]>]) -> Result<HashSet<Bytes>> {
+        let sets = keys
+            .iter()
+            .map(|key| self.set_snapshot(key.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        let mut sets = sets.into_iter();
+        let mut result = sets.next().unwrap_or_default();
+
+        for set in sets {
+            match op {
+                SetOp::Inter => result.retain(|member| set.contains(member)),
+                SetOp::Union => result.extend(set),
+                SetOp::Diff => result.retain(|member| !set.contains(member)),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Stores the result of `set_combine` at `destination`, replacing whatever
+    /// was there, and returns its cardinality. An empty result deletes the key.
+    pub fn set_combine_store(
+        &mut self,
+        op: SetOp,
+        destination: impl AsRef<[u8]>,
+        keys: &[impl AsRef<[u8]>],
+    ) -> Result<usize> {
+        let destination = destination.as_ref();
+        let result = self.set_combine(op, keys)?;
+        let len = result.len();
+
+        let mut guard = self.guard(destination);
+        guard.remove_if_expired(destination);
+        if result.is_empty() {
+            guard.remove_entry(destination);
+        } else {
+            guard.insert_entry(destination, Entry::new(Value::Set(result)));
+        }
+
+        Ok(len)
+    }
+
+    /// Cardinality of the intersection of the sets at `keys`, counting no
+    /// further than `limit` (0 for no limit). Walks the smallest set and
+    /// checks its members against the others, so an early stop skips the
+    /// rest.
+    ///
+    /// Every shard involved is locked at once, in shard order, so the count
+    /// is consistent across keys without risking deadlock.
+    pub fn set_inter_card(&self, keys: &[impl AsRef<[u8]>], limit: usize) -> Result<usize> {
+        let shards: Vec<usize> = keys.iter().map(|key| self.shard(key.as_ref())).collect();
+        let mut locked = shards.clone();
+        locked.sort_unstable();
+        locked.dedup();
+        let mut guards: HashMap<usize, MutexGuard<'_, InnerDb>> = locked
+            .into_iter()
+            .map(|shard| (shard, self.inner[shard].lock().unwrap()))
+            .collect();
+
+        let mut any_missing = false;
+        for (key, shard) in keys.iter().zip(&shards) {
+            let guard = guards.get_mut(shard).unwrap();
+            match guard.live(key.as_ref()).map(|entry| &entry.value) {
+                Some(Value::Set(_)) => {}
+                Some(_) => return Err(Error::WrongType),
+                None => any_missing = true,
+            }
+        }
+        if any_missing {
+            return Ok(0);
+        }
+
+        let mut sets: Vec<&HashSet<Bytes>> = keys
+            .iter()
+            .zip(&shards)
+            .map(|(key, shard)| match &guards[shard].db[key.as_ref()].value {
+                Value::Set(set) => set,
+                _ => unreachable!("checked to hold a set"),
+            })
+            .collect();
+        sets.sort_by_key(|set| set.len());
+        let Some((smallest, others)) = sets.split_first() else {
+            return Ok(0);
+        };
+
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        Ok(smallest
+            .iter()
+            .filter(|member| others.iter().all(|set| set.contains(*member)))
+            .take(limit)
+            .count())
+    }
+
+    /// Changes every time the key is written, expires or is deleted. A missing
+    /// key reports 0.
+    pub fn version(&self, key: impl AsRef<[u8]>) -> u64 {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.live(key).map_or(0, |entry| entry.version)
+    }
+
+    /// Runs `f` against the stored value without counting as an access.
+    pub fn inspect<R>(&self, key: impl AsRef<[u8]>, f: impl FnOnce(&Value) -> R) -> Option<R> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.live(key).map(|entry| f(&entry.value))
+    }
+
+    /// Calls `f` with every live key and its value, holding one shard lock at
+    /// a time, so the result is not a snapshot across shards.
+    pub fn for_each_entry(&self, mut f: impl FnMut(&[u8], &Value)) {
+        for index in 0..self.num_shards() {
+            let _ = self.for_each_key_in_shard(index, &mut f);
+        }
+    }
+
+    pub fn num_shards(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Calls `f` with every live key in the shard at `index` and its value,
+    /// holding only that shard's lock. What SCAN and KEYS walk.
+    pub fn for_each_key_in_shard(
+        &self,
+        index: usize,
+        mut f: impl FnMut(&[u8], &Value),
+    ) -> Result<()> {
+        let shard = self.inner.get(index).ok_or(Error::IndexOutOfRange)?;
+        let guard = shard.lock().unwrap();
+        for (key, entry) in &guard.db {
+            if !entry.is_expired() {
+                f(key, &entry.value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Walks the keys of the shard at `index` in hash order, from the first
+    /// whose hash is at least `from`, calling `f` with each live key and its
+    /// value. Stops once `limit` keys were walked, though never between two
+    /// keys of the same hash. Returns how many keys were walked and the hash
+    /// to resume from, `None` once the end of the shard was reached. What SCAN
+    /// resumes from its cursor.
+    pub fn scan_shard(
+        &self,
+        index: usize,
+        from: u64,
+        limit: usize,
+        mut f: impl FnMut(&[u8], &Value),
+    ) -> Result<(usize, Option<u64>)> {
+        let shard = self.inner.get(index).ok_or(Error::IndexOutOfRange)?;
+        let guard = shard.lock().unwrap();
+        let mut walked = 0;
+        let mut last = None;
+        for (hash, key) in guard.order.range((from, Bytes::new())..) {
+            if walked >= limit && last != Some(*hash) {
+                return Ok((walked, Some(*hash)));
+            }
+            walked += 1;
+            last = Some(*hash);
+            let entry = &guard.db[key];
+            if !entry.is_expired() {
+                f(key, &entry.value);
+            }
+        }
+        Ok((walked, None))
+    }
+
+    /// Inserts every entry, replacing existing keys without a TTL, and returns
+    /// how many there were. Entries are grouped by shard first, so each shard
+    /// is locked once however many keys land in it. Nothing is inserted if a
+    /// string is longer than `max_len` bytes.
+    pub fn load(&mut self, entries: Vec<(Bytes, Value)>, max_len: usize) -> Result<usize> {
+        let too_large = |value: &Value| matches!(value, Value::String(s) if s.len() > max_len);
+        if entries.iter().any(|(_, value)| too_large(value)) {
+            return Err(Error::ValueTooLarge);
+        }
+        let loaded = entries.len();
+        for (shard, entries) in group_by_shard(entries, self.inner.len(), self.seed)
+            .into_iter()
+            .enumerate()
+        {
+            if entries.is_empty() {
+                continue;
+            }
+            let mut guard = self.inner[shard].lock().unwrap();
+            for (key, value) in entries {
+                guard.insert_entry(&key, Entry::new(value));
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Estimated bytes used by `key`, counting the key itself, the entry
+    /// bookkeeping and the value. Does not count as an access.
+    pub fn memory_usage(&self, key: impl AsRef<[u8]>) -> Option<usize> {
+        let key = key.as_ref();
+        self.inspect(key, |value| entry_size(key, value))
+    }
+
+    /// Time since the key was last read or written, without counting as an access.
+    pub fn idle_time(&self, key: impl AsRef<[u8]>) -> Option<Duration> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.live(key).map(|entry| entry.last_access.elapsed())
+    }
+
+    /// The LFU access counter of `key`, decayed to now. Reading it doesn't
+    /// count as an access.
+    pub fn frequency(&self, key: impl AsRef<[u8]>) -> Option<u8> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        guard.live(key).map(|entry| entry.frequency())
+    }
+
+    /// Total number of keys deleted because their TTL ran out.
+    pub fn expired_keys(&self) -> u64 {
+        self.inner
+            .iter()
+            .map(|shard| shard.lock().unwrap().expired_keys)
+            .sum()
+    }
+
+    /// Exchanges every key with `other`, holding all the shards of both so no
+    /// reader sees the pair half swapped. The two must share their shard
+    /// count and seed, as a server's databases do, and concurrent swaps must
+    /// lock in the same order: the lower-numbered database as `self`.
+    ///
+    /// Nothing needs bumping for WATCH: entry versions are unique across
+    /// databases, so any key present on either side reads back a version its
+    /// watchers haven't seen.
+    pub fn swap(&self, other: &ShardedDb) {
+        if Arc::ptr_eq(&self.inner, &other.inner) {
+            return;
+        }
+        debug_assert_eq!(
+            (self.seed, self.inner.len()),
+            (other.seed, other.inner.len())
+        );
+
+        let mut ours: Vec<_> = self
+            .inner
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect();
+        let mut theirs: Vec<_> = other
+            .inner
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect();
+        for (ours, theirs) in ours.iter_mut().zip(theirs.iter_mut()) {
+            std::mem::swap(&mut ours.db, &mut theirs.db);
+            std::mem::swap(&mut ours.used_memory, &mut theirs.used_memory);
+            std::mem::swap(&mut ours.deadlines, &mut theirs.deadlines);
+            std::mem::swap(&mut ours.order, &mut theirs.order);
+            std::mem::swap(&mut ours.keys, &mut theirs.keys);
+            std::mem::swap(&mut ours.volatile, &mut theirs.volatile);
+        }
+        // every shard lock is held, so neither total can move meanwhile
+        let theirs_total = other.used_memory.load(Ordering::Relaxed);
+        let ours_total = self.used_memory.swap(theirs_total, Ordering::Relaxed);
+        other.used_memory.store(ours_total, Ordering::Relaxed);
+    }
+
+    /// Empties every shard, locking one at a time, and hands back what was
+    /// removed so the caller decides where the memory gets freed.
+    pub fn flush(&self) -> Flushed {
+        let mut size = 0;
+        let entries = self
+            .inner
+            .iter()
+            .map(|shard| {
+                let mut guard = shard.lock().unwrap();
+                size += guard.used_memory;
+                guard.take()
+            })
+            .collect();
+
+        Flushed { entries, size }
+    }
+
+    /// Empties only the shard at `index`, for looking into how keys spread
+    /// over shards.
+    pub fn flush_shard(&self, index: usize) -> Result<Flushed> {
+        let shard = self.inner.get(index).ok_or(Error::IndexOutOfRange)?;
+        let mut guard = shard.lock().unwrap();
+        Ok(Flushed {
+            size: guard.used_memory,
+            entries: vec![guard.take()],
+        })
+    }
+
+    /// Moves every key into a new database of `num_shards` shards and returns
+    /// a handle to it, carrying the expiry and eviction counters over and
+    /// sharing the settings, as `sibling` does.
+    ///
+    /// A clone would be left pointing at the old shards, so this refuses to
+    /// run while one exists. This handle is left empty and should be replaced
+    /// by the returned one.
+    pub fn reshard(&self, num_shards: usize) -> Result<ShardedDb> {
+        if num_shards == 0 {
+            return Err(Error::NoShards);
+        }
+        if Arc::strong_count(&self.inner) > 1 {
+            return Err(Error::SharedHandle);
+        }
+        let resharded = Self {
+            active_expire: self.active_expire.clone(),
+            list_limit: self.list_limit.clone(),
+            ..Self::new_seeded(num_shards, self.seed)
+        };
+        let mut guards: Vec<_> = self
+            .inner
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect();
+        let mut targets: Vec<_> = resharded
+            .inner
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect();
+
+        for guard in &mut guards {
+            targets[0].expired_keys += guard.expired_keys;
+            targets[0].evicted_keys += guard.evicted_keys;
+            targets[0].deleted_keys.add(guard.deleted_keys);
+            for (key, entry) in guard.take() {
+                targets[resharded.shard(&key)].insert_owned(key, entry);
+            }
+        }
+
+        drop(targets);
+        Ok(resharded)
+    }
+
+    /// Deletes keys whose deadline has passed, earliest first and at most
+    /// `ACTIVE_EXPIRE_LIMIT` per shard, and returns how many went. Keys still
+    /// due are left to the next call, or to lazy expiry. Does nothing while
+    /// active expiry is off.
+    pub fn purge_expired(&self) -> usize {
+        if !self.active_expire() {
+            return 0;
+        }
+
+        let now = Instant::now();
+        let mut purged = 0;
+        for shard in self.inner.iter() {
+            let mut guard = shard.lock().unwrap();
+            for _ in 0..ACTIVE_EXPIRE_LIMIT {
+                if guard
+                    .deadlines
+                    .first()
+                    .is_none_or(|(deadline, _)| *deadline > now)
+                {
+                    break;
+                }
+                let (_, key) = guard.deadlines.pop_first().expect("checked above");
+                if guard.remove_entry(&key).is_some() {
+                    guard.expired_keys += 1;
+                    purged += 1;
+                }
+            }
+        }
+        purged
+    }
+
+    pub fn active_expire(&self) -> bool {
+        self.active_expire.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.active_expire.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn list_limit(&self) -> ListLimit {
+        ListLimit(self.list_limit.load(Ordering::Relaxed))
+    }
+
+    /// Lists already stored switch encoding the next time they change.
+    pub fn set_list_limit(&self, limit: ListLimit) {
+        self.list_limit.store(limit.get(), Ordering::Relaxed);
+    }
+
+    /// Keys deleted by DEL across every shard, by type.
+    pub fn deleted_keys(&self) -> DeletedKeys {
+        let mut total = DeletedKeys::default();
+        for shard in self.inner.iter() {
+            total.add(shard.lock().unwrap().deleted_keys);
+        }
+        total
+    }
+
+    /// Total number of keys deleted to stay under `maxmemory`.
+    pub fn evicted_keys(&self) -> u64 {
+        self.inner
+            .iter()
+            .map(|shard| shard.lock().unwrap().evicted_keys)
+            .sum()
+    }
+
+    /// Estimated bytes held by every key, kept up to date as keys are written
+    /// and deleted.
+    pub fn used_memory(&self) -> usize {
+        self.used_memory.load(Ordering::Relaxed)
+    }
+
+    /// Evicts keys chosen by `policy` until `used_memory` is at most `limit`,
+    /// returning how many went. Fails when nothing more can be evicted while
+    /// still over the limit.
+    pub fn evict(&self, limit: usize, policy: EvictionPolicy) -> Result<usize> {
+        evict(std::slice::from_ref(self), limit, policy)
+    }
+
+    fn set_snapshot(&self, key: &[u8]) -> Result<HashSet<Bytes>> {
+        let mut guard = self.guard(key);
+        match guard.live(key).map(|entry| &entry.value) {
+            Some(Value::Set(set)) => Ok(set.clone()),
+            Some(_) => Err(Error::WrongType),
+            None => Ok(HashSet::new()),
+        }
+    }
+
+    /// Writes `value` at `offset`, or at the end when `None`. Nothing changes
+    /// if the resulting string would be longer than `max_len`; an existing TTL
+    /// is kept.
+    fn splice_string(
+        &mut self,
+        key: &[u8],
+        offset: Option<usize>,
+        value: &[u8],
+        max_len: usize,
+    ) -> Result<usize> {
+        let mut guard = self.guard(key);
+        let current_len = match guard.live(key).map(|entry| &entry.value) {
+            Some(Value::String(current)) => current.len(),
+            Some(_) => return Err(Error::WrongType),
+            None if offset.is_some() && value.is_empty() => return Ok(0),
+            None => 0,
+        };
+        if offset.is_some() && value.is_empty() {
+            return Ok(current_len);
+        }
+
+        let offset = offset.unwrap_or(current_len);
+        let len = offset
+            .checked_add(value.len())
+            .map(|end| end.max(current_len))
+            .filter(|len| *len <= max_len)
+            .ok_or(Error::ValueTooLarge)?;
+
+        let Some(entry) = guard.db.get_mut(key) else {
+            let mut created = BytesMut::zeroed(len);
+            created[offset..].copy_from_slice(value);
+            guard.insert_entry(key, Entry::new(Value::String(created.freeze())));
+            return Ok(len);
+        };
+        let Value::String(current) = &mut entry.value else {
+            unreachable!("checked above");
+        };
+        // a string no reader still holds is changed where it is, so repeated
+        // APPENDs don't copy it each time
+        let mut updated = match std::mem::take(current).try_into_mut() {
+            Ok(updated) => updated,
+            Err(shared) => {
+                let mut copied = BytesMut::with_capacity(len);
+                copied.extend_from_slice(&shared);
+                copied
+            }
+        };
+        updated.resize(len, 0);
+        updated[offset..offset + value.len()].copy_from_slice(value);
+        *current = updated.freeze();
+        entry.modified();
+        guard.resized(current_len, len);
+
+        Ok(len)
+    }
+
+    /// Appends an entry to the stream at `key`, creating it if needed, and
+    /// returns the id it was given.
+    pub fn stream_add(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        id: NewStreamId,
+        fields: Vec<(Bytes, Bytes)>,
+    ) -> Result<StreamId> {
+        let key = key.as_ref();
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_millis() as u64);
+
+        let mut guard = self.guard(key);
+        match guard.live(key) {
+            Some(entry) => {
+                let Value::Stream(stream) = &mut entry.value else {
+                    return Err(Error::WrongType);
+                };
+                let before = stream.heap_size();
+                let id = stream.add(id, fields, now_ms)?;
+                let after = stream.heap_size();
+                entry.modified();
+                guard.resized(before, after);
+                Ok(id)
+            }
+            None => {
+                let mut stream = Stream::default();
+                let id = stream.add(id, fields, now_ms)?;
+                guard.insert_entry(key, Entry::new(Value::Stream(stream)));
+                Ok(id)
+            }
+        }
+    }
+
+    pub fn stream_len(&self, key: impl AsRef<[u8]>) -> Result<usize> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        match guard.live(key).map(|entry| &entry.value) {
+            Some(Value::Stream(stream)) => Ok(stream.len()),
+            Some(_) => Err(Error::WrongType),
+            None => Ok(0),
+        }
+    }
+
+    /// Up to `count` entries of the stream at `key` with ids from `start` to
+    /// `end`, in id order.
+    pub fn stream_range(
+        &self,
+        key: impl AsRef<[u8]>,
+        start: StreamId,
+        end: StreamId,
+        count: usize,
+    ) -> Result<Vec<StreamEntry>> {
+        let key = key.as_ref();
+        let mut guard = self.guard(key);
+        let Some(entry) = guard.live(key) else {
+            return Ok(Vec::new());
+        };
+        let Value::Stream(stream) = &entry.value else {
+            return Err(Error::WrongType);
+        };
+
+        let entries = stream
+            .range(start, end)
+            .take(count)
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect();
+        self.touch(entry);
+        Ok(entries)
+    }
+
+    fn touch(&self, entry: &mut Entry) {
+        if self.touch {
+            entry.accessed();
+        }
+    }
+
+    fn guard(&self, key: &[u8]) -> MutexGuard<'_, InnerDb> {
+        self.inner[self.shard(key)].lock().unwrap()
+    }
+
+    fn shard(&self, key: &[u8]) -> usize {
+        shard_index(self.seed, key, self.inner.len())
+    }
+}
+
+/// The body of `ShardedDb::list_move` once the shards are locked, with
+/// `destination_db` as `None` when both keys live in `source_db`.
+fn move_list_element(
+    source_db: &mut InnerDb,
+    mut destination_db: Option<&mut InnerDb>,
+    source: &[u8],
+    destination: &[u8],
+    (from, to): (End, End),
+    limit: ListLimit,
+) -> Result<Option<Bytes>> {
+    match destination_db.as_deref_mut() {
+        Some(destination_db) => destination_db.check_list(destination)?,
+        None => source_db.check_list(destination)?,
+    }
+    let Some(value) = source_db.list_pop_one(source, from, limit)? else {
+        return Ok(None);
+    };
+    match destination_db {
+        Some(destination_db) => destination_db.list_push_one(destination, to, value.clone(), limit),
+        None => source_db.list_push_one(destination, to, value.clone(), limit),
+    }
+    Ok(Some(value))
+}
+
+/// A string entry replacing `current`, its deadline set as `expiry` says.
+fn string_entry(current: Option<&Entry>, value: Bytes, expiry: Expiry) -> Entry {
+    let mut entry = Entry::new(Value::String(value));
+    entry.expires_at = match expiry {
+        Expiry::Keep => current.and_then(|entry| entry.expires_at),
+        Expiry::Persist => None,
+        Expiry::In(ttl) => deadline_in(ttl),
+        Expiry::At(at) => match at.duration_since(SystemTime::now()) {
+            Ok(ttl) => deadline_in(ttl),
+            Err(_) => Some(Instant::now()),
+        },
+    };
+    entry
+}
+
+/// The deadline `ttl` from now, or `None` when it is too far off for an
+/// `Instant` to hold, which is as good as never. Commands reject such
+/// expiries up front, see `Expiry::is_valid`.
+fn deadline_in(ttl: Duration) -> Option<Instant> {
+    Instant::now().checked_add(ttl)
+}
+
+/// The hash placing `key` in a shard, and ordering it within the shard for
+/// SCAN.
+fn key_hash(seed: u64, key: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn shard_index(seed: u64, key: &[u8], num_shards: usize) -> usize {
+    (key_hash(seed, key) % num_shards as u64) as usize
+}
+
+/// Splits `entries` into one batch per shard, in shard order.
+fn group_by_shard(
+    entries: Vec<(Bytes, Value)>,
+    num_shards: usize,
+    seed: u64,
+) -> Vec<Vec<(Bytes, Value)>> {
+    let mut groups: Vec<Vec<_>> = (0..num_shards).map(|_| Vec::new()).collect();
+    for (key, value) in entries {
+        groups[shard_index(seed, &key, num_shards)].push((key, value));
+    }
+    groups
+}
+
+/// Parses a finite float the way Redis reads one, with no surrounding
+/// whitespace.
+pub(crate) fn parse_float(bytes: &[u8]) -> Option<f64> {
+    std::str::from_utf8(bytes)
+        .ok()?
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Formats a float in plain decimal notation with no trailing zeros, as
+/// `INCRBYFLOAT` stores it.
+fn format_float(value: f64) -> Result<String> {
+    if !value.is_finite() {
+        return Err(Error::NanOrInfinity);
+    }
+    if value == 0.0 {
+        // Avoids printing `-0`.
+        return Ok("0".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// Refuses a sample whose magnitude is above `MAX_SAMPLE`.
+fn check_sample_size(count: i64) -> Result<()> {
+    if count.unsigned_abs() > MAX_SAMPLE {
+        return Err(Error::ValueOutOfRange);
+    }
+    Ok(())
+}
+
+/// Picks `count` items without repetition, or `|count|` items independently
+/// (so possibly repeating) when `count` is negative. `|count|` is at most
+/// `MAX_SAMPLE`, as `check_sample_size` makes sure.
+fn sample<T: Copy>(items: impl Iterator<Item = T>, count: i64) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    let items: Vec<T> = items.collect();
+    if items.is_empty() {
+        return vec![];
+    }
+
+    let amount = count.unsigned_abs() as usize;
+    if count >= 0 {
+        return items
+            .choose_multiple(&mut rng, amount.min(items.len()))
+            .copied()
+            .collect();
+    }
+
+    (0..amount)
+        .map(|_| *items.choose(&mut rng).expect("items is not empty"))
+        .collect()
+}
+
+/// Maps a Redis-style index, negative counting from the tail, onto `0..len`.
+fn list_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+    } else {
+        usize::try_from(index).ok()?
+    };
+
+    (index < len).then_some(index)
+}
+
+impl Default for ShardedDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::EvictionPolicy;
+    use crate::db::{
+        free_lazily, group_by_shard, shard_index, End, Error, Expiry, List, ListLimit, NewStreamId,
+        Position, SetOp, ShardedDb, Stream, StreamId, Value, ACTIVE_EXPIRE_LIMIT,
+    };
+    use crate::dump;
+    use bytes::Bytes;
+    use claims::{assert_err, assert_ok};
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    fn list(values: &[&'static str]) -> Vec<Bytes> {
+        values
+            .iter()
+            .map(|value| Bytes::from_static(value.as_bytes()))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_expired_key_removed_lazily() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert_with_ttl("key", "value".into(), Some(Duration::from_secs(1)));
+        db.insert("other", "value".into());
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Act
+        let len_before_get = db.len();
+        let value = db.get("key");
+        let len_after_get = db.len();
+
+        // Assert
+        assert_eq!(len_before_get, 2);
+        assert_eq!(value, Ok(None));
+        assert_eq!(len_after_get, 1);
+        assert_eq!(db.expired_keys(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_expired_removes_only_expired_keys() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert_with_ttl("key", "value".into(), Some(Duration::from_secs(1)));
+        db.insert_with_ttl("later", "value".into(), Some(Duration::from_secs(10)));
+        db.insert("other", "value".into());
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Act
+        let purged = db.purge_expired();
+
+        // Assert
+        assert_eq!(purged, 1);
+        assert_eq!(db.len(), 2);
+        assert_eq!(db.expired_keys(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_expired_bounds_work_per_shard() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        for key in 0..ACTIVE_EXPIRE_LIMIT + 10 {
+            db.insert_with_ttl(
+                format!("key:{key}"),
+                "value".into(),
+                Some(Duration::from_secs(1)),
+            );
+        }
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Act
+        let first = db.purge_expired();
+        let second = db.purge_expired();
+
+        // Assert
+        assert_eq!(first, ACTIVE_EXPIRE_LIMIT);
+        assert_eq!(second, 10);
+        assert_eq!(db.len(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_expired_follows_changed_deadlines() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert_with_ttl("extended", "value".into(), Some(Duration::from_secs(1)));
+        db.expire("extended", Duration::from_secs(10));
+        db.insert_with_ttl("overwritten", "value".into(), Some(Duration::from_secs(1)));
+        db.insert("overwritten", "value".into());
+        db.insert("shortened", "value".into());
+        db.expire("shortened", Duration::from_secs(1));
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Act
+        let purged = db.purge_expired();
+
+        // Assert
+        assert_eq!(purged, 1);
+        assert_eq!(db.len(), 2);
+        assert_eq!(db.ttl("shortened"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_expired_with_active_expire_off_leaves_lazy_expiry() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_active_expire(false);
+        db.insert_with_ttl("key", "value".into(), Some(Duration::from_secs(1)));
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Act
+        let purged = db.purge_expired();
+        let len_before_get = db.len();
+        let value = db.get("key");
+
+        // Assert
+        assert_eq!(purged, 0);
+        assert_eq!(len_before_get, 1);
+        assert_eq!(value, Ok(None));
+        assert_eq!(db.len(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_ex_sets_and_clears_ttl() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        let with_ttl = db.get_ex("key", Expiry::In(Duration::from_secs(10)));
+        let ttl = db.ttl("key");
+        let persisted = db.get_ex("key", Expiry::Persist);
+
+        // Assert
+        assert_eq!(with_ttl, Ok(Some("value".into())));
+        assert_eq!(ttl, Some(Some(Duration::from_secs(10))));
+        assert_eq!(persisted, Ok(Some("value".into())));
+        assert_eq!(db.ttl("key"), Some(None));
+    }
+
+    #[test]
+    fn get_ex_missing_key_and_past_deadline() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+
+        // Act
+        let missing = db.get_ex("missing", Expiry::Persist);
+        let expired = db.get_ex("key", Expiry::At(std::time::UNIX_EPOCH));
+
+        // Assert
+        assert_eq!(missing, Ok(None));
+        assert_eq!(expired, Ok(Some("value".into())));
+        assert!(db.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn insert_clears_previous_ttl() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert_with_ttl("key", "value".into(), Some(Duration::from_secs(1)));
+
+        // Act
+        db.insert("key", "other".into());
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        // Assert
+        assert_eq!(db.ttl("key"), Some(None));
+        assert_eq!(db.get("key"), Ok(Some("other".into())));
+    }
+
+    #[test]
+    fn list_push_left_reverses_order() {
+        // Arrange
+        let mut db = ShardedDb::new();
+
+        // Act
+        let len = db.list_push("list", End::Left, list(&["a", "b", "c"]));
+        let popped = db.list_pop("list", End::Left, 3);
+
+        // Assert
+        assert_eq!(len, Ok(3));
+        assert_eq!(popped, Ok(Some(list(&["c", "b", "a"]))));
+    }
+
+    #[test]
+    fn list_move_same_key_rotates() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a", "b", "c"]))
+            .unwrap();
+
+        // Act
+        let moved = db.list_move("list", "list", End::Right, End::Left);
+
+        // Assert
+        assert_eq!(moved, Ok(Some("c".into())));
+        assert_eq!(
+            db.list_pop("list", End::Left, 3),
+            Ok(Some(list(&["c", "a", "b"])))
+        );
+    }
+
+    #[test]
+    fn list_move_across_shards() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(8);
+        let destination = (0..)
+            .map(|key| format!("dst:{key}"))
+            .find(|key| db.shard(key.as_bytes()) != db.shard(b"src"))
+            .unwrap();
+        db.list_push("src", End::Right, list(&["a", "b"])).unwrap();
+        db.list_push(&destination, End::Right, list(&["x"]))
+            .unwrap();
+        db.insert("string", "value".into());
+
+        // Act
+        let first = db.list_move("src", &destination, End::Left, End::Right);
+        let back = db.list_move(&destination, "src", End::Left, End::Left);
+        let wrong_type = db.list_move("src", "string", End::Left, End::Left);
+
+        // Assert
+        assert_eq!(first, Ok(Some("a".into())));
+        assert_eq!(back, Ok(Some("x".into())));
+        assert_eq!(wrong_type, Err(Error::WrongType));
+        assert_eq!(
+            db.list_pop("src", End::Left, 3),
+            Ok(Some(list(&["x", "b"])))
+        );
+        assert_eq!(
+            db.list_pop(&destination, End::Left, 3),
+            Ok(Some(list(&["a"])))
+        );
+    }
+
+    #[test]
+    fn list_move_empty_source_returns_none() {
+        // Arrange
+        let mut db = ShardedDb::new();
+
+        // Act
+        let moved = db.list_move("missing", "dst", End::Left, End::Right);
+
+        // Assert
+        assert_eq!(moved, Ok(None));
+        assert!(db.is_empty());
+    }
+
+    #[test]
+    fn list_pop_more_than_length() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a", "b", "c"]))
+            .unwrap();
+
+        // Act
+        let popped = db.list_pop("list", End::Right, 10);
+
+        // Assert
+        assert_eq!(popped, Ok(Some(list(&["c", "b", "a"]))));
+    }
+
+    #[test]
+    fn list_pop_missing_key_none() {
+        // Arrange
+        let mut db = ShardedDb::new();
+
+        // Act
+        let popped = db.list_pop("missing", End::Left, 1);
+
+        // Assert
+        assert_eq!(popped, Ok(None));
+    }
+
+    #[test]
+    fn list_pop_removes_key_when_empty() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a", "b"])).unwrap();
+
+        // Act
+        let first = db.list_pop("list", End::Left, 1);
+        let len_after_first = db.len();
+        let second = db.list_pop("list", End::Left, 1);
+
+        // Assert
+        assert_eq!(first, Ok(Some(list(&["a"]))));
+        assert_eq!(len_after_first, 1);
+        assert_eq!(second, Ok(Some(list(&["b"]))));
+        assert!(db.is_empty());
+    }
+
+    #[test]
+    fn set_add_duplicates_counted_once() {
+        // Arrange
+        let mut db = ShardedDb::new();
+
+        // Act
+        let added = db.set_add("set", list(&["a", "b", "a"]));
+        let added_again = db.set_add("set", list(&["b", "c"]));
+
+        // Assert
+        assert_eq!(added, Ok(2));
+        assert_eq!(added_again, Ok(1));
+        assert_eq!(db.set_members("set").map(|members| members.len()), Ok(3));
+    }
+
+    #[test]
+    fn set_remove_removes_key_when_empty() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("set", list(&["a", "b"])).unwrap();
+
+        // Act
+        let removed = db.set_remove("set", &list(&["a", "b", "c"]));
+
+        // Assert
+        assert_eq!(removed, Ok(2));
+        assert!(db.is_empty());
+    }
+
+    #[test]
+    fn set_is_member_string_key_wrong_type() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("string", "value".into());
+
+        // Act
+        let is_member = db.set_is_member("string", b"value");
+
+        // Assert
+        assert_eq!(is_member, Err(Error::WrongType));
+    }
+
+    #[test]
+    fn list_pop_string_key_wrong_type() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("string", "value".into());
+
+        // Act
+        let popped = db.list_pop("string", End::Left, 1);
+
+        // Assert
+        assert_err!(&popped);
+        assert_eq!(popped, Err(Error::WrongType));
+        assert_ok!(db.get("string"));
+    }
+
+    #[test]
+    fn append_past_limit_leaves_value_unchanged() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", Bytes::from_static(b"abc"));
+
+        // Act
+        let within = db.append("key", b"de", 5);
+        let past = db.append("key", b"f", 5);
+
+        // Assert
+        assert_eq!(within, Ok(5));
+        assert_eq!(past, Err(Error::ValueTooLarge));
+        assert_eq!(db.get("key"), Ok(Some(Bytes::from_static(b"abcde"))));
+    }
+
+    #[test]
+    fn append_extends_unshared_string_in_place_and_leaves_readers_alone() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.append("key", b"abc", usize::MAX).unwrap();
+        let read = db.get("key").unwrap().unwrap();
+        db.append("key", b"def", usize::MAX).unwrap();
+        let address = db.get("key").unwrap().unwrap().as_ptr();
+
+        // Act
+        db.set_range("key", 0, b"A", usize::MAX).unwrap();
+
+        // Assert
+        assert_eq!(read, Bytes::from_static(b"abc"));
+        let value = db.get("key").unwrap().unwrap();
+        assert_eq!(value, Bytes::from_static(b"Abcdef"));
+        assert_eq!(value.as_ptr(), address);
+    }
+
+    #[test]
+    fn set_range_pads_gap_with_zeros() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", Bytes::from_static(b"ab"));
+
+        // Act
+        let len = db.set_range("key", 4, b"cd", usize::MAX);
+        let untouched = db.set_range("missing", 3, b"", usize::MAX);
+
+        // Assert
+        assert_eq!(len, Ok(6));
+        assert_eq!(untouched, Ok(0));
+        assert_eq!(db.get("key"), Ok(Some(Bytes::from_static(b"ab\0\0cd"))));
+        assert_eq!(db.get("missing"), Ok(None));
+    }
+
+    #[test]
+    fn set_inter_card_counts_up_to_limit() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("a", list(&["v", "w", "x", "y", "z"])).unwrap();
+        db.set_add("b", list(&["w", "x", "y", "z"])).unwrap();
+        db.set_add("c", list(&["x", "y", "z", "q"])).unwrap();
+        let keys = ["a".to_string(), "b".to_string(), "c".to_string()];
+
+        // Act
+        let full = db.set_inter_card(&keys, 0);
+        let limited = db.set_inter_card(&keys, 2);
+        let above = db.set_inter_card(&keys, 10);
+        let missing = db.set_inter_card(&["a".to_string(), "missing".to_string()], 0);
+
+        // Assert
+        assert_eq!(full, Ok(3));
+        assert_eq!(limited, Ok(2));
+        assert_eq!(above, Ok(3));
+        assert_eq!(missing, Ok(0));
+    }
+
+    #[test]
+    fn set_combine_inter_overlapping_sets() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("a", list(&["x", "y", "z"])).unwrap();
+        db.set_add("b", list(&["y", "z", "w"])).unwrap();
+        let keys = ["a".to_string(), "b".to_string()];
+
+        // Act
+        let inter = db.set_combine(SetOp::Inter, &keys).unwrap();
+        let diff = db.set_combine(SetOp::Diff, &keys).unwrap();
+        let stored = db.set_combine_store(SetOp::Union, "dest", &keys);
+
+        // Assert
+        assert_eq!(inter, list(&["y", "z"]).into_iter().collect());
+        assert_eq!(diff, list(&["x"]).into_iter().collect());
+        assert_eq!(stored, Ok(4));
+        assert_eq!(db.set_members("dest").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn set_combine_string_source_wrong_type() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("a", list(&["x"])).unwrap();
+        db.insert("b", Bytes::from_static(b"string"));
+        let keys = ["a".to_string(), "b".to_string()];
+
+        // Act
+        let combined = db.set_combine(SetOp::Union, &keys);
+        let stored = db.set_combine_store(SetOp::Inter, "dest", &keys);
+
+        // Assert
+        assert_eq!(combined, Err(Error::WrongType));
+        assert_eq!(stored, Err(Error::WrongType));
+        assert!(db.inspect("dest", |_| ()).is_none());
+    }
+
+    #[test]
+    fn compare_and_swap_match_and_mismatch() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", Bytes::from_static(b"old"));
+
+        // Act
+        let mismatch = db.compare_and_swap("key", b"other", Bytes::from_static(b"x"), usize::MAX);
+        let matched = db.compare_and_swap("key", b"old", Bytes::from_static(b"new"), usize::MAX);
+        let missing = db.compare_and_swap("missing", b"", Bytes::from_static(b"x"), usize::MAX);
+
+        // Assert
+        assert_eq!(mismatch, Ok(false));
+        assert_eq!(matched, Ok(true));
+        assert_eq!(missing, Ok(false));
+        assert_eq!(db.get("key"), Ok(Some(Bytes::from_static(b"new"))));
+        assert_eq!(db.get("missing"), Ok(None));
+    }
+
+    #[test]
+    fn compare_and_swap_list_wrong_type() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a"])).unwrap();
+
+        // Act
+        let swapped = db.compare_and_swap("list", b"a", Bytes::from_static(b"b"), usize::MAX);
+
+        // Assert
+        assert_eq!(swapped, Err(Error::WrongType));
+    }
+
+    #[test]
+    fn version_changes_only_on_write() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("set", list(&["a"])).unwrap();
+        let before = db.version("set");
+
+        // Act
+        db.set_members("set").unwrap();
+        db.set_add("set", list(&["a"])).unwrap();
+        let after_noop = db.version("set");
+        db.set_add("set", list(&["b"])).unwrap();
+        let after_write = db.version("set");
+        db.remove("set");
+
+        // Assert
+        assert_eq!(after_noop, before);
+        assert_ne!(after_write, before);
+        assert_eq!(db.version("set"), 0);
+    }
+
+    #[test]
+    fn list_set_negative_index_from_tail() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a", "b", "c"]))
+            .unwrap();
+
+        // Act
+        let last = db.list_set("list", -1, Bytes::from_static(b"z"));
+        let first = db.list_set("list", -3, Bytes::from_static(b"x"));
+        let out_of_range = db.list_set("list", -4, Bytes::from_static(b"y"));
+        let missing = db.list_set("missing", 0, Bytes::from_static(b"y"));
+
+        // Assert
+        assert_eq!(last, Ok(()));
+        assert_eq!(first, Ok(()));
+        assert_eq!(out_of_range, Err(Error::IndexOutOfRange));
+        assert_eq!(missing, Err(Error::NoSuchKey));
+        assert_eq!(
+            db.list_pop("list", End::Left, 3),
+            Ok(Some(list(&["x", "b", "z"])))
+        );
+    }
+
+    #[test]
+    fn list_encoding_switches_at_limit_and_back() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_list_limit(ListLimit::new(3).unwrap());
+        let encoding = |db: &ShardedDb| db.inspect("list", Value::encoding).unwrap();
+
+        // Act
+        db.list_push("list", End::Right, list(&["a", "b", "c"]))
+            .unwrap();
+        let at_limit = encoding(&db);
+        db.list_push("list", End::Left, list(&["z"])).unwrap();
+        let over_limit = encoding(&db);
+        db.list_pop("list", End::Right, 2).unwrap();
+        let at_half_limit = encoding(&db);
+        db.list_pop("list", End::Right, 1).unwrap();
+        let under_half_limit = encoding(&db);
+
+        // Assert
+        assert_eq!(at_limit, "listpack");
+        assert_eq!(over_limit, "quicklist");
+        assert_eq!(at_half_limit, "quicklist");
+        assert_eq!(under_half_limit, "listpack");
+        assert_eq!(
+            db.collection_elements("list"),
+            Ok(vec![Bytes::from_static(b"z")])
+        );
+    }
+
+    #[test]
+    fn list_encoding_size_limit_counts_bytes() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let large = Bytes::from(vec![b'x'; 8 * 1024]);
+
+        // Act
+        db.list_push("list", End::Right, list(&["a"])).unwrap();
+        let small = db.inspect("list", Value::encoding).unwrap();
+        db.list_set("list", 0, large).unwrap();
+        let large = db.inspect("list", Value::encoding).unwrap();
+        let used = db.used_memory();
+        db.list_pop("list", End::Left, 1).unwrap();
+
+        // Assert
+        assert_eq!(small, "listpack");
+        assert_eq!(large, "quicklist");
+        assert!(used > 8 * 1024);
+        assert_eq!(db.used_memory(), 0);
+    }
+
+    #[test]
+    fn list_packed_and_deque_edits_agree() {
+        // Arrange
+        let mut packed: List = list(&["a", "b", "c"]).into_iter().collect();
+        let mut deque = packed.clone();
+        deque.fit(ListLimit::new(1).unwrap());
+        assert_eq!(packed.encoding(), "listpack");
+        assert_eq!(deque.encoding(), "quicklist");
+
+        for list in [&mut packed, &mut deque] {
+            // Act
+            list.insert(1, Bytes::from_static(b"inserted"));
+            let replaced = list.set(3, Bytes::from_static(b"last"));
+            let removed = list.remove(0);
+            list.push(End::Left, Bytes::from_static(b"first"));
+            let popped = list.pop(End::Right);
+
+            // Assert
+            assert_eq!(replaced, Some(Bytes::from_static(b"c")));
+            assert_eq!(removed, Some(Bytes::from_static(b"a")));
+            assert_eq!(popped, Some(Bytes::from_static(b"last")));
+            assert_eq!(list.remove(3), None);
+            let elements: Vec<&[u8]> = list.iter().collect();
+            assert_eq!(elements, [&b"first"[..], b"inserted", b"b"]);
+        }
+        assert_eq!(packed, deque);
+    }
+
+    #[test]
+    fn list_remove_matching_from_either_end_in_both_encodings() {
+        // Arrange
+        let cases = [
+            (2, false, list(&["a", "b", "x", "x"])),
+            (2, true, list(&["x", "a", "x", "b"])),
+            (usize::MAX, false, list(&["a", "b"])),
+        ];
+
+        for (limit, from_tail, expected) in cases {
+            for packed_limit in [ListLimit::DEFAULT, ListLimit::new(1).unwrap()] {
+                let mut list: List = list(&["x", "a", "x", "b", "x", "x"]).into_iter().collect();
+                list.fit(packed_limit);
+                let mut remaining: List = expected.iter().cloned().collect();
+                remaining.fit(packed_limit);
+
+                // Act
+                let removed = list.remove_matching(b"x", limit, from_tail);
+
+                // Assert
+                assert_eq!(removed, 6 - expected.len());
+                assert_eq!(list.encoding(), remaining.encoding());
+                assert_eq!(list, remaining);
+                assert_eq!(list.heap_size(), remaining.heap_size());
+            }
+        }
+    }
+
+    #[test]
+    fn list_insert_missing_pivot() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["a", "c"])).unwrap();
+
+        // Act
+        let absent = db.list_insert("list", Position::Before, b"z", Bytes::from_static(b"b"));
+        let inserted = db.list_insert("list", Position::After, b"a", Bytes::from_static(b"b"));
+        let missing = db.list_insert("missing", Position::After, b"a", Bytes::from_static(b"b"));
+
+        // Assert
+        assert_eq!(absent, Ok(-1));
+        assert_eq!(inserted, Ok(3));
+        assert_eq!(missing, Ok(0));
+        assert_eq!(
+            db.list_pop("list", End::Left, 3),
+            Ok(Some(list(&["a", "b", "c"])))
+        );
+    }
+
+    #[test]
+    fn list_remove_negative_count_from_tail() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.list_push("list", End::Right, list(&["x", "a", "x", "b", "x"]))
+            .unwrap();
+
+        // Act
+        let removed = db.list_remove("list", -2, b"x");
+
+        // Assert
+        assert_eq!(removed, Ok(2));
+        assert_eq!(
+            db.list_pop("list", End::Left, 5),
+            Ok(Some(list(&["x", "a", "b"])))
+        );
+    }
+
+    #[test]
+    fn used_memory_tracks_writes_and_deletes() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let keys = ["string", "list", "set", "dest"];
+
+        // Act
+        db.insert("string", "value".into());
+        db.append("string", b"-appended", usize::MAX).unwrap();
+        db.list_push("list", End::Right, list(&["a", "bb", "ccc"]))
+            .unwrap();
+        db.list_set("list", 0, Bytes::from_static(b"longer"))
+            .unwrap();
+        db.list_pop("list", End::Left, 1).unwrap();
+        db.set_add("set", list(&["x", "y", "z"])).unwrap();
+        db.set_remove("set", &list(&["y"])).unwrap();
+        db.set_combine_store(SetOp::Union, "dest", &["set".to_string()])
+            .unwrap();
+        let used = db.used_memory();
+        for key in keys {
+            db.remove(key);
+        }
+
+        // Assert
+        assert!(used > 0);
+        assert_eq!(db.used_memory(), 0);
+    }
+
+    #[test]
+    fn used_memory_matches_per_key_usage() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("string", "value".into());
+        db.set_range("string", 10, b"tail", usize::MAX).unwrap();
+        db.list_push("list", End::Left, list(&["a", "b", "a"]))
+            .unwrap();
+        db.list_remove("list", 0, b"a").unwrap();
+        db.list_insert("list", Position::Before, b"b", "c".into())
+            .unwrap();
+
+        // Act
+        let used = db.used_memory();
+
+        // Assert
+        let expected = ["string", "list"]
+            .iter()
+            .map(|key| db.memory_usage(key).unwrap())
+            .sum::<usize>();
+        assert_eq!(used, expected);
+    }
+
+    #[test]
+    fn set_mismember_keeps_input_order() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.set_add("set", list(&["a", "c"])).unwrap();
+        db.insert("string", "value".into());
+        let members: [&[u8]; 4] = [b"c", b"b", b"a", b"c"];
+
+        // Act
+        let found = db.set_mismember("set", &members);
+        let missing = db.set_mismember("missing", &members);
+        let wrong_type = db.set_mismember("string", &members);
+
+        // Assert
+        assert_eq!(found, Ok(vec![true, false, true, true]));
+        assert_eq!(missing, Ok(vec![false; 4]));
+        assert_eq!(wrong_type, Err(Error::WrongType));
+    }
+
+    #[test]
+    fn incr_by_float_trims_trailing_zeros() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "3.0e3".into());
+
+        // Act
+        let added = db.incr_by_float("key", 7.5).unwrap();
+        let whole = db.incr_by_float("key", 0.5).unwrap();
+        let missing = db.incr_by_float("missing", 10.5).unwrap();
+
+        // Assert
+        assert_eq!(added, "3007.5");
+        assert_eq!(whole, "3008");
+        assert_eq!(missing, "10.5");
+        assert_eq!(db.get("key").unwrap(), Some("3008".into()));
+    }
+
+    #[test]
+    fn incr_by_float_invalid_values_rejected() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("text", "abc".into());
+        db.insert("large", "1.7e308".into());
+        db.list_push("list", End::Left, list(&["a"])).unwrap();
+
+        // Act
+        let text = db.incr_by_float("text", 1.0);
+        let overflow = db.incr_by_float("large", 1.7e308);
+        let wrong_type = db.incr_by_float("list", 1.0);
+
+        // Assert
+        assert_eq!(text, Err(Error::NotAFloat));
+        assert_eq!(overflow, Err(Error::NanOrInfinity));
+        assert_eq!(wrong_type, Err(Error::WrongType));
+        assert_eq!(db.get("large").unwrap(), Some("1.7e308".into()));
+    }
+
+    #[test]
+    fn hash_incr_by_float_updates_field() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let pairs = vec![
+            ("float".into(), "1e2".into()),
+            ("text".into(), "abc".into()),
+        ];
+        db.hash_set("hash", pairs).unwrap();
+
+        // Act
+        let updated = db.hash_incr_by_float("hash", "float".into(), 0.25).unwrap();
+        let text = db.hash_incr_by_float("hash", "text".into(), 1.0);
+
+        // Assert
+        assert_eq!(updated, "100.25");
+        assert_eq!(text, Err(Error::HashNotAFloat));
+        assert_eq!(db.hash_get("hash", b"float").unwrap(), Some(updated));
+    }
+
+    #[test]
+    fn load_round_trips_exported_entries() {
+        // Arrange
+        let mut source = ShardedDb::new();
+        for key in 0..3000 {
+            source.insert(format!("key:{key}"), Bytes::from(format!("value:{key}")));
+        }
+        source
+            .list_push("list", End::Right, list(&["a", "b"]))
+            .unwrap();
+        let mut payload = Vec::new();
+        source.for_each_entry(|key, value| dump::serialize_entry(&mut payload, key, value));
+        let mut target = ShardedDb::new();
+
+        // Act
+        let loaded = target
+            .load(dump::deserialize_entries(&payload).unwrap(), usize::MAX)
+            .unwrap();
+
+        // Assert
+        assert_eq!(loaded, 3001);
+        assert_eq!(target.len(), 3001);
+        assert_eq!(target.used_memory(), source.used_memory());
+        for key in 0..3000 {
+            let value = target.get(format!("key:{key}")).unwrap();
+            assert_eq!(value, Some(Bytes::from(format!("value:{key}"))));
+        }
+    }
+
+    #[test]
+    fn load_rejects_oversized_strings_without_inserting() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let entries = vec![
+            (Bytes::from("small"), Value::String("1234".into())),
+            (Bytes::from("large"), Value::String("12345".into())),
+        ];
+
+        // Act
+        let loaded = db.load(entries, 4);
+
+        // Assert
+        assert_eq!(loaded, Err(Error::ValueTooLarge));
+        assert_eq!(db.len(), 0);
+    }
+
+    #[test]
+    fn group_by_shard_batches_one_lock_per_shard() {
+        // Arrange
+        let entries: Vec<_> = (0..3000)
+            .map(|key| {
+                (
+                    Bytes::from(format!("key:{key}")),
+                    Value::String("value".into()),
+                )
+            })
+            .collect();
+
+        // Act
+        let groups = group_by_shard(entries, 8, 7);
+
+        // Assert
+        assert_eq!(groups.len(), 8);
+        assert_eq!(groups.iter().map(Vec::len).sum::<usize>(), 3000);
+        for (shard, group) in groups.iter().enumerate() {
+            assert!(group.iter().all(|(key, _)| shard_index(7, key, 8) == shard));
+        }
+    }
+
+    #[test]
+    fn load_places_keys_in_the_same_shards_as_insert() {
+        // Arrange
+        let keys: Vec<String> = (0..1000).map(|key| format!("key:{key}")).collect();
+        let mut loaded = ShardedDb::new_seeded(8, 42);
+        let mut inserted = ShardedDb::new_seeded(8, 42);
+
+        // Act
+        loaded
+            .load(
+                keys.iter()
+                    .map(|key| (Bytes::from(key.clone()), Value::String("value".into())))
+                    .collect(),
+                usize::MAX,
+            )
+            .unwrap();
+        for key in &keys {
+            inserted.insert(key, "value".into());
+        }
+
+        // Assert
+        for shard in 0..8 {
+            let shard_keys = |db: &ShardedDb| {
+                let mut keys: Vec<Bytes> =
+                    db.inner[shard].lock().unwrap().db.keys().cloned().collect();
+                keys.sort_unstable();
+                keys
+            };
+            assert_eq!(shard_keys(&loaded), shard_keys(&inserted), "shard {shard}");
+        }
+    }
+
+    #[test]
+    fn for_each_key_in_shard_visits_every_key_once() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(8);
+        for key in 0..500 {
+            db.insert(format!("key:{key}"), "value".into());
+        }
+        let mut visited = Vec::new();
+
+        // Act
+        for index in 0..db.num_shards() {
+            db.for_each_key_in_shard(index, |key, _| visited.push(Bytes::copy_from_slice(key)))
+                .unwrap();
+        }
+        let out_of_range = db.for_each_key_in_shard(8, |_, _| {});
+
+        // Assert
+        visited.sort_unstable();
+        let mut expected: Vec<Bytes> = (0..500).map(|key| format!("key:{key}").into()).collect();
+        expected.sort_unstable();
+        assert_eq!(visited, expected);
+        assert_eq!(out_of_range, Err(Error::IndexOutOfRange));
+    }
+
+    #[test]
+    fn scan_shard_resumes_from_returned_hash() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        for key in 0..10 {
+            db.insert(format!("key:{key}"), "value".into());
+        }
+        let mut visited = Vec::new();
+
+        // Act
+        let (first, resume) = db
+            .scan_shard(0, 0, 4, |key, _| visited.push(Bytes::copy_from_slice(key)))
+            .unwrap();
+        let (second, resume) = db
+            .scan_shard(0, resume.unwrap(), 4, |key, _| {
+                visited.push(Bytes::copy_from_slice(key))
+            })
+            .unwrap();
+        let (last, end) = db
+            .scan_shard(0, resume.unwrap(), 4, |key, _| {
+                visited.push(Bytes::copy_from_slice(key))
+            })
+            .unwrap();
+
+        // Assert
+        assert_eq!((first, second, last, end), (4, 4, 2, None));
+        visited.sort_unstable();
+        let mut expected: Vec<Bytes> = (0..10).map(|key| format!("key:{key}").into()).collect();
+        expected.sort_unstable();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn with_capacity_reserves_each_shards_share() {
+        // Act
+        let db = ShardedDb::with_capacity(8, 42, 1000);
+
+        // Assert
+        for shard in db.inner.iter() {
+            assert!(shard.lock().unwrap().db.capacity() >= 125);
+        }
+    }
+
+    #[test]
+    fn shard_placement_follows_the_seed() {
+        // Arrange
+        let keys: Vec<String> = (0..100).map(|key| format!("key:{key}")).collect();
+        let first = ShardedDb::new_seeded(8, 1);
+        let same_seed = ShardedDb::new_seeded(8, 1);
+        let other_seed = ShardedDb::new_seeded(8, 2);
+
+        // Act
+        let shards = |db: &ShardedDb| {
+            keys.iter()
+                .map(|key| db.shard(key.as_bytes()))
+                .collect::<Vec<_>>()
+        };
+
+        // Assert
+        assert_eq!(shards(&first), shards(&same_seed));
+        assert_ne!(shards(&first), shards(&other_seed));
+    }
+
+    #[test]
+    fn reshard_keeps_every_key() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(8);
+        let keys: Vec<String> = (0..100).map(|key| format!("key:{key}")).collect();
+        for key in &keys {
+            db.insert(key, Bytes::from(key.clone()));
+        }
+        let used = db.used_memory();
+
+        // Act
+        let resharded = db.reshard(3).unwrap();
+
+        // Assert
+        assert_eq!(resharded.inner.len(), 3);
+        assert!(db.is_empty());
+        assert_eq!(resharded.used_memory(), used);
+        for key in &keys {
+            assert_eq!(resharded.get(key).unwrap(), Some(Bytes::from(key.clone())));
+            let shard = resharded.shard(key.as_bytes());
+            assert!(resharded.inner[shard]
+                .lock()
+                .unwrap()
+                .db
+                .contains_key(key.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn sibling_shares_settings_but_not_keys() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("key", "value".into());
+        let sibling = db.sibling();
+
+        // Act
+        sibling.set_active_expire(false);
+        db.set_list_limit(ListLimit::new(3).unwrap());
+
+        // Assert
+        assert!(!db.active_expire());
+        assert_eq!(sibling.list_limit(), ListLimit::new(3).unwrap());
+        assert!(sibling.is_empty());
+    }
+
+    #[test]
+    fn reshard_shares_settings() {
+        // Arrange
+        let db = ShardedDb::new_sized(8);
+        db.set_active_expire(false);
+        db.set_list_limit(ListLimit::new(3).unwrap());
+
+        // Act
+        let resharded = db.reshard(3).unwrap();
+        db.set_active_expire(true);
+
+        // Assert
+        assert!(resharded.active_expire());
+        assert_eq!(resharded.list_limit(), ListLimit::new(3).unwrap());
+    }
+
+    #[test]
+    fn reshard_refuses_zero_shards_and_shared_handles() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(8);
+        db.insert("key", "value".into());
+        let clone = db.clone();
+
+        // Act
+        let zero = db.reshard(0);
+        let shared = db.reshard(3);
+        drop(clone);
+        let alone = db.reshard(3);
+
+        // Assert
+        assert!(matches!(zero, Err(Error::NoShards)));
+        assert!(matches!(shared, Err(Error::SharedHandle)));
+        assert_eq!(alone.unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn evict_lru_removes_least_recently_used() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        db.insert("old", "value".into());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        db.insert("new", "value".into());
+        let limit = db.memory_usage("new").unwrap();
+
+        // Act
+        let evicted = db.evict(limit, EvictionPolicy::AllKeysLru);
+
+        // Assert
+        assert_eq!(evicted, Ok(1));
+        assert_eq!(db.get("old"), Ok(None));
+        assert_eq!(db.evicted_keys(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn eviction_candidate_follows_recency_not_iteration_order() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        for key in 0..50 {
+            db.insert(format!("key:{key}"), "value".into());
+        }
+        let coldest = db.inner[0]
+            .lock()
+            .unwrap()
+            .db
+            .keys()
+            .last()
+            .unwrap()
+            .clone();
+        tokio::time::advance(Duration::from_secs(1)).await;
+        for key in 0..50 {
+            let key = format!("key:{key}");
+            if key.as_bytes() != coldest {
+                db.get(key).unwrap();
+            }
+        }
+
+        // Act
+        let guard = db.inner[0].lock().unwrap();
+        let chosen: HashSet<Bytes> = (0..200)
+            .filter_map(|_| guard.eviction_candidate(EvictionPolicy::AllKeysLru))
+            .collect();
+
+        // Assert
+        assert!(chosen.contains(&coldest));
+    }
+
+    #[test]
+    fn eviction_pools_follow_removals_and_expiry_changes() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(1);
+        for key in ["a", "b", "c"] {
+            db.insert(key, "value".into());
+        }
+        assert!(db.expire("b", Duration::from_secs(10)));
+        assert!(db.expire("c", Duration::from_secs(10)));
+
+        // Act
+        db.remove("a");
+        db.get_ex("c", Expiry::Persist).unwrap();
+
+        // Assert
+        let guard = db.inner[0].lock().unwrap();
+        let mut all: Vec<&Bytes> = guard.keys.sample(5).collect();
+        all.sort();
+        assert_eq!(all, [&Bytes::from("b"), &Bytes::from("c")]);
+        let volatile: Vec<&Bytes> = guard.volatile.sample(5).collect();
+        assert_eq!(volatile, [&Bytes::from("b")]);
+    }
+
+    #[test]
+    fn used_memory_total_survives_swap_and_flush() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let other = db.sibling();
+        db.insert("key", "value".into());
+        let size = db.used_memory();
+
+        // Act
+        db.swap(&other);
+        let swapped = (db.used_memory(), other.used_memory());
+        other.flush();
+
+        // Assert
+        assert_eq!(swapped, (0, size));
+        assert_eq!(other.used_memory(), 0);
+    }
+
+    #[test]
+    fn evict_without_candidates_out_of_memory() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        db.insert("persistent", "value".into());
+
+        // Act
+        let noeviction = db.evict(0, EvictionPolicy::NoEviction);
+        let volatile = db.evict(0, EvictionPolicy::VolatileLru);
+
+        // Assert
+        assert_eq!(noeviction, Err(Error::OutOfMemory));
+        assert_eq!(volatile, Err(Error::OutOfMemory));
+        assert_eq!(db.len(), 1);
+    }
+
+    #[test]
+    fn flush_shard_leaves_other_shards() {
+        // Arrange
+        let mut db = ShardedDb::new_sized(8);
+        let keys: Vec<String> = (0..200).map(|key| format!("key:{key}")).collect();
+        for key in &keys {
+            db.insert(key, "value".into());
+        }
+        let (in_shard, elsewhere): (Vec<_>, Vec<_>) =
+            keys.iter().partition(|key| db.shard(key.as_bytes()) == 3);
+
+        // Act
+        let flushed = db.flush_shard(3);
+        let out_of_range = db.flush_shard(8);
+
+        // Assert
+        assert_eq!(flushed.unwrap().len(), in_shard.len());
+        assert!(matches!(out_of_range, Err(Error::IndexOutOfRange)));
+        assert!(in_shard.iter().all(|key| db.get(key).unwrap().is_none()));
+        assert!(elsewhere.iter().all(|key| db.get(key).unwrap().is_some()));
+    }
+
+    #[tokio::test]
+    async fn free_lazily_defers_only_values_above_threshold() {
+        // Arrange
+        struct SlowDrop(std::sync::mpsc::Receiver<()>);
+        impl Drop for SlowDrop {
+            fn drop(&mut self) {
+                let _ = self.0.recv();
+            }
+        }
+        let mut db = ShardedDb::new();
+        db.list_push("large", End::Right, list(&["a"; 1000]))
+            .unwrap();
+        db.insert("small", "value".into());
+        let large = db.delete("large").unwrap();
+        let small = db.delete("small").unwrap();
+        let threshold = 1024;
+        let (release, blocked) = std::sync::mpsc::channel();
+
+        // Act
+        let large_size = large.heap_size();
+        let deferred = free_lazily(large, large_size, threshold);
+        let small_size = small.heap_size();
+        let inline = free_lazily(small, small_size, threshold);
+        let pending = free_lazily(SlowDrop(blocked), threshold + 1, threshold).unwrap();
+
+        // Assert
+        assert_ok!(deferred.unwrap().await);
+        assert!(inline.is_none());
+        assert!(!pending.is_finished());
+        release.send(()).unwrap();
+        assert_ok!(pending.await);
+    }
+
+    #[test]
+    fn stream_auto_ids_keep_increasing() {
+        // Arrange
+        let mut stream = Stream::default();
+        let fields = || vec![("field".into(), "value".into())];
+
+        // Act
+        let first = stream.add(NewStreamId::Auto, fields(), 1000).unwrap();
+        let same_ms = stream.add(NewStreamId::Auto, fields(), 1000).unwrap();
+        let clock_behind = stream.add(NewStreamId::Auto, fields(), 999).unwrap();
+        let later = stream.add(NewStreamId::Auto, fields(), 1001).unwrap();
+        let in_ms = stream.add(NewStreamId::AutoSeq(1001), fields(), 0).unwrap();
+
+        // Assert
+        assert_eq!(first, StreamId::new(1000, 0));
+        assert_eq!(same_ms, StreamId::new(1000, 1));
+        assert_eq!(clock_behind, StreamId::new(1000, 2));
+        assert_eq!(later, StreamId::new(1001, 0));
+        assert_eq!(in_ms, StreamId::new(1001, 1));
+        assert_eq!(stream.len(), 5);
+    }
+
+    #[test]
+    fn stream_add_rejects_ids_not_above_last() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        let fields = || vec![("field".into(), "value".into())];
+        db.stream_add(
+            "stream",
+            NewStreamId::Explicit(StreamId::new(5, 5)),
+            fields(),
+        )
+        .unwrap();
+
+        // Act
+        let equal = db.stream_add(
+            "stream",
+            NewStreamId::Explicit(StreamId::new(5, 5)),
+            fields(),
+        );
+        let smaller = db.stream_add("stream", NewStreamId::AutoSeq(4), fields());
+        let zero = db.stream_add("fresh", NewStreamId::Explicit(StreamId::MIN), fields());
+        let greater = db.stream_add(
+            "stream",
+            NewStreamId::Explicit(StreamId::new(5, 6)),
+            fields(),
+        );
+
+        // Assert
+        assert_eq!(equal, Err(Error::StreamIdTooSmall));
+        assert_eq!(smaller, Err(Error::StreamIdTooSmall));
+        assert_eq!(zero, Err(Error::StreamIdZero));
+        assert_eq!(greater, Ok(StreamId::new(5, 6)));
+        assert_eq!(db.stream_len("stream"), Ok(2));
+        assert_eq!(db.stream_len("fresh"), Ok(0));
+    }
+
+    #[test]
+    fn stream_range_is_inclusive_and_limited() {
+        // Arrange
+        let mut db = ShardedDb::new();
+        for ms in 1..=5 {
+            let id = NewStreamId::Explicit(StreamId::new(ms, 0));
+            db.stream_add("stream", id, vec![("n".into(), ms.to_string().into())])
+                .unwrap();
+        }
+        let ids = |entries: Vec<(StreamId, _)>| -> Vec<u64> {
+            entries.into_iter().map(|(id, _)| id.ms).collect()
+        };
+
+        // Act
+        let middle = db.stream_range(
+            "stream",
+            StreamId::new(2, 0),
+            StreamId::new(4, 0),
+            usize::MAX,
+        );
+        let all = db.stream_range("stream", StreamId::MIN, StreamId::MAX, usize::MAX);
+        let limited = db.stream_range("stream", StreamId::MIN, StreamId::MAX, 2);
+        let reversed = db.stream_range(
+            "stream",
+            StreamId::new(4, 0),
+            StreamId::new(2, 0),
+            usize::MAX,
+        );
+
+        // Assert
+        assert_eq!(ids(middle.unwrap()), [2, 3, 4]);
+        assert_eq!(ids(all.unwrap()), [1, 2, 3, 4, 5]);
+        assert_eq!(ids(limited.unwrap()), [1, 2]);
+        assert_eq!(ids(reversed.unwrap()), []);
     }
 }
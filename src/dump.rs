@@ -0,0 +1,321 @@
+use crate::db::{Hash, NewStreamId, Stream, StreamId, Value};
+use bytes::{Buf, BufMut, Bytes};
+
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_HASH: u8 = 3;
+const TYPE_STREAM: u8 = 4;
+const DUMP_VERSION: u16 = 1;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("ERR DUMP payload version or checksum are wrong")]
+    Version,
+    #[error("ERR Bad data format")]
+    BadFormat,
+}
+
+/// Serializes a value as `[type][payload][u16 version]`, the payload of DUMP
+/// replies. Strings are a `u32` length followed by the bytes; lists and sets
+/// are a `u32` element count followed by each element as a string, and hashes
+/// a `u32` field count followed by each field and its value. Streams are a
+/// `u32` entry count followed by each entry's id as two `u64`s and its fields
+/// the way a hash writes them.
+pub fn serialize(value: &Value) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(serialized_len(value));
+    match value {
+        Value::String(value) => {
+            dst.put_u8(TYPE_STRING);
+            put_string(&mut dst, value);
+        }
+        Value::List(list) => {
+            dst.put_u8(TYPE_LIST);
+            put_strings(&mut dst, list.len(), list.iter());
+        }
+        Value::Set(set) => {
+            dst.put_u8(TYPE_SET);
+            put_strings(&mut dst, set.len(), set.iter());
+        }
+        Value::Hash(hash) => {
+            dst.put_u8(TYPE_HASH);
+            dst.put_u32_le(hash.len() as u32);
+            for (field, value) in hash.iter() {
+                put_string(&mut dst, field);
+                put_string(&mut dst, value);
+            }
+        }
+        Value::Stream(stream) => {
+            dst.put_u8(TYPE_STREAM);
+            dst.put_u32_le(stream.len() as u32);
+            for (id, fields) in stream.iter() {
+                dst.put_u64_le(id.ms);
+                dst.put_u64_le(id.seq);
+                dst.put_u32_le(fields.len() as u32);
+                for (field, value) in fields {
+                    put_string(&mut dst, field);
+                    put_string(&mut dst, value);
+                }
+            }
+        }
+    }
+    dst.put_u16_le(DUMP_VERSION);
+    dst
+}
+
+/// Reads back a value written by `serialize`.
+pub fn deserialize(mut src: &[u8]) -> Result<Value> {
+    let Some(version) = src.len().checked_sub(2) else {
+        return Err(Error::BadFormat);
+    };
+    if (&src[version..]).get_u16_le() != DUMP_VERSION {
+        return Err(Error::Version);
+    }
+    src = &src[..version];
+
+    if !src.has_remaining() {
+        return Err(Error::BadFormat);
+    }
+    let value = match src.get_u8() {
+        TYPE_STRING => Value::String(get_string(&mut src)?),
+        TYPE_LIST => Value::List(get_strings(&mut src)?.into_iter().collect()),
+        TYPE_SET => Value::Set(get_strings(&mut src)?.into_iter().collect()),
+        TYPE_HASH => {
+            let mut hash = Hash::default();
+            for _ in 0..get_u32(&mut src)? {
+                hash.insert(get_string(&mut src)?, get_string(&mut src)?);
+            }
+            Value::Hash(hash)
+        }
+        TYPE_STREAM => {
+            let mut stream = Stream::default();
+            for _ in 0..get_u32(&mut src)? {
+                let id = StreamId::new(get_u64(&mut src)?, get_u64(&mut src)?);
+                let fields = (0..get_u32(&mut src)?)
+                    .map(|_| Ok((get_string(&mut src)?, get_string(&mut src)?)))
+                    .collect::<Result<_>>()?;
+                stream
+                    .add(NewStreamId::Explicit(id), fields, 0)
+                    .map_err(|_| Error::BadFormat)?;
+            }
+            Value::Stream(stream)
+        }
+        _ => return Err(Error::BadFormat),
+    };
+
+    if src.has_remaining() {
+        return Err(Error::BadFormat);
+    }
+    Ok(value)
+}
+
+/// Appends an entry for bulk export: its key followed by the `serialize`
+/// payload of its value, both prefixed with a `u32` length.
+pub fn serialize_entry(dst: &mut Vec<u8>, key: &[u8], value: &Value) {
+    put_string(dst, key);
+    put_string(dst, &serialize(value));
+}
+
+/// Reads back entries appended by `serialize_entry`, failing on the first
+/// malformed one.
+pub fn deserialize_entries(mut src: &[u8]) -> Result<Vec<(Bytes, Value)>> {
+    let mut entries = Vec::new();
+    while src.has_remaining() {
+        let key = get_string(&mut src)?;
+        let value = deserialize(&get_string(&mut src)?)?;
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+pub fn serialized_len(value: &Value) -> usize {
+    let payload = match value {
+        Value::String(value) => string_len(value),
+        Value::List(list) => strings_len(list.iter()),
+        Value::Set(set) => strings_len(set.iter()),
+        Value::Hash(hash) => {
+            4 + hash
+                .iter()
+                .map(|(field, value)| string_len(field) + string_len(value))
+                .sum::<usize>()
+        }
+        Value::Stream(stream) => {
+            4 + stream
+                .iter()
+                .map(|(_, fields)| {
+                    16 + 4
+                        + fields
+                            .iter()
+                            .map(|(field, value)| string_len(field) + string_len(value))
+                            .sum::<usize>()
+                })
+                .sum::<usize>()
+        }
+    };
+
+    1 + payload + 2
+}
+
+fn put_string(dst: &mut Vec<u8>, value: &[u8]) {
+    dst.put_u32_le(value.len() as u32);
+    dst.put_slice(value);
+}
+
+fn put_strings(dst: &mut Vec<u8>, len: usize, values: impl Iterator<Item = impl AsRef<[u8]>>) {
+    dst.put_u32_le(len as u32);
+    for value in values {
+        put_string(dst, value.as_ref());
+    }
+}
+
+fn get_u32(src: &mut &[u8]) -> Result<u32> {
+    if src.remaining() < 4 {
+        return Err(Error::BadFormat);
+    }
+    Ok(src.get_u32_le())
+}
+
+fn get_u64(src: &mut &[u8]) -> Result<u64> {
+    if src.remaining() < 8 {
+        return Err(Error::BadFormat);
+    }
+    Ok(src.get_u64_le())
+}
+
+fn get_string(src: &mut &[u8]) -> Result<Bytes> {
+    let len = get_u32(src)? as usize;
+    if src.remaining() < len {
+        return Err(Error::BadFormat);
+    }
+    Ok(src.copy_to_bytes(len))
+}
+
+fn get_strings(src: &mut &[u8]) -> Result<Vec<Bytes>> {
+    let len = get_u32(src)?;
+    (0..len).map(|_| get_string(src)).collect()
+}
+
+fn string_len(value: &[u8]) -> usize {
+    4 + value.len()
+}
+
+fn strings_len(values: impl Iterator<Item = impl AsRef<[u8]>>) -> usize {
+    4 + values
+        .map(|value| string_len(value.as_ref()))
+        .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{Hash, NewStreamId, Stream, StreamId, Value};
+    use crate::dump::{
+        deserialize, deserialize_entries, serialize, serialize_entry, serialized_len, Error,
+    };
+    use bytes::Bytes;
+
+    #[test]
+    fn deserialize_round_trips_every_type() {
+        // Arrange
+        let mut hash = Hash::default();
+        hash.insert("field".into(), "value".into());
+        let mut stream = Stream::default();
+        let fields = vec![("field".into(), "value".into()), ("a".into(), "b".into())];
+        let id = NewStreamId::Explicit(StreamId::new(1, 2));
+        stream.add(id, fields, 0).unwrap();
+        let values = [
+            Value::String("abc".into()),
+            Value::List(["a".into(), "b".into()].into_iter().collect()),
+            Value::Set(["a".into(), "b".into()].into_iter().collect()),
+            Value::Hash(hash),
+            Value::Stream(stream),
+        ];
+
+        for value in values {
+            // Act
+            let restored = deserialize(&serialize(&value));
+
+            // Assert
+            assert_eq!(restored, Ok(value));
+        }
+    }
+
+    #[test]
+    fn deserialize_malformed_payloads_rejected() {
+        // Arrange
+        let wrong_version = b"\x00\x00\x00\x00\x00\x02\x00";
+        let truncated = b"\x00\x05\x00\x00\x00abc\x01\x00";
+        let trailing = b"\x00\x00\x00\x00\x00!\x01\x00";
+
+        // Act
+        let wrong_version = deserialize(wrong_version);
+        let truncated = deserialize(truncated);
+        let trailing = deserialize(trailing);
+
+        // Assert
+        assert_eq!(wrong_version, Err(Error::Version));
+        assert_eq!(truncated, Err(Error::BadFormat));
+        assert_eq!(trailing, Err(Error::BadFormat));
+    }
+
+    #[test]
+    fn deserialize_entries_round_trip() {
+        // Arrange
+        let entries = vec![
+            (Bytes::from_static(b"one"), Value::String("1".into())),
+            (
+                Bytes::from_static(b"two"),
+                Value::List(["2".into()].into_iter().collect()),
+            ),
+        ];
+        let mut serialized = Vec::new();
+        for (key, value) in &entries {
+            serialize_entry(&mut serialized, key, value);
+        }
+
+        // Act
+        let restored = deserialize_entries(&serialized);
+
+        // Assert
+        assert_eq!(restored, Ok(entries));
+        assert_eq!(
+            deserialize_entries(&serialized[..serialized.len() - 1]),
+            Err(Error::BadFormat)
+        );
+    }
+
+    #[test]
+    fn serialize_string_layout() {
+        // Arrange
+        let value = Value::String(Bytes::from_static(b"abc"));
+
+        // Act
+        let dump = serialize(&value);
+
+        // Assert
+        assert_eq!(dump, b"\x00\x03\x00\x00\x00abc\x01\x00".to_vec());
+        assert_eq!(dump.len(), serialized_len(&value));
+    }
+
+    #[test]
+    fn serialize_list_layout() {
+        // Arrange
+        let value = Value::List(
+            [&b"a"[..], b"bc"]
+                .into_iter()
+                .map(Bytes::from_static)
+                .collect(),
+        );
+
+        // Act
+        let dump = serialize(&value);
+
+        // Assert
+        assert_eq!(
+            dump,
+            b"\x01\x02\x00\x00\x00\x01\x00\x00\x00a\x02\x00\x00\x00bc\x01\x00".to_vec()
+        );
+        assert_eq!(dump.len(), serialized_len(&value));
+    }
+}
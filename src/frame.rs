@@ -1,11 +1,15 @@
+use crate::parse_int::parse_i64;
 use anyhow::{anyhow, Context};
-use btoi::btoi;
-use bytes::{Buf, Bytes};
-use memchr::memchr;
+use bytes::{Buf, BufMut, Bytes};
+use memchr::{memchr, memchr2};
+use std::fmt::{Display, Formatter};
 use std::io::Cursor;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Largest bulk string accepted from a peer, Redis's default `proto-max-bulk-len`.
+pub const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Stream ended early")]
@@ -16,22 +20,49 @@ pub enum Error {
     UnexpectedError(#[from] anyhow::Error),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
-    Simple(String),
+    Simple(Bytes),
     Error(String),
     Integer(i64),
     Bulk(Bytes),
     Null,
     Array(Vec<Frame>),
+    Set(Vec<Frame>),
+    Map(Vec<(Frame, Frame)>),
+}
+
+/// How line terminators are read. RESP requires `\r\n`, but `Lenient` also
+/// takes a bare `\n`, which telnet and some other clients send instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Newlines {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
 }
 
 impl Frame {
+    /// `+OK`, pointing at static bytes so the reply never allocates.
+    pub const fn ok() -> Self {
+        Self::Simple(Bytes::from_static(b"OK"))
+    }
+
+    /// `+PONG`, pointing at static bytes so the reply never allocates.
+    pub const fn pong() -> Self {
+        Self::Simple(Bytes::from_static(b"PONG"))
+    }
+
     fn simple(line: &[u8]) -> std::result::Result<Self, Error> {
-        let str = String::from_utf8(line.to_vec())
-            .context("protocol error; invalid simple string format")?;
+        std::str::from_utf8(line).context("protocol error; invalid simple string format")?;
 
-        Ok(Self::Simple(str))
+        Ok(Self::Simple(Bytes::copy_from_slice(line)))
     }
 
     fn error(line: &[u8]) -> Result<Self> {
@@ -42,17 +73,17 @@ impl Frame {
     }
 
     fn integer(line: &[u8]) -> Result<Self> {
-        btoi::<i64>(line)
+        parse_i64(line)
             .map(Frame::Integer)
             .map_err(|_| Error::UnexpectedError(anyhow!("protocol error; invalid integer format")))
     }
 
-    fn bulk(buff: &mut Cursor<&[u8]>) -> Result<Self> {
+    fn bulk(buff: &mut Cursor<&[u8]>, newlines: Newlines) -> Result<Self> {
         let len_512_mb_no = 9;
         let len_crlf = 2;
-        let limit = buff.position() + len_512_mb_no + len_crlf;
-        let len = read_line_with_limit(buff, Some(limit as usize))?;
-        let len = btoi::<i32>(len).map_err(|_| {
+        let limit = cursor_index(buff)? + len_512_mb_no + len_crlf;
+        let len = read_line_with_limit(buff, Some(limit), newlines)?;
+        let len = parse_i64(len).map_err(|_| {
             Error::UnexpectedError(anyhow!("protocol error; invalid bulk string length digit"))
         })?;
 
@@ -61,6 +92,11 @@ impl Frame {
             len if len < -1 => Err(Error::UnexpectedError(anyhow!(
                 "protocol error; invalid bulk string length"
             ))),
+            // the digit limit only bounds the magnitude, the cap is checked on the value
+            len if len as u64 > MAX_BULK_LEN as u64 => Err(Error::UnexpectedError(anyhow!(
+                "protocol error; bulk string length exceeds {} bytes",
+                MAX_BULK_LEN
+            ))),
             len => {
                 let binary_line = read_binary_line(buff, len as usize)?.to_vec();
 
@@ -75,26 +111,462 @@ impl Frame {
             }
         }
     }
+
+    fn null(line: &[u8]) -> Result<Self> {
+        if !line.is_empty() {
+            return Err(Error::UnexpectedError(anyhow!(
+                "protocol error; invalid null format"
+            )));
+        }
+
+        Ok(Frame::Null)
+    }
+
+    /// Encodes for RESP2, which is what every connection speaks until HELLO.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut dst = Vec::new();
+        self.encode_into(&mut dst);
+        dst
+    }
+
+    pub fn encode_into<B: BufMut>(&self, dst: &mut B) {
+        self.encode_with(dst, Protocol::Resp2);
+    }
+
+    /// RESP2 has no set, map or null types, so under it sets become arrays, maps
+    /// become flat key/value arrays and null becomes the null bulk string.
+    pub fn encode_with<B: BufMut>(&self, dst: &mut B, protocol: Protocol) {
+        match self {
+            Frame::Simple(content) => {
+                dst.put_u8(b'+');
+                dst.put_slice(content);
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Error(content) => {
+                dst.put_u8(b'-');
+                dst.put_slice(content.as_bytes());
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Integer(num) => match shared_integer(*num) {
+                Some(encoded) => dst.put_slice(encoded),
+                None => put_integer(dst, *num),
+            },
+            Frame::Bulk(content) => {
+                dst.put_u8(b'$');
+                put_decimal(dst, content.len() as u64);
+                dst.put_slice(b"\r\n");
+                dst.put_slice(content);
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Null => match protocol {
+                Protocol::Resp2 => dst.put_slice(b"$-1\r\n"),
+                Protocol::Resp3 => dst.put_slice(b"_\r\n"),
+            },
+            Frame::Array(frames) => {
+                put_aggregate_header(dst, b'*', frames.len());
+                for frame in frames {
+                    frame.encode_with(dst, protocol);
+                }
+            }
+            Frame::Set(frames) => {
+                let prefix = match protocol {
+                    Protocol::Resp2 => b'*',
+                    Protocol::Resp3 => b'~',
+                };
+                put_aggregate_header(dst, prefix, frames.len());
+                for frame in frames {
+                    frame.encode_with(dst, protocol);
+                }
+            }
+            Frame::Map(entries) => {
+                match protocol {
+                    Protocol::Resp2 => put_aggregate_header(dst, b'*', entries.len() * 2),
+                    Protocol::Resp3 => put_aggregate_header(dst, b'%', entries.len()),
+                }
+                for (key, value) in entries {
+                    key.encode_with(dst, protocol);
+                    value.encode_with(dst, protocol);
+                }
+            }
+        }
+    }
+
+    /// The number of bytes `encode` would produce, computed without encoding.
+    pub fn encoded_len(&self) -> usize {
+        self.encoded_len_with(Protocol::Resp2)
+    }
+
+    /// The number of bytes `encode_with` would produce under `protocol`.
+    pub fn encoded_len_with(&self, protocol: Protocol) -> usize {
+        match self {
+            Frame::Simple(content) => 1 + content.len() + 2,
+            Frame::Error(content) => 1 + content.len() + 2,
+            Frame::Integer(num) => {
+                let sign = usize::from(*num < 0);
+                1 + sign + decimal_len(num.unsigned_abs()) + 2
+            }
+            Frame::Bulk(content) => aggregate_header_len(content.len()) + content.len() + 2,
+            Frame::Null => match protocol {
+                Protocol::Resp2 => 5,
+                Protocol::Resp3 => 3,
+            },
+            Frame::Array(frames) | Frame::Set(frames) => {
+                aggregate_header_len(frames.len())
+                    + frames
+                        .iter()
+                        .map(|frame| frame.encoded_len_with(protocol))
+                        .sum::<usize>()
+            }
+            Frame::Map(entries) => {
+                let header = match protocol {
+                    Protocol::Resp2 => aggregate_header_len(entries.len() * 2),
+                    Protocol::Resp3 => aggregate_header_len(entries.len()),
+                };
+                header
+                    + entries
+                        .iter()
+                        .map(|(key, value)| {
+                            key.encoded_len_with(protocol) + value.encoded_len_with(protocol)
+                        })
+                        .sum::<usize>()
+            }
+        }
+    }
+
+    /// Renders the frame for logs, bounded and readable however large or
+    /// binary its bulks are.
+    pub fn for_log(&self) -> LogFrame<'_> {
+        LogFrame(self)
+    }
 }
 
+/// How many bytes of each bulk `LogFrame` shows.
+pub const LOG_BULK_LIMIT: usize = 64;
+
+/// A frame rendered for logs. Bulks show their first `LOG_BULK_LIMIT` bytes
+/// quoted, with anything but printable ASCII escaped as `\xNN` and a note of
+/// how much was left out.
+pub struct LogFrame<'a>(&'a Frame);
+
+impl Display for LogFrame<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Frame::Simple(content) => write!(f, "+{}", String::from_utf8_lossy(content)),
+            Frame::Error(content) => write!(f, "-{content}"),
+            Frame::Integer(num) => write!(f, ":{num}"),
+            Frame::Bulk(content) => {
+                let shown = &content[..content.len().min(LOG_BULK_LIMIT)];
+                f.write_str("\"")?;
+                for &byte in shown {
+                    match byte {
+                        b'\\' => f.write_str("\\\\")?,
+                        b'"' => f.write_str("\\\"")?,
+                        byte if byte.is_ascii_graphic() || byte == b' ' => {
+                            write!(f, "{}", byte as char)?
+                        }
+                        byte => write!(f, "\\x{byte:02x}")?,
+                    }
+                }
+                f.write_str("\"")?;
+                if shown.len() < content.len() {
+                    write!(f, "...({} more bytes)", content.len() - shown.len())?;
+                }
+                Ok(())
+            }
+            Frame::Null => f.write_str("(nil)"),
+            Frame::Array(frames) | Frame::Set(frames) => {
+                f.write_str("[")?;
+                for (i, frame) in frames.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", frame.for_log())?;
+                }
+                f.write_str("]")
+            }
+            Frame::Map(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", key.for_log(), value.for_log())?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Length of a one-byte prefix, a length and CRLF, as `put_aggregate_header`
+/// writes them.
+fn aggregate_header_len(len: usize) -> usize {
+    1 + decimal_len(len as u64) + 2
+}
+
+fn decimal_len(num: u64) -> usize {
+    num.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Writes `num` in decimal through a stack buffer, sparing the `String` that
+/// `to_string` would allocate for every integer and length prefix.
+fn put_decimal<B: BufMut>(dst: &mut B, mut num: u64) {
+    let mut digits = [0; 20];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (num % 10) as u8;
+        num /= 10;
+        if num == 0 {
+            break;
+        }
+    }
+    dst.put_slice(&digits[start..]);
+}
+
+fn put_integer<B: BufMut>(dst: &mut B, num: i64) {
+    dst.put_u8(b':');
+    if num < 0 {
+        dst.put_u8(b'-');
+    }
+    put_decimal(dst, num.unsigned_abs());
+    dst.put_slice(b"\r\n");
+}
+
+const SHARED_INTEGERS_MIN: i64 = -1;
+const SHARED_INTEGERS_MAX: i64 = 256;
+/// Room for the longest shared reply, `:256\r\n`.
+const SHARED_INTEGER_LEN: usize = 6;
+const SHARED_INTEGERS_COUNT: usize = (SHARED_INTEGERS_MAX - SHARED_INTEGERS_MIN + 1) as usize;
+
+/// Integer replies from -1 to 256 encoded ahead of time, like Redis's shared
+/// integers, since counts and flags make up most integer replies. Each is
+/// padded to the same width, with its real length alongside.
+static SHARED_INTEGERS: [([u8; SHARED_INTEGER_LEN], usize); SHARED_INTEGERS_COUNT] =
+    shared_integers();
+
+const fn shared_integers() -> [([u8; SHARED_INTEGER_LEN], usize); SHARED_INTEGERS_COUNT] {
+    let mut table = [([0; SHARED_INTEGER_LEN], 0); SHARED_INTEGERS_COUNT];
+    let mut index = 0;
+    while index < table.len() {
+        let num = SHARED_INTEGERS_MIN + index as i64;
+        let (encoded, len) = &mut table[index];
+        encoded[0] = b':';
+        *len = 1;
+        if num < 0 {
+            encoded[*len] = b'-';
+            *len += 1;
+        }
+        let magnitude = num.unsigned_abs();
+        let mut divisor = 1;
+        while divisor * 10 <= magnitude {
+            divisor *= 10;
+        }
+        while divisor > 0 {
+            encoded[*len] = b'0' + (magnitude / divisor % 10) as u8;
+            *len += 1;
+            divisor /= 10;
+        }
+        encoded[*len] = b'\r';
+        encoded[*len + 1] = b'\n';
+        *len += 2;
+        index += 1;
+    }
+    table
+}
+
+/// The pre-encoded reply for `num`, if it is in the shared range.
+fn shared_integer(num: i64) -> Option<&'static [u8]> {
+    let index = usize::try_from(num.checked_sub(SHARED_INTEGERS_MIN)?).ok()?;
+    let (encoded, len) = SHARED_INTEGERS.get(index)?;
+    Some(&encoded[..*len])
+}
+
+fn put_aggregate_header<B: BufMut>(dst: &mut B, prefix: u8, len: usize) {
+    dst.put_u8(prefix);
+    put_decimal(dst, len as u64);
+    dst.put_slice(b"\r\n");
+}
+
+/// Reads the element count of an aggregate, `None` standing for the RESP2 null
+/// (`-1`) form.
+fn aggregate_len(
+    buff: &mut Cursor<&[u8]>,
+    kind: &str,
+    newlines: Newlines,
+) -> Result<Option<usize>> {
+    let len = read_line(buff, newlines)?;
+    let len = parse_i64(len).map_err(|_| {
+        Error::UnexpectedError(anyhow!("protocol error; invalid {} length digit", kind))
+    })?;
+
+    match len {
+        -1 => Ok(None),
+        len if len < -1 => Err(Error::UnexpectedError(anyhow!(
+            "protocol error; invalid {} length",
+            kind
+        ))),
+        len => usize::try_from(len).map(Some).map_err(|_| {
+            Error::UnexpectedError(anyhow!("protocol error; invalid {} length", kind))
+        }),
+    }
+}
+
+/// An aggregate whose elements are still being read. Maps and attributes
+/// count their keys and values as separate elements.
+struct Partial {
+    kind: Aggregate,
+    len: usize,
+    elements: Vec<Frame>,
+}
+
+#[derive(Clone, Copy)]
+enum Aggregate {
+    Array,
+    Set,
+    Map,
+    Attribute,
+}
+
+impl Partial {
+    fn new(buff: &Cursor<&[u8]>, kind: Aggregate, len: usize) -> Result<Self> {
+        let len = match kind {
+            Aggregate::Map | Aggregate::Attribute => len.checked_mul(2).ok_or_else(|| {
+                Error::UnexpectedError(anyhow!("protocol error; invalid aggregate length"))
+            })?,
+            Aggregate::Array | Aggregate::Set => len,
+        };
+
+        // the declared length is untrusted, don't let it drive the allocation
+        Ok(Partial {
+            kind,
+            len,
+            elements: Vec::with_capacity(len.min(buff.remaining())),
+        })
+    }
+
+    fn is_complete(&self) -> bool {
+        self.elements.len() == self.len
+    }
+
+    /// The finished frame, or `None` for an attribute, which is dropped.
+    fn finish(self) -> Option<Frame> {
+        match self.kind {
+            Aggregate::Array => Some(Frame::Array(self.elements)),
+            Aggregate::Set => Some(Frame::Set(self.elements)),
+            Aggregate::Map => {
+                let mut elements = self.elements.into_iter();
+                let entries = std::iter::from_fn(|| Some((elements.next()?, elements.next()?)));
+                Some(Frame::Map(entries.collect()))
+            }
+            Aggregate::Attribute => None,
+        }
+    }
+}
+
+/// What the bytes at the cursor start: either a whole frame or an aggregate
+/// whose elements follow.
+enum Start {
+    Frame(Frame),
+    Aggregate(Partial),
+}
+
+/// Parses the frame at the cursor and moves the cursor past it.
+///
+/// On any error, whether the frame is incomplete or malformed somewhere inside
+/// a nested aggregate, the cursor is put back where the frame started, so the
+/// caller can wait for more bytes or drop the buffer from that point.
 pub fn parse(buff: &mut Cursor<&[u8]>) -> Result<Frame> {
+    parse_with(buff, Newlines::Strict)
+}
+
+/// Like `parse`, reading line terminators as `newlines` says.
+pub fn parse_with(buff: &mut Cursor<&[u8]>, newlines: Newlines) -> Result<Frame> {
+    let start = buff.position();
+    parse_frame(buff, newlines).inspect_err(|_| buff.set_position(start))
+}
+
+/// Nested aggregates are kept on an explicit stack rather than the call stack,
+/// so how deep a peer nests is bounded by the heap.
+fn parse_frame(buff: &mut Cursor<&[u8]>, newlines: Newlines) -> Result<Frame> {
+    let mut stack: Vec<Partial> = Vec::new();
+
+    loop {
+        let mut frame = match start_frame(buff, newlines)? {
+            Start::Frame(frame) => Some(frame),
+            Start::Aggregate(partial) => {
+                stack.push(partial);
+                None
+            }
+        };
+
+        // hand the frame to its parent, closing every aggregate it completes
+        while let Some(parent) = stack.last_mut() {
+            if let Some(frame) = frame.take() {
+                parent.elements.push(frame);
+            }
+            if !parent.is_complete() {
+                break;
+            }
+            frame = stack.pop().and_then(Partial::finish);
+        }
+
+        if stack.is_empty() {
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+fn start_frame(buff: &mut Cursor<&[u8]>, newlines: Newlines) -> Result<Start> {
     let first_byte = get_u8(buff)?;
-    match first_byte {
+    let frame = match first_byte {
         b'+' => {
-            let line = read_line(buff)?;
-            Frame::simple(line)
+            let line = read_line(buff, newlines)?;
+            Frame::simple(line)?
         }
         b'-' => {
-            let line = read_line(buff)?;
-            Frame::error(line)
+            let line = read_line(buff, newlines)?;
+            Frame::error(line)?
         }
         b':' => {
-            let line = read_line(buff)?;
-            Frame::integer(line)
+            let line = read_line(buff, newlines)?;
+            Frame::integer(line)?
+        }
+        b'$' => Frame::bulk(buff, newlines)?,
+        b'*' => return start_aggregate(buff, Aggregate::Array, "array", newlines),
+        b'~' => return start_aggregate(buff, Aggregate::Set, "set", newlines),
+        b'%' => return start_aggregate(buff, Aggregate::Map, "map", newlines),
+        b'|' => {
+            // an attribute is RESP3's out-of-band metadata about the reply that
+            // follows it; nothing here needs it, so it is read and dropped
+            let Some(len) = aggregate_len(buff, "attribute", newlines)? else {
+                return Err(Error::UnexpectedError(anyhow!(
+                    "protocol error; invalid attribute length"
+                )));
+            };
+            return Partial::new(buff, Aggregate::Attribute, len).map(Start::Aggregate);
         }
-        b'$' => Frame::bulk(buff),
-        b'*' => todo!("Arrays"),
-        _ => Err(Error::UnsupportedFrameType),
+        b'_' => {
+            let line = read_line(buff, newlines)?;
+            Frame::null(line)?
+        }
+        _ => return Err(Error::UnsupportedFrameType),
+    };
+
+    Ok(Start::Frame(frame))
+}
+
+fn start_aggregate(
+    buff: &mut Cursor<&[u8]>,
+    kind: Aggregate,
+    name: &str,
+    newlines: Newlines,
+) -> Result<Start> {
+    match aggregate_len(buff, name, newlines)? {
+        None => Ok(Start::Frame(Frame::Null)),
+        Some(len) => Partial::new(buff, kind, len).map(Start::Aggregate),
     }
 }
 
@@ -106,23 +578,36 @@ fn get_u8(buff: &mut Cursor<&[u8]>) -> Result<u8> {
     Ok(buff.get_u8())
 }
 
-fn read_line<'a>(buff: &mut Cursor<&'a [u8]>) -> Result<&'a [u8]> {
-    read_line_with_limit(buff, None)
+fn read_line<'a>(buff: &mut Cursor<&'a [u8]>, newlines: Newlines) -> Result<&'a [u8]> {
+    read_line_with_limit(buff, None, newlines)
 }
 
-fn read_line_with_limit<'a>(buff: &mut Cursor<&'a [u8]>, limit: Option<usize>) -> Result<&'a [u8]> {
-    let start = buff.position() as usize;
+fn read_line_with_limit<'a>(
+    buff: &mut Cursor<&'a [u8]>,
+    limit: Option<usize>,
+    newlines: Newlines,
+) -> Result<&'a [u8]> {
+    let start = cursor_index(buff)?;
     let buff_ref = *buff.get_ref();
     let end = limit.unwrap_or(buff_ref.len());
     let end = end.min(buff_ref.len());
 
-    let Some(cr_pos) = memchr(b'\r', &buff_ref[start..end]) else {
-        return if limit.is_some() && limit.unwrap() > buff_ref.len() {
-            Err(Error::UnexpectedError(anyhow!(
+    let terminator = match newlines {
+        Newlines::Strict => memchr(b'\r', &buff_ref[start..end]),
+        Newlines::Lenient => memchr2(b'\r', b'\n', &buff_ref[start..end]),
+    };
+    if let Some(lf_pos) = terminator.filter(|pos| buff_ref[start + pos] == b'\n') {
+        buff.set_position((start + lf_pos + 1) as u64);
+        return Ok(&buff_ref[start..start + lf_pos]);
+    }
+
+    let Some(cr_pos) = terminator else {
+        // only an error once everything up to the limit has arrived without a \r
+        return match limit {
+            Some(limit) if limit <= buff_ref.len() => Err(Error::UnexpectedError(anyhow!(
                 "protocol error; \\r\\n not found."
-            )))
-        } else {
-            Err(Error::Incomplete)
+            ))),
+            _ => Err(Error::Incomplete),
         };
     };
 
@@ -150,11 +635,11 @@ fn read_line_with_limit<'a>(buff: &mut Cursor<&'a [u8]>, limit: Option<usize>) -
 }
 
 fn read_binary_line<'a>(buff: &mut Cursor<&'a [u8]>, content_len: usize) -> Result<&'a [u8]> {
-    if buff.remaining() < content_len + 2 {
+    let start = cursor_index(buff)?;
+    if buff.remaining() < content_len.saturating_add(2) {
         return Err(Error::Incomplete);
     }
 
-    let start = buff.position() as usize;
     let end = start + content_len;
     let buff_ref = *buff.get_ref();
     let data = &buff_ref[start..end];
@@ -172,11 +657,25 @@ fn read_binary_line<'a>(buff: &mut Cursor<&'a [u8]>, content_len: usize) -> Resu
     Ok(data)
 }
 
+/// The cursor's position as an index into its buffer. A position past the end,
+/// or one `usize` can't hold on this target, is a protocol error rather than
+/// a truncated index or an out of bounds slice.
+fn cursor_index(buff: &Cursor<&[u8]>) -> Result<usize> {
+    usize::try_from(buff.position())
+        .ok()
+        .filter(|&position| position <= buff.get_ref().len())
+        .ok_or_else(|| Error::UnexpectedError(anyhow!("protocol error; invalid cursor position")))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::frame::{parse, read_line, Error, Frame};
+    use crate::frame::{
+        cursor_index, parse, parse_with, put_integer, read_binary_line, read_line, shared_integer,
+        Error, Frame, Newlines, Protocol, LOG_BULK_LIMIT, MAX_BULK_LEN,
+    };
+    use bytes::Bytes;
     use claims::{assert_err, assert_ok};
-    use proptest::prelude::{any, Strategy};
+    use proptest::prelude::{any, prop_oneof, Just, Strategy};
     use proptest::proptest;
     use std::io::Cursor;
 
@@ -187,7 +686,7 @@ mod tests {
         let mut buff = Cursor::new(buff.as_slice());
 
         // Act
-        let line = read_line(&mut buff);
+        let line = read_line(&mut buff, Newlines::Strict);
 
         // Assert
         assert_err!(&line);
@@ -201,7 +700,7 @@ mod tests {
         let mut buff = Cursor::new(buff.as_slice());
 
         // Act
-        let line = read_line(&mut buff);
+        let line = read_line(&mut buff, Newlines::Strict);
 
         // Assert
         assert_err!(&line);
@@ -215,7 +714,7 @@ mod tests {
         let mut buff = Cursor::new(buff.as_slice());
 
         // Act
-        let line = read_line(&mut buff);
+        let line = read_line(&mut buff, Newlines::Strict);
 
         // Assert
         assert_err!(&line);
@@ -229,7 +728,7 @@ mod tests {
         let mut buff = Cursor::new(buff.as_slice());
 
         // Act
-        let line = read_line(&mut buff);
+        let line = read_line(&mut buff, Newlines::Strict);
 
         // Assert
         assert_err!(&line);
@@ -243,13 +742,57 @@ mod tests {
         let mut buff = Cursor::new(buff.as_slice());
 
         // Act
-        let line = read_line(&mut buff);
+        let line = read_line(&mut buff, Newlines::Strict);
 
         // Assert
         assert_err!(&line);
         assert!(matches!(line, Err(Error::UnexpectedError(_))));
     }
 
+    #[test]
+    fn parse_bare_lf_only_in_lenient_mode() {
+        // Arrange
+        let buff = b"+OK\n:1\r\n";
+
+        // Act
+        let lenient = parse_with(&mut Cursor::new(buff.as_slice()), Newlines::Lenient);
+        let strict = parse(&mut Cursor::new(buff.as_slice()));
+
+        // Assert
+        assert_eq!(lenient.unwrap(), Frame::ok());
+        assert!(matches!(strict, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn parse_lenient_mixed_terminators_valid() {
+        // Arrange
+        let buff = b"*2\n$3\r\nfoo\r\n:42\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse_with(&mut buff, Newlines::Lenient);
+
+        // Assert
+        assert_eq!(
+            frame.unwrap(),
+            Frame::Array(vec![Frame::Bulk("foo".into()), Frame::Integer(42)])
+        );
+        assert_eq!(buff.position(), buff.get_ref().len() as u64);
+    }
+
+    #[test]
+    fn read_line_lenient_cr_without_lf_invalid() {
+        // Arrange
+        let buff = b"unimportant\rx\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let line = read_line(&mut buff, Newlines::Lenient);
+
+        // Assert
+        assert!(matches!(line, Err(Error::UnexpectedError(_))));
+    }
+
     #[test]
     fn parse_empty_buf_incomplete() {
         // Arrange
@@ -734,7 +1277,429 @@ mod tests {
         assert!(matches!(frame, Err(Error::UnexpectedError(_))));
     }
 
+    #[test]
+    fn parse_bulk_string_length_i32_max_invalid() {
+        // Arrange
+        let buff = b"$2147483647\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn parse_bulk_string_length_just_over_cap_invalid() {
+        // Arrange
+        let buff = format!("${}\r\n", MAX_BULK_LEN + 1);
+        let mut buff = Cursor::new(buff.as_bytes());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn parse_bulk_string_length_at_cap_incomplete() {
+        // Arrange
+        let buff = format!("${}\r\n", MAX_BULK_LEN);
+        let mut buff = Cursor::new(buff.as_bytes());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::Incomplete)));
+    }
+
+    #[test]
+    fn parse_bulk_string_partial_length_incomplete() {
+        // Arrange
+        let buff = b"$12";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::Incomplete)));
+    }
+
+    #[test]
+    fn parse_bulk_string_length_too_many_digits_invalid() {
+        // Arrange
+        let buff = b"$123456789012\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn parse_array_frame_valid() {
+        // Arrange
+        let buff = b"*3\r\n$3\r\nSET\r\n:1\r\n*1\r\n+nested\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_ok!(&frame);
+        assert_eq!(
+            frame.unwrap(),
+            Frame::Array(vec![
+                Frame::Bulk("SET".into()),
+                Frame::Integer(1),
+                Frame::Array(vec![Frame::Simple("nested".into())]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_array_frame_null_valid() {
+        // Arrange
+        let buff = b"*-1\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_ok!(&frame);
+        assert!(matches!(frame, Ok(Frame::Null)));
+    }
+
+    #[test]
+    fn parse_array_frame_missing_element_incomplete() {
+        // Arrange
+        let buff = b"*2\r\n$3\r\nGET\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert_err!(&frame);
+        assert!(matches!(frame, Err(Error::Incomplete)));
+    }
+
+    #[test]
+    fn for_log_escapes_and_truncates_bulks() {
+        // Arrange
+        let binary = Frame::Bulk(Bytes::from_static(b"a\x00\xff\"b"));
+        let long = Frame::Bulk(Bytes::from(vec![b'x'; LOG_BULK_LIMIT + 10]));
+        let command = Frame::Array(vec![Frame::Bulk("GET".into()), Frame::Integer(1)]);
+
+        // Act
+        let binary = binary.for_log().to_string();
+        let long = long.for_log().to_string();
+        let command = command.for_log().to_string();
+
+        // Assert
+        assert_eq!(binary, r#""a\x00\xff\"b""#);
+        assert_eq!(
+            long,
+            format!("\"{}\"...(10 more bytes)", "x".repeat(LOG_BULK_LIMIT))
+        );
+        assert_eq!(command, r#"["GET", :1]"#);
+    }
+
+    #[test]
+    fn encode_static_replies() {
+        // Act
+        let ok = Frame::ok().encode();
+        let pong = Frame::pong().encode();
+
+        // Assert
+        assert_eq!(ok, b"+OK\r\n");
+        assert_eq!(pong, b"+PONG\r\n");
+        assert_eq!(Frame::ok().encoded_len(), ok.len());
+    }
+
+    #[test]
+    fn encode_integer_extremes() {
+        // Arrange
+        let frames = [
+            Frame::Integer(0),
+            Frame::Integer(i64::MIN),
+            Frame::Integer(i64::MAX),
+            Frame::Bulk(Bytes::from(vec![b'x'; 10])),
+        ];
+
+        // Act
+        let encoded: Vec<Vec<u8>> = frames.iter().map(Frame::encode).collect();
+
+        // Assert
+        assert_eq!(encoded[0], b":0\r\n");
+        assert_eq!(encoded[1], b":-9223372036854775808\r\n");
+        assert_eq!(encoded[2], b":9223372036854775807\r\n");
+        assert!(encoded[3].starts_with(b"$10\r\n"));
+    }
+
+    #[test]
+    fn encode_array_frame_valid() {
+        // Arrange
+        let frame = Frame::Array(vec![
+            Frame::ok(),
+            Frame::Error("ERR".to_string()),
+            Frame::Integer(-42),
+            Frame::Bulk("hel\r\nlo".into()),
+            Frame::Null,
+        ]);
+
+        // Act
+        let encoded = frame.encode();
+
+        // Assert
+        assert_eq!(
+            encoded,
+            b"*5\r\n+OK\r\n-ERR\r\n:-42\r\n$7\r\nhel\r\nlo\r\n$-1\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn parse_attribute_skipped_before_reply() {
+        // Arrange
+        let buff = b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n:19\r\n:42\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert!(matches!(frame, Ok(Frame::Integer(42))));
+        assert_eq!(buff.position() as usize, buff.get_ref().len());
+    }
+
+    #[test]
+    fn parse_attribute_without_reply_incomplete() {
+        // Arrange
+        let buff = b"|1\r\n+ttl\r\n:3600\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        assert!(matches!(frame, Err(Error::Incomplete)));
+        assert_eq!(buff.position(), 0);
+    }
+
+    #[test]
+    fn encode_shared_integers_match_formatted_encoding() {
+        for num in [i64::MIN, -2, -1, 0, 1, 9, 10, 255, 256, 257, i64::MAX] {
+            // Arrange
+            let mut formatted = Vec::new();
+            put_integer(&mut formatted, num);
+
+            // Act
+            let encoded = Frame::Integer(num).encode();
+
+            // Assert
+            assert_eq!(encoded, formatted);
+            assert_eq!(encoded, format!(":{num}\r\n").into_bytes());
+            assert_eq!(encoded.len(), Frame::Integer(num).encoded_len());
+            assert_eq!(shared_integer(num).is_some(), (-1..=256).contains(&num));
+        }
+    }
+
+    #[test]
+    fn cursor_index_boundaries() {
+        // Arrange
+        let buff = b"+OK\r\n";
+        let at = |position: u64| {
+            let mut buff = Cursor::new(buff.as_slice());
+            buff.set_position(position);
+            cursor_index(&buff)
+        };
+
+        // Act
+        let start = at(0);
+        let end = at(buff.len() as u64);
+        let past_end = at(buff.len() as u64 + 1);
+        let huge = at(u64::MAX);
+
+        // Assert
+        assert_eq!(assert_ok!(start), 0);
+        assert_eq!(assert_ok!(end), buff.len());
+        assert!(matches!(past_end, Err(Error::UnexpectedError(_))));
+        assert!(matches!(huge, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn read_past_end_of_buffer_invalid() {
+        // Arrange
+        let buff = b"$3\r\nfoo\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+        buff.set_position(u64::MAX);
+
+        // Act
+        let line = read_line(&mut buff, Newlines::Strict);
+        let binary_line = read_binary_line(&mut buff, 3);
+
+        // Assert
+        assert!(matches!(line, Err(Error::UnexpectedError(_))));
+        assert!(matches!(binary_line, Err(Error::UnexpectedError(_))));
+    }
+
+    #[test]
+    fn parse_mid_array_error_resets_cursor_to_frame_start() {
+        // Arrange
+        let buff = b"+OK\r\n*3\r\n:1\r\n*1\r\n:x\r\n:3\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let first = parse(&mut buff);
+        let second = parse(&mut buff);
+
+        // Assert
+        assert_ok!(&first);
+        assert!(matches!(second, Err(Error::UnexpectedError(_))));
+        assert_eq!(buff.position(), 5);
+    }
+
+    #[test]
+    fn parse_incomplete_array_resets_cursor_to_frame_start() {
+        // Arrange
+        let buff = b":7\r\n*2\r\n$3\r\nfoo\r\n$3\r\nba";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let first = parse(&mut buff);
+        let second = parse(&mut buff);
+
+        // Assert
+        assert_ok!(&first);
+        assert!(matches!(second, Err(Error::Incomplete)));
+        assert_eq!(buff.position(), 4);
+    }
+
+    #[test]
+    fn parse_deeply_nested_array_round_trips() {
+        // Arrange
+        let depth = 5_000;
+        let mut bytes = b"*1\r\n".repeat(depth);
+        bytes.extend_from_slice(b"%1\r\n+k\r\n~1\r\n:1\r\n");
+        let mut buff = Cursor::new(bytes.as_slice());
+
+        // Act
+        let frame = parse(&mut buff);
+
+        // Assert
+        let frame = assert_ok!(frame);
+        assert_eq!(buff.position() as usize, bytes.len());
+        let mut encoded = Vec::new();
+        frame.encode_with(&mut encoded, Protocol::Resp3);
+        assert_eq!(encoded, bytes);
+        let mut nested = &frame;
+        for _ in 0..depth {
+            let Frame::Array(elements) = nested else {
+                panic!("expected an array, got {nested:?}");
+            };
+            nested = &elements[0];
+        }
+        assert!(matches!(nested, Frame::Map(entries) if entries.len() == 1));
+    }
+
+    #[test]
+    fn parse_resp3_aggregates_valid() {
+        // Arrange
+        let buff = b"~2\r\n+a\r\n+b\r\n%1\r\n+key\r\n:1\r\n_\r\n";
+        let mut buff = Cursor::new(buff.as_slice());
+
+        // Act
+        let set = parse(&mut buff);
+        let map = parse(&mut buff);
+        let null = parse(&mut buff);
+
+        // Assert
+        assert_eq!(
+            set.unwrap(),
+            Frame::Set(vec![Frame::Simple("a".into()), Frame::Simple("b".into())])
+        );
+        assert_eq!(
+            map.unwrap(),
+            Frame::Map(vec![(Frame::Simple("key".into()), Frame::Integer(1))])
+        );
+        assert_eq!(null.unwrap(), Frame::Null);
+    }
+
+    #[test]
+    fn encode_resp3_aggregates_downgraded_for_resp2() {
+        // Arrange
+        let frame = Frame::Array(vec![
+            Frame::Set(vec![Frame::Integer(1)]),
+            Frame::Map(vec![(Frame::Simple("key".into()), Frame::Null)]),
+        ]);
+        let mut resp2 = Vec::new();
+        let mut resp3 = Vec::new();
+
+        // Act
+        frame.encode_with(&mut resp2, Protocol::Resp2);
+        frame.encode_with(&mut resp3, Protocol::Resp3);
+
+        // Assert
+        assert_eq!(resp2, b"*2\r\n*1\r\n:1\r\n*2\r\n+key\r\n$-1\r\n".to_vec());
+        assert_eq!(resp3, b"*2\r\n~1\r\n:1\r\n%1\r\n+key\r\n_\r\n".to_vec());
+    }
+
+    #[test]
+    fn encoded_len_matches_encoding() {
+        // Arrange
+        let frames = [
+            Frame::ok(),
+            Frame::Error("ERR".to_string()),
+            Frame::Integer(0),
+            Frame::Integer(-42),
+            Frame::Integer(i64::MIN),
+            Frame::Bulk(Bytes::new()),
+            Frame::Bulk(Bytes::from(vec![b'x'; 1000])),
+            Frame::Null,
+            Frame::Array(vec![
+                Frame::Array(vec![Frame::Integer(1), Frame::Null]),
+                Frame::Array(vec![]),
+            ]),
+            Frame::Set(vec![Frame::Integer(1)]),
+            Frame::Map(vec![(Frame::Simple("key".into()), Frame::Null)]),
+        ];
+
+        for frame in frames {
+            for protocol in [Protocol::Resp2, Protocol::Resp3] {
+                // Act
+                let len = frame.encoded_len_with(protocol);
+
+                // Assert
+                let mut encoded = Vec::new();
+                frame.encode_with(&mut encoded, protocol);
+                assert_eq!(len, encoded.len(), "{frame:?} under {protocol:?}");
+            }
+            assert_eq!(frame.encoded_len(), frame.encode().len());
+        }
+    }
+
     proptest! {
+        #[test]
+        fn encoded_len_matches_encoding_of_any_tree(frame in frame_tree_strategy()) {
+            // Act
+            let len = frame.encoded_len();
+
+            // Assert
+            assert_eq!(len, frame.encode().len());
+        }
+
         #[test]
         fn read_line_valid_from_any_position((prefix, content, suffix) in valid_line_with_prefix_and_suffix_strategy()) {
             // Arrange
@@ -747,7 +1712,7 @@ mod tests {
             cursor.set_position(prefix.len() as u64);
 
             // Act
-            let line = read_line(&mut cursor);
+            let line = read_line(&mut cursor, Newlines::Strict);
 
             // Assert
             assert_ok!(&line);
@@ -818,7 +1783,7 @@ mod tests {
             let mut buff = Cursor::new(line);
 
             // Act
-            let frame = Frame::bulk(&mut buff);
+            let frame = Frame::bulk(&mut buff, Newlines::Strict);
             // Assert
             assert_ok!(&frame);
             if let Ok(Frame::Bulk(content)) = frame {
@@ -827,6 +1792,21 @@ mod tests {
                 panic!("Expected Frame::Integer variant");
             }
         }
+
+        #[test]
+        fn array_frame_round_trip(frame in frame_tree_strategy()) {
+            // Arrange
+            let encoded = frame.encode();
+            let mut buff = Cursor::new(encoded.as_slice());
+
+            // Act
+            let parsed = parse(&mut buff);
+
+            // Assert
+            assert_ok!(&parsed);
+            assert_eq!(parsed.unwrap(), frame);
+            assert_eq!(buff.position(), encoded.len() as u64);
+        }
     }
 
     // ------------------------------------------------
@@ -879,4 +1859,24 @@ mod tests {
                 (frame.into_bytes(), content)
             })
     }
+
+    fn valid_leaf_frame_strategy() -> impl Strategy<Value = Frame> {
+        prop_oneof![
+            valid_simple_string_strategy().prop_map(|bytes| Frame::Simple(bytes.into())),
+            valid_simple_error_strategy()
+                .prop_map(|bytes| Frame::Error(String::from_utf8(bytes).unwrap())),
+            any::<i64>().prop_map(Frame::Integer),
+            // arbitrary bytes, so CRLF inside bulks is covered too
+            proptest::collection::vec(any::<u8>(), 0..64)
+                .prop_map(|bytes| Frame::Bulk(Bytes::from(bytes))),
+            Just(Frame::Bulk(Bytes::from_static(b"\r\n"))),
+            Just(Frame::Null),
+        ]
+    }
+
+    fn frame_tree_strategy() -> impl Strategy<Value = Frame> {
+        valid_leaf_frame_strategy().prop_recursive(4, 64, 8, |inner| {
+            proptest::collection::vec(inner, 0..8).prop_map(Frame::Array)
+        })
+    }
 }
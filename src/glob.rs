@@ -0,0 +1,137 @@
+/// Redis-style glob matching supporting `*`, `?`, `[...]` classes (with `^`
+/// negation and `a-z` ranges) and `\` escapes.
+pub fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // position to resume from when a `*` has to swallow one more byte
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        p += 2;
+                        t += 1;
+                        continue;
+                    }
+                }
+                byte => {
+                    if byte == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        match backtrack {
+            Some((star, consumed)) => {
+                p = star + 1;
+                t = consumed + 1;
+                backtrack = Some((star, consumed + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+/// Returns whether `byte` matches the class starting at `start`, and the
+/// pattern position right after the closing `]`. An unterminated class is
+/// `None` and never matches.
+fn match_class(pattern: &[u8], start: usize, byte: u8) -> Option<(bool, usize)> {
+    let mut p = start + 1;
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+
+    let mut matched = false;
+    loop {
+        match *pattern.get(p)? {
+            b']' => break,
+            b'\\' => {
+                p += 1;
+                matched |= *pattern.get(p)? == byte;
+            }
+            low if pattern.get(p + 1) == Some(&b'-') && pattern.get(p + 2) != Some(&b']') => {
+                let high = *pattern.get(p + 2)?;
+                let (low, high) = if low <= high {
+                    (low, high)
+                } else {
+                    (high, low)
+                };
+                matched |= (low..=high).contains(&byte);
+                p += 2;
+            }
+            other => matched |= other == byte,
+        }
+        p += 1;
+    }
+
+    Some((matched != negate, p + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glob::matches;
+
+    #[test]
+    fn matches_literal() {
+        assert!(matches(b"maxmemory", b"maxmemory"));
+        assert!(!matches(b"maxmemory", b"maxmemory-policy"));
+    }
+
+    #[test]
+    fn matches_star() {
+        assert!(matches(b"*", b""));
+        assert!(matches(b"max*", b"maxmemory-policy"));
+        assert!(matches(b"*memory*", b"maxmemory-policy"));
+        assert!(matches(b"news.*", b"news.tech"));
+        assert!(!matches(b"news.*", b"sport.tech"));
+    }
+
+    #[test]
+    fn matches_question_mark() {
+        assert!(matches(b"h?llo", b"hello"));
+        assert!(!matches(b"h?llo", b"hllo"));
+    }
+
+    #[test]
+    fn matches_class() {
+        assert!(matches(b"h[ae]llo", b"hallo"));
+        assert!(!matches(b"h[ae]llo", b"hillo"));
+        assert!(matches(b"h[^e]llo", b"hallo"));
+        assert!(!matches(b"h[^e]llo", b"hello"));
+        assert!(matches(b"h[a-c]llo", b"hbllo"));
+        assert!(!matches(b"h[a-c]llo", b"hdllo"));
+        assert!(!matches(b"h[ae", b"ha"));
+    }
+
+    #[test]
+    fn matches_escape() {
+        assert!(matches(b"h\\*llo", b"h*llo"));
+        assert!(!matches(b"h\\*llo", b"hello"));
+    }
+}
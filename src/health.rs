@@ -0,0 +1,70 @@
+use crate::db::ShardedDb;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{self, Instant};
+use tracing::{debug, error};
+
+/// Most of a request read before answering; probes send a line or two.
+const MAX_REQUEST_LEN: usize = 4 * 1024;
+/// How long a probe gets to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answers every HTTP request on `listener` with `200 OK` and a small JSON
+/// body of the key count and uptime, for liveness and readiness probes. There
+/// is no routing: any path and method gets the same answer.
+pub async fn serve(listener: TcpListener, db: ShardedDb, started_at: Instant) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(err) => {
+                error!(cause = %err, "failed to accept health check");
+                return;
+            }
+        };
+
+        let db = db.clone();
+        tokio::spawn(async move {
+            if let Err(err) = respond(socket, &db, started_at).await {
+                debug!(cause = %err, "health check failed");
+            }
+        });
+    }
+}
+
+async fn respond(
+    mut socket: TcpStream,
+    db: &ShardedDb,
+    started_at: Instant,
+) -> std::io::Result<()> {
+    // The request itself doesn't matter, but reading up to the end of its
+    // headers keeps the client from seeing a reset.
+    let mut request = Vec::new();
+    let mut chunk = [0; 512];
+    let read = time::timeout(REQUEST_TIMEOUT, async {
+        while !request.windows(4).any(|window| window == b"\r\n\r\n")
+            && request.len() < MAX_REQUEST_LEN
+        {
+            let n = socket.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&chunk[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    });
+    let _ = read.await;
+
+    let body = format!(
+        r#"{{"status":"ok","keys":{},"uptime_in_seconds":{}}}"#,
+        db.len(),
+        started_at.elapsed().as_secs()
+    );
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await
+}
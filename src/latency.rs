@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bucket `i` counts samples under `2^i` microseconds; the last one also takes
+/// everything slower.
+const BUCKETS: usize = 32;
+
+/// Server-wide histogram of command processing times. Recording is a handful
+/// of relaxed atomic updates, so every connection can share one without a lock.
+#[derive(Clone)]
+pub struct LatencyMonitor {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    buckets: [AtomicU64; BUCKETS],
+    latest_micros: AtomicU64,
+    latest_at: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+/// The most recent sample, with the worst seen since the last reset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Latest {
+    /// Unix time, in seconds, of the most recent sample.
+    pub at: u64,
+    pub latest: Duration,
+    pub max: Duration,
+}
+
+impl LatencyMonitor {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+                latest_micros: AtomicU64::new(0),
+                latest_at: AtomicU64::new(0),
+                max_micros: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn record(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+
+        self.inner.buckets[bucket.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+        self.inner.latest_micros.store(micros, Ordering::Relaxed);
+        self.inner.latest_at.store(at, Ordering::Relaxed);
+        self.inner.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// `None` until something has been recorded since the last reset.
+    pub fn latest(&self) -> Option<Latest> {
+        let at = self.inner.latest_at.load(Ordering::Relaxed);
+        (at != 0).then(|| Latest {
+            at,
+            latest: Duration::from_micros(self.inner.latest_micros.load(Ordering::Relaxed)),
+            max: Duration::from_micros(self.inner.max_micros.load(Ordering::Relaxed)),
+        })
+    }
+
+    /// Non-empty buckets as `(upper bound, samples)`, fastest first.
+    pub fn histogram(&self) -> Vec<(Duration, u64)> {
+        self.inner
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(bucket, count)| {
+                let bound = Duration::from_micros(1 << bucket);
+                (bound, count.load(Ordering::Relaxed))
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        for bucket in &self.inner.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.inner.latest_micros.store(0, Ordering::Relaxed);
+        self.inner.latest_at.store(0, Ordering::Relaxed);
+        self.inner.max_micros.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for LatencyMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::latency::LatencyMonitor;
+    use std::time::Duration;
+
+    #[test]
+    fn record_buckets_by_power_of_two() {
+        // Arrange
+        let monitor = LatencyMonitor::new();
+
+        // Act
+        monitor.record(Duration::ZERO);
+        monitor.record(Duration::from_micros(3));
+        monitor.record(Duration::from_micros(2));
+        monitor.record(Duration::from_secs(1_000_000));
+
+        // Assert
+        assert_eq!(
+            monitor.histogram(),
+            vec![
+                (Duration::from_micros(1), 1),
+                (Duration::from_micros(4), 2),
+                (Duration::from_micros(1 << 31), 1),
+            ]
+        );
+        let latest = monitor.latest().unwrap();
+        assert_eq!(latest.latest, Duration::from_secs(1_000_000));
+        assert_eq!(latest.max, Duration::from_secs(1_000_000));
+    }
+
+    #[test]
+    fn reset_clears_everything() {
+        // Arrange
+        let monitor = LatencyMonitor::new();
+        monitor.record(Duration::from_millis(5));
+
+        // Act
+        monitor.reset();
+
+        // Assert
+        assert_eq!(monitor.latest(), None);
+        assert!(monitor.histogram().is_empty());
+    }
+}
@@ -1,3 +1,19 @@
+pub mod client;
+pub mod clients;
+pub mod cmd;
+pub mod config;
 pub mod connection;
 pub mod db;
+pub mod dump;
 pub mod frame;
+pub mod glob;
+pub mod health;
+pub mod latency;
+pub mod monitor;
+pub mod notify;
+pub mod parse_int;
+pub mod pubsub;
+pub mod reply;
+pub mod server;
+pub mod stats;
+pub mod transaction;
@@ -0,0 +1,137 @@
+use crate::frame::Frame;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Lines a MONITOR connection may fall behind by; older ones are skipped.
+const CAPACITY: usize = 1024;
+
+/// Server-wide feed of every command received, in the line format of Redis's
+/// MONITOR.
+#[derive(Clone)]
+pub struct Feed {
+    sender: broadcast::Sender<String>,
+}
+
+impl Feed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    /// Formats and sends `frame`, but only while someone is monitoring.
+    pub fn publish(&self, frame: &Frame, client: SocketAddr) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        if let Some(line) = format_line(SystemTime::now(), client, frame) {
+            let _ = self.sender.send(line);
+        }
+    }
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single connection's view of the feed, empty until MONITOR is issued.
+#[derive(Default)]
+pub struct Monitoring {
+    receiver: Option<broadcast::Receiver<String>>,
+}
+
+impl Monitoring {
+    pub fn is_active(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    pub fn start(&mut self, feed: &Feed) {
+        if self.receiver.is_none() {
+            self.receiver = Some(feed.sender.subscribe());
+        }
+    }
+
+    /// Waits for the next line. Never resolves while not monitoring, so it can
+    /// sit in a `select!`. Lines missed by falling behind are skipped.
+    pub async fn next_line(&mut self) -> String {
+        let Some(receiver) = &mut self.receiver else {
+            return std::future::pending().await;
+        };
+
+        loop {
+            match receiver.recv().await {
+                Ok(line) => return line,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return std::future::pending().await,
+            }
+        }
+    }
+}
+
+/// `<unix time> [0 <client>] "arg" "arg" ...`, with arguments escaped the way
+/// Redis quotes them. Frames that aren't commands have no line.
+fn format_line(at: SystemTime, client: SocketAddr, frame: &Frame) -> Option<String> {
+    let Frame::Array(parts) = frame else {
+        return None;
+    };
+    let at = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    let mut line = format!("{}.{:06} [0 {client}]", at.as_secs(), at.subsec_micros());
+    for part in parts {
+        let arg = match part {
+            Frame::Bulk(arg) | Frame::Simple(arg) => &arg[..],
+            _ => return None,
+        };
+
+        line.push_str(" \"");
+        for &byte in arg {
+            match byte {
+                b'\\' => line.push_str("\\\\"),
+                b'"' => line.push_str("\\\""),
+                b'\n' => line.push_str("\\n"),
+                b'\r' => line.push_str("\\r"),
+                b'\t' => line.push_str("\\t"),
+                0x07 => line.push_str("\\a"),
+                0x08 => line.push_str("\\b"),
+                byte if byte.is_ascii_graphic() || byte == b' ' => line.push(byte as char),
+                byte => {
+                    let _ = write!(line, "\\x{byte:02x}");
+                }
+            }
+        }
+        line.push('"');
+    }
+
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Frame;
+    use crate::monitor::format_line;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn format_line_quotes_and_escapes_args() {
+        // Arrange
+        let at = UNIX_EPOCH + Duration::from_micros(1_339_518_083_107_412);
+        let client = "127.0.0.1:60866".parse().unwrap();
+        let frame = Frame::Array(vec![
+            Frame::Bulk("set".into()),
+            Frame::Bulk("key".into()),
+            Frame::Bulk("say \"hi\"\n\x01".into()),
+        ]);
+
+        // Act
+        let line = format_line(at, client, &frame);
+
+        // Assert
+        assert_eq!(
+            line.unwrap(),
+            r#"1339518083.107412 [0 127.0.0.1:60866] "set" "key" "say \"hi\"\n\x01""#
+        );
+    }
+}
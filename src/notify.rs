@@ -0,0 +1,262 @@
+use crate::cmd::Command;
+use crate::frame::Frame;
+use crate::pubsub::PubSub;
+use bytes::Bytes;
+use std::fmt::{Display, Formatter};
+use std::ops::BitOr;
+use std::str::FromStr;
+
+/// Which keyspace notifications get published, in the letter format of Redis's
+/// `notify-keyspace-events`. Nothing is published unless at least one of `K`
+/// or `E` and one event class are set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NotifyFlags(u16);
+
+impl NotifyFlags {
+    pub const KEYSPACE: Self = Self(1 << 0);
+    pub const KEYEVENT: Self = Self(1 << 1);
+    pub const GENERIC: Self = Self(1 << 2);
+    pub const STRING: Self = Self(1 << 3);
+    pub const LIST: Self = Self(1 << 4);
+    pub const SET: Self = Self(1 << 5);
+    pub const HASH: Self = Self(1 << 6);
+    pub const ZSET: Self = Self(1 << 7);
+    pub const EXPIRED: Self = Self(1 << 8);
+    pub const EVICTED: Self = Self(1 << 9);
+    pub const STREAM: Self = Self(1 << 10);
+    pub const KEY_MISS: Self = Self(1 << 11);
+    pub const NEW: Self = Self(1 << 12);
+    /// Every class `A` stands for, which excludes key misses and new keys.
+    pub const ALL: Self = Self(
+        Self::GENERIC.0
+            | Self::STRING.0
+            | Self::LIST.0
+            | Self::SET.0
+            | Self::HASH.0
+            | Self::ZSET.0
+            | Self::EXPIRED.0
+            | Self::EVICTED.0
+            | Self::STREAM.0,
+    );
+
+    const LETTERS: [(char, Self); 12] = [
+        ('g', Self::GENERIC),
+        ('$', Self::STRING),
+        ('l', Self::LIST),
+        ('s', Self::SET),
+        ('h', Self::HASH),
+        ('z', Self::ZSET),
+        ('x', Self::EXPIRED),
+        ('e', Self::EVICTED),
+        ('t', Self::STREAM),
+        ('m', Self::KEY_MISS),
+        ('n', Self::NEW),
+        ('K', Self::KEYSPACE),
+    ];
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for NotifyFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl FromStr for NotifyFlags {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars().try_fold(Self::default(), |flags, letter| {
+            let flag = match letter {
+                'A' => Self::ALL,
+                'E' => Self::KEYEVENT,
+                letter => Self::LETTERS
+                    .iter()
+                    .find(|(candidate, _)| *candidate == letter)
+                    .map(|(_, flag)| *flag)
+                    .ok_or(())?,
+            };
+            Ok(flags | flag)
+        })
+    }
+}
+
+impl Display for NotifyFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let all = self.contains(Self::ALL);
+        if all {
+            f.write_str("A")?;
+        }
+        for (letter, flag) in Self::LETTERS {
+            if !(all && Self::ALL.contains(flag)) && self.contains(flag) {
+                write!(f, "{letter}")?;
+            }
+        }
+        if self.contains(Self::KEYEVENT) {
+            f.write_str("E")?;
+        }
+        Ok(())
+    }
+}
+
+/// A change to a single key, published on `__keyspace@<db>__:<key>` and
+/// `__keyevent@<db>__:<event>` when the flags allow it.
+#[derive(Debug)]
+pub struct Event {
+    class: NotifyFlags,
+    name: String,
+    key: Bytes,
+}
+
+impl Event {
+    /// The event a command raises if it changes its key, or `None` for commands
+    /// that never write.
+    pub fn for_command(command: &Command) -> Option<Self> {
+        let (class, key) = match command {
+            Command::Append(cmd) => (NotifyFlags::STRING, cmd.key()),
+            Command::Cas(cmd) => (NotifyFlags::STRING, cmd.key()),
+            Command::Expire(cmd) => (NotifyFlags::GENERIC, cmd.key()),
+            Command::HExpire(cmd) => (NotifyFlags::HASH, cmd.key()),
+            Command::HIncrByFloat(cmd) => (NotifyFlags::HASH, cmd.key()),
+            Command::HPersist(cmd) => (NotifyFlags::HASH, cmd.key()),
+            Command::HSet(cmd) => (NotifyFlags::HASH, cmd.key()),
+            Command::IncrByFloat(cmd) => (NotifyFlags::STRING, cmd.key()),
+            Command::LInsert(cmd) => (NotifyFlags::LIST, cmd.key()),
+            Command::LMove(cmd) => (NotifyFlags::LIST, cmd.destination()),
+            Command::LRem(cmd) => (NotifyFlags::LIST, cmd.key()),
+            Command::LSet(cmd) => (NotifyFlags::LIST, cmd.key()),
+            Command::Pop(cmd) => (NotifyFlags::LIST, cmd.key()),
+            Command::Push(cmd) => (NotifyFlags::LIST, cmd.key()),
+            Command::SAdd(cmd) => (NotifyFlags::SET, cmd.key()),
+            Command::SetOperation(cmd) => (NotifyFlags::SET, cmd.destination()?),
+            Command::SetRange(cmd) => (NotifyFlags::STRING, cmd.key()),
+            Command::SRem(cmd) => (NotifyFlags::SET, cmd.key()),
+            Command::XAdd(cmd) => (NotifyFlags::STREAM, cmd.key()),
+            _ => return None,
+        };
+
+        Some(Self {
+            class,
+            name: command.get_name().to_string(),
+            key: key.clone(),
+        })
+    }
+
+    /// SET's event, raised only once it has written.
+    pub fn set(key: &Bytes) -> Self {
+        Self {
+            class: NotifyFlags::STRING,
+            name: "set".to_string(),
+            key: key.clone(),
+        }
+    }
+
+    /// DEL's event for one of the keys it removed.
+    pub fn deleted(key: &Bytes) -> Self {
+        Self {
+            class: NotifyFlags::GENERIC,
+            name: "del".to_string(),
+            key: key.clone(),
+        }
+    }
+
+    /// Publishes the event unless `response` shows the command failed or left
+    /// the key untouched (an error, a null, or a zero or negative count). `db`
+    /// is the number of the database the key is in.
+    pub fn publish(self, pubsub: &PubSub, flags: NotifyFlags, response: &Frame, db: usize) {
+        if matches!(
+            response,
+            Frame::Error(_) | Frame::Null | Frame::Integer(..=0)
+        ) || !flags.intersects(self.class)
+        {
+            return;
+        }
+
+        if flags.contains(NotifyFlags::KEYSPACE) {
+            let channel = format!("__keyspace@{db}__:{}", String::from_utf8_lossy(&self.key));
+            pubsub.publish(&channel, Bytes::from(self.name.clone()));
+        }
+        if flags.contains(NotifyFlags::KEYEVENT) {
+            let channel = format!("__keyevent@{db}__:{}", self.name);
+            pubsub.publish(&channel, self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Frame;
+    use crate::notify::{Event, NotifyFlags};
+    use crate::pubsub::{PubSub, Subscriptions};
+    use bytes::Bytes;
+    use claims::assert_err;
+
+    #[test]
+    fn flags_parse_and_display_round_trip() {
+        // Arrange
+        let cases = [("", ""), ("E$", "$E"), ("KEA", "AKE"), ("Klx", "lxK")];
+
+        for (input, expected) in cases {
+            // Act
+            let flags: NotifyFlags = input.parse().unwrap();
+
+            // Assert
+            assert_eq!(flags.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn flags_parse_unknown_letter_invalid() {
+        // Act
+        let flags = "KEQ".parse::<NotifyFlags>();
+
+        // Assert
+        assert_err!(flags);
+    }
+
+    #[tokio::test]
+    async fn publish_respects_class_and_outcome() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.psubscribe(&pubsub, "__key*".to_string());
+        let flags = NotifyFlags::KEYSPACE | NotifyFlags::STRING;
+        let ok = Frame::ok();
+
+        // Act
+        Event::set(&Bytes::from_static(b"key")).publish(
+            &pubsub,
+            NotifyFlags::KEYSPACE | NotifyFlags::LIST,
+            &ok,
+            0,
+        );
+        Event::set(&Bytes::from_static(b"key")).publish(
+            &pubsub,
+            flags,
+            &Frame::Error("ERR".into()),
+            0,
+        );
+        Event::set(&Bytes::from_static(b"key")).publish(&pubsub, flags, &ok, 0);
+        let message = subscriptions.next_message().await.unwrap();
+
+        // Assert
+        assert_eq!(
+            message,
+            Frame::Array(vec![
+                Frame::Bulk("pmessage".into()),
+                Frame::Bulk("__key*".into()),
+                Frame::Bulk("__keyspace@0__:key".into()),
+                Frame::Bulk("set".into()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,136 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("no digits to parse")]
+    Empty,
+    #[error("invalid digit")]
+    InvalidDigit,
+    #[error("number too large to fit in target type")]
+    Overflow,
+    #[error("sign not allowed here")]
+    UnexpectedSign,
+}
+
+/// Parses a decimal `i64` with an optional leading `+` or `-`, the format of
+/// RESP integers and lengths.
+pub fn parse_i64(bytes: &[u8]) -> Result<i64> {
+    match bytes.split_first() {
+        Some((b'-', digits)) => accumulate(digits, |acc: i64, digit| {
+            acc.checked_mul(10)?.checked_sub(i64::from(digit))
+        }),
+        Some((b'+', digits)) => parse_digits(digits),
+        _ => parse_digits(bytes),
+    }
+}
+
+/// Parses a decimal `u64` that must not carry a sign.
+pub fn parse_u64(bytes: &[u8]) -> Result<u64> {
+    accumulate(bytes, |acc: u64, digit| {
+        acc.checked_mul(10)?.checked_add(u64::from(digit))
+    })
+}
+
+fn parse_digits(digits: &[u8]) -> Result<i64> {
+    accumulate(digits, |acc: i64, digit| {
+        acc.checked_mul(10)?.checked_add(i64::from(digit))
+    })
+}
+
+/// Folds ASCII digits into an accumulator, in the direction `step` dictates,
+/// so that `i64::MIN` can be reached without overflowing on the way.
+fn accumulate<T: Default>(digits: &[u8], step: impl Fn(T, u8) -> Option<T>) -> Result<T> {
+    if digits.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    digits
+        .iter()
+        .try_fold(T::default(), |acc, byte| match byte {
+            b'0'..=b'9' => step(acc, byte - b'0').ok_or(Error::Overflow),
+            b'+' | b'-' => Err(Error::UnexpectedSign),
+            _ => Err(Error::InvalidDigit),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_int::{parse_i64, parse_u64, Error};
+
+    #[test]
+    fn parse_i64_valid() {
+        // Arrange
+        let cases: [(&[u8], i64); 5] = [
+            (b"0", 0),
+            (b"+42", 42),
+            (b"-42", -42),
+            (b"9223372036854775807", i64::MAX),
+            (b"-9223372036854775808", i64::MIN),
+        ];
+
+        for (input, expected) in cases {
+            // Act
+            let parsed = parse_i64(input);
+
+            // Assert
+            assert_eq!(parsed, Ok(expected));
+        }
+    }
+
+    #[test]
+    fn parse_i64_empty() {
+        // Act
+        let empty = parse_i64(b"");
+        let lone_sign = parse_i64(b"-");
+
+        // Assert
+        assert_eq!(empty, Err(Error::Empty));
+        assert_eq!(lone_sign, Err(Error::Empty));
+    }
+
+    #[test]
+    fn parse_i64_invalid_digit() {
+        // Act
+        let letter = parse_i64(b"12a");
+        let space = parse_i64(b" 1");
+
+        // Assert
+        assert_eq!(letter, Err(Error::InvalidDigit));
+        assert_eq!(space, Err(Error::InvalidDigit));
+    }
+
+    #[test]
+    fn parse_i64_overflow() {
+        // Act
+        let above_max = parse_i64(b"9223372036854775808");
+        let below_min = parse_i64(b"-9223372036854775809");
+
+        // Assert
+        assert_eq!(above_max, Err(Error::Overflow));
+        assert_eq!(below_min, Err(Error::Overflow));
+    }
+
+    #[test]
+    fn parse_i64_unexpected_sign() {
+        // Act
+        let double_sign = parse_i64(b"--1");
+        let inner_sign = parse_i64(b"1-2");
+
+        // Assert
+        assert_eq!(double_sign, Err(Error::UnexpectedSign));
+        assert_eq!(inner_sign, Err(Error::UnexpectedSign));
+    }
+
+    #[test]
+    fn parse_u64_rejects_sign() {
+        // Act
+        let signed = parse_u64(b"+1");
+        let max = parse_u64(b"18446744073709551615");
+        let overflow = parse_u64(b"18446744073709551616");
+
+        // Assert
+        assert_eq!(signed, Err(Error::UnexpectedSign));
+        assert_eq!(max, Ok(u64::MAX));
+        assert_eq!(overflow, Err(Error::Overflow));
+    }
+}
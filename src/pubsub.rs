@@ -0,0 +1,263 @@
+use crate::frame::Frame;
+use crate::glob;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt, StreamMap};
+
+/// Messages a subscriber may fall behind by before it is dropped.
+pub const CHANNEL_CAPACITY: usize = 1024;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("ERR output buffer limit exceeded")]
+    Lagged(u64),
+}
+
+/// Server-wide registry of channel and pattern subscribers.
+#[derive(Clone, Default)]
+pub struct PubSub {
+    inner: Arc<Mutex<Registry>>,
+}
+
+#[derive(Default)]
+struct Registry {
+    channels: HashMap<String, broadcast::Sender<Bytes>>,
+    patterns: HashMap<String, broadcast::Sender<(String, Bytes)>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, channel: &str) -> broadcast::Receiver<Bytes> {
+        let mut registry = self.inner.lock().unwrap();
+        match registry.channels.get(channel) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+                registry.channels.insert(channel.to_string(), sender);
+                receiver
+            }
+        }
+    }
+
+    pub fn psubscribe(&self, pattern: &str) -> broadcast::Receiver<(String, Bytes)> {
+        let mut registry = self.inner.lock().unwrap();
+        match registry.patterns.get(pattern) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+                registry.patterns.insert(pattern.to_string(), sender);
+                receiver
+            }
+        }
+    }
+
+    /// Delivers `message` to exact subscribers of `channel` and to every
+    /// pattern matching it, returning how many receivers it reached. Channels
+    /// and patterns nobody listens to anymore are dropped along the way.
+    pub fn publish(&self, channel: &str, message: Bytes) -> usize {
+        let mut registry = self.inner.lock().unwrap();
+        let mut receivers = 0;
+
+        if let Some(sender) = registry.channels.get(channel) {
+            match sender.send(message.clone()) {
+                Ok(count) => receivers += count,
+                Err(_) => {
+                    registry.channels.remove(channel);
+                }
+            }
+        }
+
+        registry.patterns.retain(|pattern, sender| {
+            if !glob::matches(pattern.as_bytes(), channel.as_bytes()) {
+                return sender.receiver_count() > 0;
+            }
+            match sender.send((channel.to_string(), message.clone())) {
+                Ok(count) => {
+                    receivers += count;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+
+        receivers
+    }
+}
+
+type Messages = Pin<Box<dyn Stream<Item = Result<Frame>> + Send>>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Target {
+    Channel(String),
+    Pattern(String),
+}
+
+/// The channels and patterns a single connection is subscribed to, merged into
+/// one stream of `message`/`pmessage` frames.
+#[derive(Default)]
+pub struct Subscriptions {
+    streams: StreamMap<Target, Messages>,
+}
+
+impl Subscriptions {
+    pub fn count(&self) -> usize {
+        self.streams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    pub fn channels(&self) -> Vec<String> {
+        self.targets(|target| match target {
+            Target::Channel(channel) => Some(channel),
+            Target::Pattern(_) => None,
+        })
+    }
+
+    pub fn patterns(&self) -> Vec<String> {
+        self.targets(|target| match target {
+            Target::Pattern(pattern) => Some(pattern),
+            Target::Channel(_) => None,
+        })
+    }
+
+    pub fn subscribe(&mut self, pubsub: &PubSub, channel: String) {
+        let target = Target::Channel(channel.clone());
+        if self.streams.contains_key(&target) {
+            return;
+        }
+
+        let messages = BroadcastStream::new(pubsub.subscribe(&channel)).map(move |message| {
+            let message = message.map_err(lagged)?;
+            Ok(Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"message")),
+                Frame::Bulk(Bytes::from(channel.clone())),
+                Frame::Bulk(message),
+            ]))
+        });
+        self.streams.insert(target, Box::pin(messages));
+    }
+
+    pub fn psubscribe(&mut self, pubsub: &PubSub, pattern: String) {
+        let target = Target::Pattern(pattern.clone());
+        if self.streams.contains_key(&target) {
+            return;
+        }
+
+        let messages = BroadcastStream::new(pubsub.psubscribe(&pattern)).map(move |message| {
+            let (channel, message) = message.map_err(lagged)?;
+            Ok(Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"pmessage")),
+                Frame::Bulk(Bytes::from(pattern.clone())),
+                Frame::Bulk(Bytes::from(channel)),
+                Frame::Bulk(message),
+            ]))
+        });
+        self.streams.insert(target, Box::pin(messages));
+    }
+
+    pub fn unsubscribe(&mut self, channel: &str) {
+        self.streams.remove(&Target::Channel(channel.to_string()));
+    }
+
+    pub fn punsubscribe(&mut self, pattern: &str) {
+        self.streams.remove(&Target::Pattern(pattern.to_string()));
+    }
+
+    /// Waits for the next message on any subscription. Never resolves while
+    /// there are no subscriptions, so it can sit in a `select!`. Fails once the
+    /// connection has fallen more than `CHANNEL_CAPACITY` messages behind.
+    pub async fn next_message(&mut self) -> Result<Frame> {
+        match self.streams.next().await {
+            Some((_, frame)) => frame,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn targets(&self, name: impl Fn(&Target) -> Option<&String>) -> Vec<String> {
+        self.streams.keys().filter_map(name).cloned().collect()
+    }
+}
+
+fn lagged(err: BroadcastStreamRecvError) -> Error {
+    let BroadcastStreamRecvError::Lagged(skipped) = err;
+    Error::Lagged(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Frame;
+    use crate::pubsub::{Error, PubSub, Subscriptions, CHANNEL_CAPACITY};
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn publish_counts_exact_and_pattern_subscribers() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.subscribe(&pubsub, "news.tech".to_string());
+        subscriptions.psubscribe(&pubsub, "news.*".to_string());
+
+        // Act
+        let receivers = pubsub.publish("news.tech", Bytes::from_static(b"hi"));
+        let first = subscriptions.next_message().await.unwrap();
+        let second = subscriptions.next_message().await.unwrap();
+
+        // Assert
+        assert_eq!(receivers, 2);
+        let mut kinds = [first, second].map(|frame| match frame {
+            Frame::Array(parts) => parts[0].clone(),
+            frame => panic!("unexpected frame {frame:?}"),
+        });
+        kinds.sort_by_key(|kind| format!("{kind:?}"));
+        assert_eq!(
+            kinds,
+            [
+                Frame::Bulk(Bytes::from_static(b"message")),
+                Frame::Bulk(Bytes::from_static(b"pmessage")),
+            ]
+        );
+    }
+
+    #[test]
+    fn publish_without_match_reaches_nobody() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.psubscribe(&pubsub, "news.*".to_string());
+
+        // Act
+        let receivers = pubsub.publish("sports", Bytes::from_static(b"hi"));
+
+        // Assert
+        assert_eq!(receivers, 0);
+    }
+
+    #[tokio::test]
+    async fn next_message_after_falling_behind_lagged() {
+        // Arrange
+        let pubsub = PubSub::new();
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.subscribe(&pubsub, "firehose".to_string());
+
+        // Act
+        for _ in 0..CHANNEL_CAPACITY + 1 {
+            pubsub.publish("firehose", Bytes::from_static(b"hi"));
+        }
+        let message = subscriptions.next_message().await;
+
+        // Assert
+        assert_eq!(message, Err(Error::Lagged(1)));
+    }
+}
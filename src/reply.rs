@@ -0,0 +1,80 @@
+use crate::frame::Frame;
+use bytes::Bytes;
+
+/// `+OK`, sharing one static buffer across every call.
+pub const fn ok() -> Frame {
+    Frame::ok()
+}
+
+/// `+QUEUED`, the reply to a command queued inside MULTI.
+pub const fn queued() -> Frame {
+    Frame::Simple(Bytes::from_static(b"QUEUED"))
+}
+
+pub const fn int(value: i64) -> Frame {
+    Frame::Integer(value)
+}
+
+pub fn bulk(content: impl Into<Bytes>) -> Frame {
+    Frame::Bulk(content.into())
+}
+
+/// The null reply, a null bulk under RESP2.
+pub const fn nil() -> Frame {
+    Frame::Null
+}
+
+/// An error reply. `message` starts with its code, as in `ERR ...`.
+pub fn error(message: impl Into<String>) -> Frame {
+    Frame::Error(message.into())
+}
+
+pub fn array(frames: Vec<Frame>) -> Frame {
+    Frame::Array(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Frame;
+    use crate::reply;
+    use bytes::Bytes;
+
+    #[test]
+    fn helpers_build_expected_frames() {
+        // Act
+        let frames = [
+            reply::ok(),
+            reply::queued(),
+            reply::int(-3),
+            reply::bulk("value"),
+            reply::nil(),
+            reply::error("ERR oops"),
+            reply::array(vec![reply::int(1)]),
+        ];
+
+        // Assert
+        assert_eq!(
+            frames,
+            [
+                Frame::Simple(Bytes::from_static(b"OK")),
+                Frame::Simple(Bytes::from_static(b"QUEUED")),
+                Frame::Integer(-3),
+                Frame::Bulk(Bytes::from_static(b"value")),
+                Frame::Null,
+                Frame::Error("ERR oops".to_string()),
+                Frame::Array(vec![Frame::Integer(1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn ok_reuses_static_bytes() {
+        // Act
+        let (Frame::Simple(first), Frame::Simple(second)) = (reply::ok(), reply::ok()) else {
+            panic!("expected simple strings");
+        };
+
+        // Assert
+        assert_eq!(first.as_ptr(), second.as_ptr());
+    }
+}
@@ -0,0 +1,813 @@
+use crate::clients::{Clients, Registration};
+use crate::cmd::{parse_args, Command, ParseError, Registry, Sort, Unknown};
+use crate::config::ServerConfig;
+use crate::connection::{self, Connection};
+use crate::db::{self, ShardedDb};
+use crate::frame::{self, Frame, Newlines, Protocol};
+use crate::health;
+use crate::latency::LatencyMonitor;
+use crate::monitor::{Feed, Monitoring};
+use crate::notify::Event;
+use crate::pubsub::{self, PubSub, Subscriptions};
+use crate::reply;
+use crate::stats::{CommandStats, Slot};
+use crate::transaction::Transaction;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::{task, time};
+use tracing::{debug, error, warn};
+
+/// Accepts connections until `shutdown` completes.
+pub async fn run(listener: TcpListener, mut config: ServerConfig, shutdown: impl Future) {
+    let started_at = time::Instant::now();
+    let health_addr = config.health_addr;
+    let seed = config.hash_seed.unwrap_or_else(rand::random);
+    let shared = Shared {
+        commands: Arc::new(std::mem::take(&mut config.commands)),
+        dbs: {
+            let template = ShardedDb::new_seeded(db::DEFAULT_SHARDS, seed);
+            (0..config.databases.max(1))
+                .map(|_| template.sibling())
+                .collect()
+        },
+        config: Arc::new(RwLock::new(config)),
+        pubsub: PubSub::new(),
+        latency: LatencyMonitor::new(),
+        command_stats: CommandStats::new(),
+        feed: Feed::new(),
+        clients: Clients::new(),
+        exec_lock: Arc::new(tokio::sync::RwLock::new(())),
+    };
+
+    tokio::select! {
+        _ = accept_loop(listener, shared.clone()) => {}
+        _ = expire_cycle(shared.dbs.clone()) => {}
+        _ = serve_health(health_addr, shared.dbs[0].clone(), started_at) => {}
+        _ = reap_idle(shared.clients, shared.config) => {}
+        _ = shutdown => debug!("shutting down"),
+    }
+}
+
+/// Runs the health check endpoint when there is an address for it. Failing to
+/// bind it is logged rather than taking the server down.
+async fn serve_health(addr: Option<SocketAddr>, db: ShardedDb, started_at: time::Instant) {
+    let Some(addr) = addr else {
+        return std::future::pending().await;
+    };
+    match TcpListener::bind(addr).await {
+        Ok(listener) => health::serve(listener, db, started_at).await,
+        Err(err) => {
+            error!(cause = %err, %addr, "failed to bind health check");
+            std::future::pending().await
+        }
+    }
+}
+
+/// How often keys past their deadline are swept, so that keys nobody reads
+/// again still get freed.
+const EXPIRE_CYCLE_PERIOD: Duration = Duration::from_millis(100);
+
+async fn expire_cycle(dbs: Arc<[ShardedDb]>) {
+    let mut interval = time::interval(EXPIRE_CYCLE_PERIOD);
+    loop {
+        interval.tick().await;
+        let purged: usize = dbs.iter().map(ShardedDb::purge_expired).sum();
+        if purged > 0 {
+            debug!(purged, "expired keys swept");
+        }
+    }
+}
+
+/// Sorts over fewer elements than this run inline: handing them to the
+/// blocking pool would cost more than it saves.
+const OFFLOAD_SORT_LEN: usize = 1024;
+
+/// How often connections are checked against the `timeout` setting.
+const REAP_IDLE_PERIOD: Duration = Duration::from_millis(100);
+
+async fn reap_idle(clients: Clients, config: Arc<RwLock<ServerConfig>>) {
+    let mut interval = time::interval(REAP_IDLE_PERIOD);
+    loop {
+        interval.tick().await;
+        let timeout = config.read().unwrap().timeout;
+        if timeout.is_zero() {
+            continue;
+        }
+        let reaped = clients.kill_idle(timeout);
+        if reaped > 0 {
+            debug!(reaped, "idle connections closed");
+        }
+    }
+}
+
+/// State every connection shares.
+#[derive(Clone)]
+struct Shared {
+    /// Every database, SELECT choosing among them by index.
+    dbs: Arc<[ShardedDb]>,
+    config: Arc<RwLock<ServerConfig>>,
+    /// Custom commands, out of the config so dispatch needs no lock.
+    commands: Arc<Registry>,
+    pubsub: PubSub,
+    latency: LatencyMonitor,
+    command_stats: CommandStats,
+    feed: Feed,
+    clients: Clients,
+    /// Taken shared by every write and exclusively by EXEC, so no other
+    /// connection's write lands between a transaction's WATCH check and its
+    /// commands.
+    exec_lock: Arc<tokio::sync::RwLock<()>>,
+}
+
+async fn accept_loop(listener: TcpListener, shared: Shared) {
+    loop {
+        let (socket, addr) = match accept(|| listener.accept()).await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                error!(cause = %err, "failed to accept connection, shutting down");
+                return;
+            }
+        };
+
+        let socket_options = shared.config.read().unwrap().socket.clone();
+        if let Err(err) = socket_options.apply(&socket) {
+            warn!(cause = %err, "failed to apply socket options");
+        }
+
+        let client = shared.clients.register(addr);
+        let shared = shared.clone();
+
+        tokio::spawn(async move {
+            match process(socket, shared, client).await {
+                Ok(()) => {}
+                Err(connection::Error::ConnectionReset) => {
+                    debug!("client disconnected mid-frame")
+                }
+                Err(err) => error!(cause = %err, "connection error"),
+            }
+        });
+    }
+}
+
+/// Retries `accept` through errors that tend to clear up on their own, such as
+/// running out of file descriptors, backing off between attempts. Any other
+/// error means the listener itself is broken and is returned.
+async fn accept<T, F>(mut accept: impl FnMut() -> F) -> io::Result<T>
+where
+    F: Future<Output = io::Result<T>>,
+{
+    const MAX_BACKOFF: Duration = Duration::from_secs(1);
+    let mut backoff = Duration::from_millis(10);
+
+    loop {
+        match accept().await {
+            Ok(accepted) => return Ok(accepted),
+            Err(err) if is_transient(&err) => {
+                warn!(cause = %err, ?backoff, "failed to accept connection, retrying");
+                time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn is_transient(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    if let Some(libc::EMFILE | libc::ENFILE | libc::ENOBUFS | libc::ENOMEM) = err.raw_os_error() {
+        return true;
+    }
+
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::OutOfMemory
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+    )
+}
+
+/// The refusal for a command a RESP2 connection can't run while subscribed.
+fn not_in_subscriber_mode(name: &str) -> Frame {
+    reply::error(format!(
+        "ERR Can't execute '{name}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / \
+         RESET are allowed in this context"
+    ))
+}
+
+async fn process(
+    socket: TcpStream,
+    shared: Shared,
+    client: Registration,
+) -> connection::Result<()> {
+    let addr = socket.peer_addr()?;
+    let mut connection = Connection::new(socket);
+    let burst = {
+        let config = shared.config.read().unwrap();
+        if config.lenient_newlines {
+            connection.set_newlines(Newlines::Lenient);
+        }
+        Burst::new(config.max_pipeline_burst)
+    };
+
+    let Shared {
+        dbs,
+        config,
+        commands,
+        pubsub,
+        latency,
+        command_stats,
+        feed,
+        clients,
+        exec_lock,
+    } = shared;
+    let mut handler = Handler {
+        client,
+        clients,
+        addr,
+        connection,
+        db: dbs[0].clone(),
+        dbs,
+        db_index: 0,
+        config,
+        commands,
+        pubsub,
+        latency,
+        command_stats,
+        feed,
+        subscriptions: Subscriptions::default(),
+        monitoring: Monitoring::default(),
+        transaction: Transaction::default(),
+        burst,
+        exec_lock,
+    };
+
+    handler.run().await
+}
+
+/// Per-connection state and the dispatch of its commands.
+struct Handler {
+    client: Registration,
+    clients: Clients,
+    addr: SocketAddr,
+    connection: Connection,
+    /// The selected database, a handle of the connection's own.
+    db: ShardedDb,
+    dbs: Arc<[ShardedDb]>,
+    db_index: usize,
+    config: Arc<RwLock<ServerConfig>>,
+    commands: Arc<Registry>,
+    pubsub: PubSub,
+    latency: LatencyMonitor,
+    command_stats: CommandStats,
+    feed: Feed,
+    subscriptions: Subscriptions,
+    monitoring: Monitoring,
+    transaction: Transaction,
+    burst: Burst,
+    exec_lock: Arc<tokio::sync::RwLock<()>>,
+}
+
+/// Counts the pipelined frames a connection runs back to back, so that one
+/// client filling its buffer can't keep the others on its worker waiting.
+struct Burst {
+    limit: usize,
+    run: usize,
+}
+
+impl Burst {
+    fn new(limit: usize) -> Self {
+        Self { limit, run: 0 }
+    }
+
+    /// Whether to yield after a frame, given whether more input is already
+    /// buffered behind it. A run ends once the buffer drains.
+    fn should_yield(&mut self, more_buffered: bool) -> bool {
+        if !more_buffered {
+            self.run = 0;
+            return false;
+        }
+        self.run += 1;
+        if self.limit == 0 || self.run < self.limit {
+            return false;
+        }
+        self.run = 0;
+        true
+    }
+}
+
+impl Handler {
+    async fn run(&mut self) -> connection::Result<()> {
+        loop {
+            let frame = tokio::select! {
+                frame = self.connection.read_frame() => match frame {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => return Ok(()),
+                    Err(connection::Error::Frame(err)) => return self.protocol_error(err).await,
+                    Err(err) => return Err(err),
+                },
+                message = self.subscriptions.next_message() => {
+                    if !self.deliver(message).await? {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                line = self.monitoring.next_line() => {
+                    self.connection.write_frame(&Frame::Simple(line.into())).await?;
+                    continue;
+                }
+                _ = self.client.killed() => return Ok(()),
+            };
+
+            debug!(frame = %frame.for_log());
+            if !self.handle(frame).await? {
+                return Ok(());
+            }
+            if self
+                .burst
+                .should_yield(self.connection.has_buffered_input())
+            {
+                task::yield_now().await;
+            }
+        }
+    }
+
+    /// Answers a malformed frame before hanging up, since the rest of the
+    /// stream can't be trusted to line up with frame boundaries anymore.
+    async fn protocol_error(&mut self, err: frame::Error) -> connection::Result<()> {
+        warn!(cause = %err, "protocol error");
+        self.command_stats.record_protocol_error();
+
+        let response = if self.config.read().unwrap().verbose_protocol_errors {
+            let detail = err.to_string();
+            let detail = detail.trim_start_matches("protocol error; ");
+            format!("ERR Protocol error: {detail}")
+        } else {
+            "ERR Protocol error".to_string()
+        };
+        self.connection.write_frame(&reply::error(response)).await
+    }
+
+    /// Writes a pub/sub message unless the subscriber has fallen behind or
+    /// stopped reading, in which case it is told so (best effort) and `false`
+    /// is returned to close the connection.
+    async fn deliver(&mut self, message: pubsub::Result<Frame>) -> connection::Result<bool> {
+        let timeout = self.config.read().unwrap().pubsub_write_timeout;
+        let frame = match message {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!(cause = %err, "dropping slow subscriber");
+                let response = reply::error(err.to_string());
+                let _ = time::timeout(timeout, self.connection.write_frame(&response)).await;
+                return Ok(false);
+            }
+        };
+
+        match time::timeout(timeout, self.connection.write_frame(&frame)).await {
+            Ok(written) => written.map(|()| true),
+            Err(_) => {
+                warn!("dropping subscriber that stopped reading");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Runs one frame, returning `false` once the connection should close.
+    async fn handle(&mut self, frame: Frame) -> connection::Result<bool> {
+        self.feed.publish(&frame, self.addr);
+        if let Some(response) = self.refuse_disabled(&frame) {
+            self.abort_transaction();
+            return self.connection.write_frame(&response).await.map(|()| true);
+        }
+        if let Some(response) = self.handle_custom(&frame).await {
+            return self.connection.write_frame(&response).await.map(|()| true);
+        }
+        let command = match Command::from_frame(frame) {
+            Ok(command) => command,
+            Err(err) => {
+                self.abort_transaction();
+                let response = match err {
+                    ParseError::Command(err) => Frame::from(err),
+                    err => reply::error(format!("ERR {err}")),
+                };
+                return self.connection.write_frame(&response).await.map(|()| true);
+            }
+        };
+        self.client.record(command.get_name());
+
+        if self.in_subscriber_mode() && !Self::allowed_in_subscriber_mode(&command) {
+            let response = not_in_subscriber_mode(command.get_name());
+            return self.connection.write_frame(&response).await.map(|()| true);
+        }
+
+        let response = match command {
+            Command::Quit(cmd) => {
+                // anything pipelined after QUIT is dropped with the connection
+                self.connection.write_frame(&cmd.apply()).await?;
+                return Ok(false);
+            }
+            Command::Unknown(cmd) => {
+                self.abort_transaction();
+                cmd.apply()
+            }
+            Command::Exec(_) => {
+                let exec_lock = self.exec_lock.clone();
+                let _exclusive = exec_lock.write().await;
+                self.execute_sampled(command)
+            }
+            Command::Discard(_) | Command::Multi(_) | Command::Watch(_) => {
+                self.execute_sampled(command)
+            }
+            Command::Ping(cmd) if self.in_subscriber_mode() => cmd.apply_subscribed(),
+            command if self.transaction.is_active() => {
+                self.transaction.queue(command);
+                reply::queued()
+            }
+            Command::Monitor(cmd) => {
+                let enabled = self.config.read().unwrap().enable_monitor;
+                let response = cmd.apply(&self.feed, &mut self.monitoring, enabled);
+                self.client.set_monitor(self.monitoring.is_active());
+                response
+            }
+            Command::Subscribe(cmd) => {
+                let replies = cmd.apply(&self.pubsub, &mut self.subscriptions);
+                self.client.set_pubsub(!self.subscriptions.is_empty());
+                return self.connection.write_frames(&replies).await.map(|()| true);
+            }
+            Command::Unsubscribe(cmd) => {
+                let replies = cmd.apply(&mut self.subscriptions);
+                self.client.set_pubsub(!self.subscriptions.is_empty());
+                return self.connection.write_frames(&replies).await.map(|()| true);
+            }
+            Command::Sort(cmd) => self.sort_offloaded(cmd).await,
+            command if command.is_write() => {
+                let exec_lock = self.exec_lock.clone();
+                let _shared = exec_lock.read().await;
+                self.execute_sampled(command)
+            }
+            command => self.execute_sampled(command),
+        };
+
+        self.connection.write_frame(&response).await.map(|()| true)
+    }
+
+    /// Runs a command the embedding application registered, if `frame` names
+    /// one. EXEC runs its queue synchronously, so a custom command can't be
+    /// queued: inside MULTI it is refused and the transaction aborted.
+    async fn handle_custom(&mut self, frame: &Frame) -> Option<Frame> {
+        if self.commands.is_empty() {
+            return None;
+        }
+        let Frame::Array(parts) = frame else {
+            return None;
+        };
+        let name = match parts.first()? {
+            Frame::Bulk(name) | Frame::Simple(name) => String::from_utf8_lossy(name).to_lowercase(),
+            _ => return None,
+        };
+        let handler = self.commands.get(&name)?;
+        self.client.record(&name);
+
+        if self.transaction.is_active() {
+            self.abort_transaction();
+            return Some(reply::error(format!(
+                "ERR '{name}' is a custom command and can't be used inside MULTI"
+            )));
+        }
+        if self.in_subscriber_mode() {
+            return Some(not_in_subscriber_mode(&name));
+        }
+
+        let args = match parse_args(frame.clone()) {
+            Ok(args) => args,
+            Err(err) => return Some(reply::error(format!("ERR {err}"))),
+        };
+        if handler.is_write() && self.config.read().unwrap().read_only {
+            return Some(reply::error(
+                "READONLY You can't write against a read only replica.",
+            ));
+        }
+        if let Err(err) = self.enforce_maxmemory(handler.may_grow()) {
+            return Some(reply::error(err.to_string()));
+        }
+
+        let exec_lock = self.exec_lock.clone();
+        let _shared = if handler.is_write() {
+            Some(exec_lock.read().await)
+        } else {
+            None
+        };
+        let start = std::time::Instant::now();
+        let response = handler.execute(args, &mut self.db).await;
+        self.record_sample(None, start.elapsed());
+        Some(response)
+    }
+
+    /// Under RESP2 a subscribed connection's replies are interleaved with
+    /// messages, so it is limited to managing its subscriptions. RESP3 tells
+    /// messages apart by their push type and lifts the limit.
+    fn in_subscriber_mode(&self) -> bool {
+        !self.subscriptions.is_empty() && self.connection.protocol() == Protocol::Resp2
+    }
+
+    fn allowed_in_subscriber_mode(command: &Command) -> bool {
+        matches!(
+            command,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::Ping(_)
+                | Command::Quit(_)
+                | Command::Unknown(_)
+        )
+    }
+
+    /// The error for a command the config disables, checked before parsing
+    /// so that a hidden command can't be told apart from an unknown one by
+    /// its argument errors.
+    fn refuse_disabled(&self, frame: &Frame) -> Option<Frame> {
+        let Frame::Array(parts) = frame else {
+            return None;
+        };
+        let name = match parts.first()? {
+            Frame::Bulk(name) | Frame::Simple(name) => String::from_utf8_lossy(name).into_owned(),
+            _ => return None,
+        };
+
+        let config = self.config.read().unwrap();
+        if !config.is_disabled(&name) {
+            return None;
+        }
+        if !config.hide_disabled_commands {
+            return Some(reply::error(format!("ERR command '{name}' is disabled")));
+        }
+        let args = parts[1..]
+            .iter()
+            .filter_map(|part| match part {
+                Frame::Bulk(arg) => Some(arg.clone()),
+                _ => None,
+            })
+            .collect();
+        Some(Unknown::new(name, args).apply())
+    }
+
+    /// `execute`, recording how long the command took for LATENCY and INFO
+    /// commandstats.
+    fn execute_sampled(&mut self, command: Command) -> Frame {
+        let slot = self.command_stats.slot(command.get_name());
+        let start = std::time::Instant::now();
+        let response = self.execute(command);
+        self.record_sample(slot, start.elapsed());
+        response
+    }
+
+    fn record_sample(&mut self, slot: Option<Slot>, elapsed: Duration) {
+        self.latency.record(elapsed);
+        if let Some(slot) = slot {
+            self.command_stats.record(slot, elapsed);
+        }
+    }
+
+    /// SORT outside a transaction: the elements are copied out under the
+    /// shard lock and a large sort runs on the blocking pool, so it doesn't
+    /// stall the other connections sharing this worker.
+    async fn sort_offloaded(&mut self, cmd: Sort) -> Frame {
+        let slot = self.command_stats.slot("sort");
+        let start = std::time::Instant::now();
+        let response = match cmd.elements(&self.db) {
+            Ok(elements) if elements.len() < OFFLOAD_SORT_LEN => cmd.sort(elements),
+            Ok(elements) => task::spawn_blocking(move || cmd.sort(elements))
+                .await
+                .unwrap_or_else(|err| reply::error(format!("ERR {err}"))),
+            Err(response) => response,
+        };
+        self.record_sample(slot, start.elapsed());
+        response
+    }
+
+    /// Runs a command that answers with a single frame, publishing its
+    /// keyspace notifications if it changed anything.
+    fn execute(&mut self, command: Command) -> Frame {
+        if command.is_write() && self.config.read().unwrap().read_only {
+            return reply::error("READONLY You can't write against a read only replica.");
+        }
+        if let Err(err) = self.enforce_maxmemory(command.may_grow()) {
+            return reply::error(err.to_string());
+        }
+
+        let mut events: Vec<_> = Event::for_command(&command).into_iter().collect();
+        let (max_value_size, default_ttl, lazyfree_threshold) = {
+            let config = self.config.read().unwrap();
+            (
+                config.value_size_limit(),
+                config.default_ttl,
+                config.lazyfree_threshold,
+            )
+        };
+        let db = &mut self.db;
+
+        let response = match command {
+            Command::Append(cmd) => cmd.apply(db, max_value_size),
+            Command::Cas(cmd) => cmd.apply(db, max_value_size),
+            Command::Client(cmd) => cmd.apply(&self.client, &self.clients, db),
+            Command::Config(cmd) => cmd.apply(&self.config, &self.command_stats),
+            Command::Debug(cmd) => cmd.apply(db),
+            Command::Del(cmd) => cmd.apply(db, lazyfree_threshold, |key| {
+                events.push(Event::deleted(key))
+            }),
+            Command::Discard(cmd) => cmd.apply(&mut self.transaction),
+            Command::Dump(cmd) => cmd.apply(db),
+            Command::Exec(_) => self.exec(),
+            Command::Expire(cmd) => cmd.apply(db),
+            Command::Flush(cmd) => cmd.apply(db, &self.dbs, lazyfree_threshold),
+            Command::Get(cmd) => cmd.apply(db),
+            Command::GetEx(cmd) => cmd.apply(db),
+            Command::Hello(cmd) => {
+                let mut protocol = self.connection.protocol();
+                let response = cmd.apply(&mut protocol);
+                self.connection.set_protocol(protocol);
+                response
+            }
+            Command::HExpire(cmd) => cmd.apply(db),
+            Command::HGet(cmd) => cmd.apply(db),
+            Command::HGetAll(cmd) => cmd.apply(db),
+            Command::HIncrByFloat(cmd) => cmd.apply(db),
+            Command::HPersist(cmd) => cmd.apply(db),
+            Command::HRandField(cmd) => cmd.apply(db),
+            Command::HSet(cmd) => cmd.apply(db),
+            Command::HTtl(cmd) => cmd.apply(db),
+            Command::IncrByFloat(cmd) => cmd.apply(db),
+            Command::Commands(cmd) => cmd.apply(),
+            Command::Info(cmd) => {
+                cmd.apply(&self.dbs, &self.config.read().unwrap(), &self.command_stats)
+            }
+            Command::Latency(cmd) => cmd.apply(&self.latency),
+            Command::LInsert(cmd) => cmd.apply(db),
+            Command::LMove(cmd) => cmd.apply(db),
+            Command::Lolwut(cmd) => cmd.apply(),
+            Command::LPos(cmd) => cmd.apply(db),
+            Command::LRem(cmd) => cmd.apply(db),
+            Command::LSet(cmd) => cmd.apply(db),
+            Command::Memory(cmd) => cmd.apply(db),
+            Command::MDump(cmd) => cmd.apply(db),
+            Command::MGet(cmd) => cmd.apply(db),
+            Command::MLoad(cmd) => cmd.apply(db, max_value_size),
+            Command::Multi(cmd) => cmd.apply(&mut self.transaction),
+            Command::Object(cmd) => {
+                let policy = self.config.read().unwrap().maxmemory_policy;
+                cmd.apply(db, policy)
+            }
+            Command::Ping(cmd) => cmd.apply(),
+            Command::Pop(cmd) => cmd.apply(db),
+            Command::Publish(cmd) => cmd.apply(&self.pubsub),
+            Command::Push(cmd) => cmd.apply(db),
+            Command::SAdd(cmd) => cmd.apply(db),
+            Command::Scan(cmd) => cmd.apply(db),
+            Command::Select(cmd) => cmd.apply(&self.dbs, db, &mut self.db_index),
+            Command::Set(cmd) => cmd.apply(db, max_value_size, default_ttl, |key| {
+                events.push(Event::set(key))
+            }),
+            Command::SetOperation(cmd) => cmd.apply(db),
+            Command::SetRange(cmd) => cmd.apply(db, max_value_size),
+            Command::SInterCard(cmd) => cmd.apply(db),
+            Command::SIsMember(cmd) => cmd.apply(db),
+            Command::SMembers(cmd) => cmd.apply(db),
+            Command::SMIsMember(cmd) => cmd.apply(db),
+            Command::Sort(cmd) => cmd.apply(db),
+            Command::SRandMember(cmd) => cmd.apply(db),
+            Command::SRem(cmd) => cmd.apply(db),
+            Command::Monitor(_)
+            | Command::Quit(_)
+            | Command::Subscribe(_)
+            | Command::Unsubscribe(_) => {
+                reply::error("ERR Command not allowed inside a transaction")
+            }
+            Command::SwapDb(cmd) => cmd.apply(&self.dbs),
+            Command::Time(cmd) => cmd.apply(),
+            Command::Ttl(cmd) => cmd.apply(db),
+            Command::Unknown(cmd) => cmd.apply(),
+            Command::Unwatch(cmd) => cmd.apply(&mut self.transaction),
+            Command::WaitAof(cmd) => cmd.apply(),
+            Command::Watch(cmd) => cmd.apply(&self.dbs, self.db_index, &mut self.transaction),
+            Command::XAdd(cmd) => cmd.apply(db),
+            Command::XLen(cmd) => cmd.apply(db),
+            Command::XRange(cmd) => cmd.apply(db),
+        };
+
+        if !events.is_empty() {
+            let flags = self.config.read().unwrap().notify_keyspace_events;
+            for event in events {
+                event.publish(&self.pubsub, flags, &response, self.db_index);
+            }
+        }
+
+        response
+    }
+
+    /// Evicts down to `maxmemory` before running a command. Failing to make
+    /// room only refuses commands that could grow the dataset further.
+    fn enforce_maxmemory(&self, may_grow: bool) -> db::Result<()> {
+        let (maxmemory, policy) = {
+            let config = self.config.read().unwrap();
+            (config.maxmemory, config.maxmemory_policy)
+        };
+        if maxmemory == 0 {
+            return Ok(());
+        }
+
+        let limit = usize::try_from(maxmemory).unwrap_or(usize::MAX);
+        match db::evict(&self.dbs, limit, policy) {
+            Err(err) if may_grow => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// A null reply means a watched key changed and nothing ran.
+    fn exec(&mut self) -> Frame {
+        match self.transaction.exec(&self.dbs) {
+            Ok(Some(commands)) => reply::array(
+                commands
+                    .into_iter()
+                    .map(|command| self.execute(command))
+                    .collect(),
+            ),
+            Ok(None) => reply::nil(),
+            Err(err) => reply::error(err.to_string()),
+        }
+    }
+
+    fn abort_transaction(&mut self) {
+        if self.transaction.is_active() {
+            self.transaction.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::{accept, Burst};
+    use std::io;
+
+    #[test]
+    fn burst_yields_every_limit_pipelined_frames() {
+        // Arrange
+        let mut burst = Burst::new(3);
+        let mut unlimited = Burst::new(0);
+
+        // Act
+        let pipelined: Vec<_> = (0..7).map(|_| burst.should_yield(true)).collect();
+        let drained = burst.should_yield(false);
+        let after_drain: Vec<_> = (0..3).map(|_| burst.should_yield(true)).collect();
+        let never = (0..1000).any(|_| unlimited.should_yield(true));
+
+        // Assert
+        assert_eq!(pipelined, [false, false, true, false, false, true, false]);
+        assert!(!drained);
+        assert_eq!(after_drain, [false, false, true]);
+        assert!(!never);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(start_paused = true)]
+    async fn accept_retries_transient_errors() {
+        // Arrange
+        let mut attempts = 0;
+
+        // Act
+        let accepted = accept(|| {
+            attempts += 1;
+            let result = match attempts {
+                1 | 2 => Err(io::Error::from_raw_os_error(libc::EMFILE)),
+                _ => Ok("socket"),
+            };
+            async move { result }
+        })
+        .await;
+
+        // Assert
+        assert_eq!(accepted.unwrap(), "socket");
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accept_fatal_error_returned() {
+        // Arrange
+        let mut attempts = 0;
+
+        // Act
+        let accepted = accept(|| {
+            attempts += 1;
+            async { Err::<(), _>(io::Error::from(io::ErrorKind::InvalidInput)) }
+        })
+        .await;
+
+        // Assert
+        assert_eq!(accepted.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::cmd::table::{self, SPECS};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Server-wide call counts and processing time per command, one pair of
+/// counters per entry of the command table, so recording never takes a lock.
+/// Also counts the malformed frames that made the server hang up.
+#[derive(Clone)]
+pub struct CommandStats {
+    inner: Arc<[Counters]>,
+    protocol_errors: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+struct Counters {
+    calls: AtomicU64,
+    micros: AtomicU64,
+}
+
+/// Where a command's counters live, found before it runs.
+#[derive(Clone, Copy, Debug)]
+pub struct Slot(usize);
+
+/// Totals for one command that has been called at least once.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommandStat {
+    pub name: &'static str,
+    pub calls: u64,
+    pub usec: u64,
+}
+
+impl CommandStats {
+    pub fn new() -> Self {
+        Self {
+            inner: SPECS.iter().map(|_| Counters::default()).collect(),
+            protocol_errors: Arc::default(),
+        }
+    }
+
+    /// `None` for names missing from the command table, which aren't counted.
+    pub fn slot(&self, name: &str) -> Option<Slot> {
+        table::position(name).map(Slot)
+    }
+
+    pub fn record(&self, slot: Slot, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let counters = &self.inner[slot.0];
+        counters.calls.fetch_add(1, Ordering::Relaxed);
+        counters.micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    pub fn record_protocol_error(&self) {
+        self.protocol_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn protocol_errors(&self) -> u64 {
+        self.protocol_errors.load(Ordering::Relaxed)
+    }
+
+    /// Zeroes every counter, for CONFIG RESETSTAT.
+    pub fn reset(&self) {
+        for counters in self.inner.iter() {
+            counters.calls.store(0, Ordering::Relaxed);
+            counters.micros.store(0, Ordering::Relaxed);
+        }
+        self.protocol_errors.store(0, Ordering::Relaxed);
+    }
+
+    /// Every command called since the last reset, in name order.
+    pub fn called(&self) -> Vec<CommandStat> {
+        SPECS
+            .iter()
+            .zip(self.inner.iter())
+            .map(|(spec, counters)| CommandStat {
+                name: spec.name,
+                calls: counters.calls.load(Ordering::Relaxed),
+                usec: counters.micros.load(Ordering::Relaxed),
+            })
+            .filter(|stat| stat.calls > 0)
+            .collect()
+    }
+}
+
+impl Default for CommandStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::CommandStats;
+    use claims::assert_none;
+    use std::time::Duration;
+
+    #[test]
+    fn record_counts_calls_per_command() {
+        // Arrange
+        let stats = CommandStats::new();
+        let get = stats.slot("get").unwrap();
+        let set = stats.slot("set").unwrap();
+
+        // Act
+        stats.record(get, Duration::from_micros(3));
+        stats.record(get, Duration::from_micros(4));
+        stats.record(set, Duration::from_micros(10));
+
+        // Assert
+        let called = stats.called();
+        assert_eq!(called.len(), 2);
+        assert_eq!(
+            (called[0].name, called[0].calls, called[0].usec),
+            ("get", 2, 7)
+        );
+        assert_eq!(
+            (called[1].name, called[1].calls, called[1].usec),
+            ("set", 1, 10)
+        );
+        assert_none!(stats.slot("nosuchcommand"));
+    }
+
+    #[test]
+    fn reset_zeroes_every_counter() {
+        // Arrange
+        let stats = CommandStats::new();
+        stats.record(stats.slot("get").unwrap(), Duration::from_micros(3));
+        stats.record_protocol_error();
+
+        // Act
+        stats.clone().reset();
+
+        // Assert
+        assert!(stats.called().is_empty());
+        assert_eq!(stats.protocol_errors(), 0);
+    }
+}
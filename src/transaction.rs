@@ -0,0 +1,189 @@
+use crate::cmd::Command;
+use crate::db::ShardedDb;
+use bytes::Bytes;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("ERR MULTI calls can not be nested")]
+    Nested,
+    #[error("ERR EXEC without MULTI")]
+    ExecWithoutMulti,
+    #[error("ERR DISCARD without MULTI")]
+    DiscardWithoutMulti,
+    #[error("ERR WATCH inside MULTI is not allowed")]
+    WatchInsideMulti,
+    #[error("EXECABORT Transaction discarded because of previous errors.")]
+    Aborted,
+}
+
+/// Per-connection MULTI/EXEC state: the commands queued since MULTI and the
+/// versions of the keys under WATCH.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    queued: Option<Vec<Command>>,
+    aborted: bool,
+    /// The index of the database each key was watched in, since the
+    /// connection may SELECT another one before EXEC.
+    watched: Vec<(usize, Bytes, u64)>,
+}
+
+impl Transaction {
+    pub fn is_active(&self) -> bool {
+        self.queued.is_some()
+    }
+
+    pub fn begin(&mut self) -> Result<()> {
+        if self.is_active() {
+            return Err(Error::Nested);
+        }
+
+        self.queued = Some(Vec::new());
+        Ok(())
+    }
+
+    pub fn queue(&mut self, command: Command) {
+        if let Some(queued) = &mut self.queued {
+            queued.push(command);
+        }
+    }
+
+    /// Makes the coming EXEC fail, after a command was rejected while queueing.
+    pub fn abort(&mut self) {
+        self.aborted = true;
+    }
+
+    /// Watches `keys` in the database at `db_index`.
+    pub fn watch(&mut self, dbs: &[ShardedDb], db_index: usize, keys: Vec<Bytes>) -> Result<()> {
+        if self.is_active() {
+            return Err(Error::WatchInsideMulti);
+        }
+
+        for key in keys {
+            let version = dbs[db_index].version(&key);
+            self.watched.push((db_index, key, version));
+        }
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self) {
+        self.watched.clear();
+    }
+
+    pub fn discard(&mut self) -> Result<()> {
+        if !self.is_active() {
+            return Err(Error::DiscardWithoutMulti);
+        }
+
+        *self = Self::default();
+        Ok(())
+    }
+
+    /// Ends the transaction and clears every watch. Returns the queued
+    /// commands, or `None` if a watched key changed since it was watched.
+    ///
+    /// Nothing here keeps other connections out: the caller must hold them off
+    /// from the watch check until the commands have run, as the server does
+    /// with its exec lock.
+    pub fn exec(&mut self, dbs: &[ShardedDb]) -> Result<Option<Vec<Command>>> {
+        let Some(queued) = self.queued.take() else {
+            return Err(Error::ExecWithoutMulti);
+        };
+        let transaction = std::mem::take(self);
+
+        if transaction.aborted {
+            return Err(Error::Aborted);
+        }
+        let unchanged = transaction
+            .watched
+            .iter()
+            .all(|(db_index, key, version)| dbs[*db_index].version(key) == *version);
+
+        Ok(unchanged.then_some(queued))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Command, Get};
+    use crate::db::ShardedDb;
+    use crate::transaction::{Error, Transaction};
+    use bytes::Bytes;
+    use claims::assert_ok;
+
+    #[test]
+    fn exec_checks_keys_in_the_database_they_were_watched_in() {
+        // Arrange
+        let mut dbs = [ShardedDb::new(), ShardedDb::new()];
+        let mut transaction = Transaction::default();
+        transaction
+            .watch(&dbs, 0, vec![Bytes::from_static(b"key")])
+            .unwrap();
+        transaction.begin().unwrap();
+        dbs[0].insert("key", Bytes::from_static(b"changed"));
+
+        // Act
+        let result = transaction.exec(&dbs);
+
+        // Assert
+        assert_eq!(result.map(|queued| queued.is_none()), Ok(true));
+    }
+
+    #[test]
+    fn exec_after_watched_key_changed_returns_none() {
+        // Arrange
+        let mut dbs = [ShardedDb::new()];
+        let mut transaction = Transaction::default();
+        transaction
+            .watch(&dbs, 0, vec![Bytes::from_static(b"key")])
+            .unwrap();
+        transaction.begin().unwrap();
+        transaction.queue(Command::Get(Get::new("key")));
+        dbs[0].insert("key", Bytes::from_static(b"changed"));
+
+        // Act
+        let result = transaction.exec(&dbs);
+
+        // Assert
+        assert_eq!(result.map(|queued| queued.is_none()), Ok(true));
+        assert!(!transaction.is_active());
+    }
+
+    #[test]
+    fn exec_after_abort_discards_queue() {
+        // Arrange
+        let dbs = [ShardedDb::new()];
+        let mut transaction = Transaction::default();
+        transaction.begin().unwrap();
+        transaction.abort();
+
+        // Act
+        let result = transaction.exec(&dbs);
+        let again = transaction.exec(&dbs);
+
+        // Assert
+        assert_eq!(result.map(|_| ()), Err(Error::Aborted));
+        assert_eq!(again.map(|_| ()), Err(Error::ExecWithoutMulti));
+    }
+
+    #[test]
+    fn exec_unwatched_returns_queued_commands() {
+        // Arrange
+        let dbs = [ShardedDb::new()];
+        let mut transaction = Transaction::default();
+        transaction
+            .watch(&dbs, 0, vec![Bytes::from_static(b"key")])
+            .unwrap();
+        transaction.unwatch();
+        transaction.begin().unwrap();
+        transaction.queue(Command::Get(Get::new("key")));
+
+        // Act
+        let result = transaction.exec(&dbs);
+
+        // Assert
+        assert_ok!(&result);
+        assert_eq!(result.unwrap().map(|queued| queued.len()), Some(1));
+    }
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use common::{bulk, ok, TestServer};
+use diy_redis::client::{Client, Error, Pool};
+use diy_redis::config::ServerConfig;
+use diy_redis::frame::Frame;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn get_and_set() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = Client::connect(server.addr()).await.unwrap();
+
+    // Act
+    client.set("hello", "world".into()).await.unwrap();
+    let value = client.get("hello").await.unwrap();
+    let missing = client.get("missing").await.unwrap();
+
+    // Assert
+    assert_eq!(value, Some("world".into()));
+    assert_eq!(missing, None);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn server_error_reply_returned_as_typed_error() {
+    // Arrange
+    let config = ServerConfig {
+        max_value_size: 4,
+        ..ServerConfig::default()
+    };
+    let server = TestServer::spawn_with(config).await;
+    let mut client = Client::connect(server.addr()).await.unwrap();
+
+    // Act
+    let rejected = client.set("key", "too long".into()).await;
+    let after = client.get("key").await;
+
+    // Assert
+    assert!(
+        matches!(rejected, Err(Error::Server(message)) if message == "ERR value exceeds maximum size")
+    );
+    assert_eq!(after.unwrap(), None);
+    assert!(!client.is_broken());
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn pipeline_sets_then_mget() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = Client::connect(server.addr()).await.unwrap();
+    let keys: Vec<String> = (0..100).map(|i| format!("key:{i}")).collect();
+
+    // Act
+    let mut pipeline = client.pipeline();
+    for (i, key) in keys.iter().enumerate() {
+        pipeline.set(key, i.to_string());
+    }
+    pipeline.cmd(["MGET"].into_iter().chain(keys.iter().map(String::as_str)));
+    let responses = pipeline.execute().await.unwrap();
+
+    // Assert
+    assert_eq!(responses.len(), 101);
+    assert!(responses[..100].iter().all(|response| *response == ok()));
+    let expected = (0..100).map(|i| bulk(&i.to_string())).collect();
+    assert_eq!(responses[100], Frame::Array(expected));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn pipeline_error_keeps_replies_aligned() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = Client::connect(server.addr()).await.unwrap();
+
+    // Act
+    let mut pipeline = client.pipeline();
+    pipeline
+        .set("key", "value")
+        .cmd(["GET"])
+        .cmd(["NOSUCHCOMMAND"])
+        .get("key");
+    let responses = pipeline.execute().await.unwrap();
+
+    // Assert
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0], ok());
+    assert!(matches!(responses[1], Frame::Error(_)));
+    assert!(matches!(responses[2], Frame::Error(_)));
+    assert_eq!(responses[3], bulk("value"));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn pool_shared_by_concurrent_tasks_stays_bounded() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let pool = Pool::new(server.addr(), 4);
+    let checked_out = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    // Act
+    let tasks: Vec<_> = (0..32)
+        .map(|i| {
+            let pool = pool.clone();
+            let checked_out = Arc::clone(&checked_out);
+            let peak = Arc::clone(&peak);
+            tokio::spawn(async move {
+                let mut client = pool.get().await.unwrap();
+                let now = checked_out.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+
+                let key = format!("key:{i}");
+                client.set(&key, i.to_string().into()).await.unwrap();
+                let value = client.get(&key).await.unwrap();
+                tokio::task::yield_now().await;
+
+                checked_out.fetch_sub(1, Ordering::SeqCst);
+                value
+            })
+        })
+        .collect();
+
+    let mut values = Vec::new();
+    for task in tasks {
+        values.push(task.await.unwrap());
+    }
+
+    // Assert
+    assert!(peak.load(Ordering::SeqCst) <= 4);
+    assert!(pool.open_connections() <= 4);
+    assert_eq!(pool.idle_connections(), pool.open_connections());
+    for (i, value) in values.into_iter().enumerate() {
+        assert_eq!(value, Some(i.to_string().into()));
+    }
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn pool_discards_connections_with_a_cancelled_request() {
+    // Arrange
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let pool = Pool::new(listener.local_addr().unwrap(), 2);
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            // hold the connection open without ever replying
+            sockets.push(socket);
+        }
+    });
+
+    // Act
+    let result = {
+        let mut client = pool.get().await.unwrap();
+        tokio::time::timeout(Duration::from_millis(50), client.get("key")).await
+    };
+
+    // Assert
+    assert!(result.is_err());
+    assert_eq!(pool.open_connections(), 0);
+    assert_eq!(pool.idle_connections(), 0);
+}
+
+#[tokio::test]
+async fn pool_discards_broken_connections() {
+    // Arrange
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let pool = Pool::new(listener.local_addr().unwrap(), 2);
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            drop(socket);
+        }
+    });
+
+    // Act
+    let result = {
+        let mut client = pool.get().await.unwrap();
+        client.get("key").await
+    };
+
+    // Assert
+    assert!(result.is_err());
+    assert_eq!(pool.open_connections(), 0);
+    assert_eq!(pool.idle_connections(), 0);
+}
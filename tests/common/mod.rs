@@ -0,0 +1,98 @@
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use diy_redis::config::ServerConfig;
+use diy_redis::connection::Connection;
+use diy_redis::frame::Frame;
+use diy_redis::server;
+use std::net::SocketAddr;
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+pub struct TestServer {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl TestServer {
+    pub async fn spawn() -> Self {
+        Self::spawn_with(ServerConfig::default()).await
+    }
+
+    pub async fn spawn_with(config: ServerConfig) -> Self {
+        let listener = config
+            .socket
+            .listen("127.0.0.1:0".parse().unwrap())
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+
+        let handle = tokio::spawn(async move {
+            server::run(listener, config, shutdown_rx).await;
+        });
+
+        Self {
+            addr,
+            shutdown,
+            handle,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    pub async fn connect(&self) -> TestClient {
+        let socket = TcpStream::connect(self.addr).await.unwrap();
+        TestClient {
+            connection: Connection::new(socket),
+        }
+    }
+
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        self.handle.await.unwrap();
+    }
+}
+
+pub struct TestClient {
+    pub connection: Connection,
+}
+
+impl TestClient {
+    pub async fn send(&mut self, args: &[&str]) {
+        let frame = command(args);
+        self.connection.write_frame(&frame).await.unwrap();
+    }
+
+    pub async fn read(&mut self) -> Option<Frame> {
+        self.connection.read_frame().await.unwrap()
+    }
+
+    pub async fn cmd(&mut self, args: &[&str]) -> Frame {
+        self.send(args).await;
+        self.read().await.expect("server closed the connection")
+    }
+}
+
+pub fn command(args: &[&str]) -> Frame {
+    Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    )
+}
+
+pub fn bulk(content: &str) -> Frame {
+    Frame::Bulk(Bytes::copy_from_slice(content.as_bytes()))
+}
+
+pub fn ok() -> Frame {
+    Frame::ok()
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{bulk, ok, TestServer};
+use diy_redis::config::ServerConfig;
+use diy_redis::frame::Frame;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[tokio::test]
+async fn psubscribe_receives_matching_publish() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut subscriber = server.connect().await;
+    let mut publisher = server.connect().await;
+    let confirmation = subscriber.cmd(&["PSUBSCRIBE", "news.*"]).await;
+
+    // Act
+    let receivers = publisher.cmd(&["PUBLISH", "news.tech", "hello"]).await;
+    let message = subscriber.read().await;
+
+    // Assert
+    assert_eq!(
+        confirmation,
+        Frame::Array(vec![bulk("psubscribe"), bulk("news.*"), Frame::Integer(1)])
+    );
+    assert_eq!(receivers, Frame::Integer(1));
+    assert_eq!(
+        message,
+        Some(Frame::Array(vec![
+            bulk("pmessage"),
+            bulk("news.*"),
+            bulk("news.tech"),
+            bulk("hello"),
+        ]))
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn publish_counts_channel_and_pattern_subscribers() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut exact = server.connect().await;
+    let mut pattern = server.connect().await;
+    let mut publisher = server.connect().await;
+    exact.cmd(&["SUBSCRIBE", "news.tech"]).await;
+    pattern.cmd(&["PSUBSCRIBE", "news.*"]).await;
+
+    // Act
+    let receivers = publisher.cmd(&["PUBLISH", "news.tech", "hello"]).await;
+    let unmatched = publisher.cmd(&["PUBLISH", "sports", "hello"]).await;
+    let message = exact.read().await;
+
+    // Assert
+    assert_eq!(receivers, Frame::Integer(2));
+    assert_eq!(unmatched, Frame::Integer(0));
+    assert_eq!(
+        message,
+        Some(Frame::Array(vec![
+            bulk("message"),
+            bulk("news.tech"),
+            bulk("hello"),
+        ]))
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn keyevent_notification_on_set() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut subscriber = server.connect().await;
+    let mut client = server.connect().await;
+    let config = client
+        .cmd(&["CONFIG", "SET", "notify-keyspace-events", "E$"])
+        .await;
+    subscriber.cmd(&["SUBSCRIBE", "__keyevent@0__:set"]).await;
+
+    // Act
+    client.cmd(&["SET", "mykey", "value"]).await;
+    let message = subscriber.read().await;
+
+    // Assert
+    assert_eq!(config, ok());
+    assert_eq!(
+        message,
+        Some(Frame::Array(vec![
+            bulk("message"),
+            bulk("__keyevent@0__:set"),
+            bulk("mykey"),
+        ]))
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn subscriber_that_never_reads_is_dropped() {
+    // Arrange
+    let config = ServerConfig {
+        pubsub_write_timeout: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = TestServer::spawn_with(config).await;
+    let mut subscriber = server.connect().await;
+    let mut publisher = server.connect().await;
+    subscriber.cmd(&["SUBSCRIBE", "firehose"]).await;
+    let payload = "x".repeat(64 * 1024);
+
+    // Act
+    let mut receivers = Frame::Integer(1);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while receivers != Frame::Integer(0) && Instant::now() < deadline {
+        receivers = publisher.cmd(&["PUBLISH", "firehose", &payload]).await;
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // Assert
+    assert_eq!(receivers, Frame::Integer(0));
+
+    server.shutdown().await;
+}
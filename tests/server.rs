@@ -0,0 +1,1399 @@
+mod common;
+
+use bytes::Bytes;
+use common::{bulk, ok, TestClient, TestServer};
+use diy_redis::cmd::{CommandHandler, HandlerFuture};
+use diy_redis::config::ServerConfig;
+use diy_redis::db::ShardedDb;
+use diy_redis::frame::Frame;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time;
+
+#[tokio::test]
+async fn set_then_get() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let set = client.cmd(&["SET", "hello", "world"]).await;
+    let get = client.cmd(&["GET", "hello"]).await;
+    let missing = client.cmd(&["GET", "missing"]).await;
+
+    // Assert
+    assert_eq!(set, ok());
+    assert_eq!(get, bulk("world"));
+    assert_eq!(missing, Frame::Null);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn smembers_resp3_set_frame() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SADD", "set", "a", "b", "a"]).await;
+
+    // Act
+    let resp2 = client.cmd(&["SMEMBERS", "set"]).await;
+    let hello = client.cmd(&["HELLO", "3"]).await;
+    let resp3 = client.cmd(&["SMEMBERS", "set"]).await;
+
+    // Assert
+    assert!(matches!(resp2, Frame::Array(members) if members.len() == 2));
+    assert!(matches!(hello, Frame::Map(_)));
+    let Frame::Set(mut members) = resp3 else {
+        panic!("Expected Frame::Set variant");
+    };
+    members.sort_by_key(|member| format!("{:?}", member));
+    assert_eq!(members, vec![bulk("a"), bulk("b")]);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn set_commands_against_string_wrong_type() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "string", "value"]).await;
+
+    // Act
+    let frame = client.cmd(&["SADD", "string", "a"]).await;
+
+    // Assert
+    assert_eq!(
+        frame,
+        Frame::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+        )
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn set_with_expiry_reports_ttl() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let set = client.cmd(&["SET", "key", "value", "EX", "100"]).await;
+    let ttl = client.cmd(&["TTL", "key"]).await;
+    let invalid = client.cmd(&["SET", "key", "value", "EX", "0"]).await;
+
+    // Assert
+    assert_eq!(set, ok());
+    assert_eq!(ttl, Frame::Integer(100));
+    assert_eq!(
+        invalid,
+        Frame::Error("ERR invalid expire time in 'set' command".to_string())
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn max_value_size_rejects_without_mutating() {
+    // Arrange
+    let config = ServerConfig {
+        max_value_size: 8,
+        ..ServerConfig::default()
+    };
+    let server = TestServer::spawn_with(config).await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "key", "12345"]).await;
+
+    // Act
+    let set = client.cmd(&["SET", "other", "123456789"]).await;
+    let append = client.cmd(&["APPEND", "key", "6789"]).await;
+    let within = client.cmd(&["APPEND", "key", "678"]).await;
+    let cas = client.cmd(&["CAS", "key", "12345678", "123456789"]).await;
+    let value = client.cmd(&["GET", "key"]).await;
+    let other = client.cmd(&["GET", "other"]).await;
+
+    // Assert
+    let too_large = Frame::Error("ERR value exceeds maximum size".to_string());
+    assert_eq!(set, too_large);
+    assert_eq!(append, too_large);
+    assert_eq!(within, Frame::Integer(8));
+    assert_eq!(cas, too_large);
+    assert_eq!(value, bulk("12345678"));
+    assert_eq!(other, Frame::Null);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn exec_aborts_when_watched_key_changes() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let mut other = server.connect().await;
+    client.cmd(&["SET", "key", "1"]).await;
+
+    // Act
+    client.cmd(&["WATCH", "key"]).await;
+    client.cmd(&["MULTI"]).await;
+    let queued = client.cmd(&["SET", "key", "2"]).await;
+    other.cmd(&["SET", "key", "changed"]).await;
+    let aborted = client.cmd(&["EXEC"]).await;
+
+    client.cmd(&["WATCH", "key"]).await;
+    client.cmd(&["MULTI"]).await;
+    client.cmd(&["SET", "key", "3"]).await;
+    let executed = client.cmd(&["EXEC"]).await;
+    let value = client.cmd(&["GET", "key"]).await;
+
+    // Assert
+    assert_eq!(queued, Frame::Simple("QUEUED".into()));
+    assert_eq!(aborted, Frame::Null);
+    assert_eq!(executed, Frame::Array(vec![ok()]));
+    assert_eq!(value, bulk("3"));
+
+    server.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn exec_keeps_concurrent_writes_out_of_watched_transactions() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    server.connect().await.cmd(&["SET", "counter", "0"]).await;
+    let mut tasks = Vec::new();
+    for _ in 0..4 {
+        let mut client = server.connect().await;
+        tasks.push(tokio::spawn(async move {
+            let mut committed = 0;
+            while committed < 25 {
+                client.cmd(&["WATCH", "counter"]).await;
+                let Frame::Bulk(value) = client.cmd(&["GET", "counter"]).await else {
+                    panic!("Expected Frame::Bulk variant");
+                };
+                let next = std::str::from_utf8(&value).unwrap().parse::<f64>().unwrap() + 1.0;
+                client.cmd(&["MULTI"]).await;
+                // widen the gap between the watch check and the counter write
+                for _ in 0..200 {
+                    client.send(&["SET", "filler", "value"]).await;
+                }
+                for _ in 0..200 {
+                    client.read().await;
+                }
+                client.cmd(&["SET", "counter", &next.to_string()]).await;
+                if client.cmd(&["EXEC"]).await != Frame::Null {
+                    committed += 1;
+                }
+            }
+        }));
+    }
+    let mut incrementer = server.connect().await;
+
+    // Act
+    for _ in 0..100 {
+        incrementer.cmd(&["INCRBYFLOAT", "counter", "1"]).await;
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    let counter = incrementer.cmd(&["GET", "counter"]).await;
+
+    // Assert
+    assert_eq!(counter, bulk("200"));
+}
+
+#[tokio::test]
+async fn exec_checks_watched_keys_in_their_own_database() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let mut other = server.connect().await;
+    client.cmd(&["SET", "key", "1"]).await;
+
+    // Act
+    client.cmd(&["WATCH", "key"]).await;
+    client.cmd(&["SELECT", "1"]).await;
+    other.cmd(&["SET", "key", "changed"]).await;
+    client.cmd(&["MULTI"]).await;
+    client.cmd(&["SET", "key", "2"]).await;
+    let aborted = client.cmd(&["EXEC"]).await;
+    let in_1 = client.cmd(&["GET", "key"]).await;
+
+    // Assert
+    assert_eq!(aborted, Frame::Null);
+    assert_eq!(in_1, Frame::Null);
+}
+
+#[tokio::test]
+async fn protocol_error_verbosity_follows_config() {
+    // Arrange
+    let terse = TestServer::spawn().await;
+    let verbose = TestServer::spawn_with(ServerConfig {
+        verbose_protocol_errors: true,
+        ..ServerConfig::default()
+    })
+    .await;
+
+    // Act
+    let terse_reply = send_raw(&terse, b"*1\r\n$abc\r\n").await;
+    let verbose_reply = send_raw(&verbose, b"*1\r\n$abc\r\n").await;
+
+    // Assert
+    assert_eq!(terse_reply, "-ERR Protocol error\r\n");
+    assert_eq!(
+        verbose_reply,
+        "-ERR Protocol error: invalid bulk string length digit\r\n"
+    );
+
+    terse.shutdown().await;
+    verbose.shutdown().await;
+}
+
+#[tokio::test]
+async fn protocol_errors_counted_until_resetstat() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["PING"]).await;
+
+    // Act
+    send_raw(&server, b"*1\r\n$abc\r\n").await;
+    send_raw(&server, b"!oops\r\n").await;
+    let counted = info_field(&mut client, "total_protocol_errors").await;
+    let reset = client.cmd(&["CONFIG", "RESETSTAT"]).await;
+    let after_reset = info_field(&mut client, "total_protocol_errors").await;
+
+    // Assert
+    assert_eq!(counted, 2);
+    assert_eq!(reset, ok());
+    assert_eq!(after_reset, 0);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn info_memory_tracks_usage_and_evictions() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let value = "x".repeat(1024);
+
+    // Act
+    let empty = info_field(&mut client, "used_memory").await;
+    for key in ["a", "b", "c"] {
+        client.cmd(&["SET", key, &value]).await;
+    }
+    let filled = info_field(&mut client, "used_memory").await;
+    client.cmd(&["DEL", "a", "b"]).await;
+    let deleted = info_field(&mut client, "used_memory").await;
+
+    client
+        .cmd(&["CONFIG", "SET", "maxmemory-policy", "allkeys-lru"])
+        .await;
+    let maxmemory = (filled / 2).to_string();
+    client
+        .cmd(&["CONFIG", "SET", "maxmemory", &maxmemory])
+        .await;
+    for key in ["d", "e", "f"] {
+        client.cmd(&["SET", key, &value]).await;
+    }
+    let evicted = info_field(&mut client, "evicted_keys").await;
+
+    // Assert
+    assert_eq!(empty, 0);
+    assert!(filled > 3 * 1024);
+    assert!(deleted < filled);
+    assert!(evicted > 0);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn memory_limits_and_settings_span_databases() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let value = "x".repeat(1024);
+    for key in ["a", "b", "c"] {
+        client.cmd(&["SET", key, &value]).await;
+    }
+    client.cmd(&["RPUSH", "list", "a", "b", "c"]).await;
+    client.cmd(&["DEBUG", "LIST-MAX-LISTPACK-SIZE", "2"]).await;
+
+    // Act
+    client.cmd(&["SELECT", "1"]).await;
+    let used = info_field(&mut client, "used_memory").await;
+    client.cmd(&["RPUSH", "list", "a", "b", "c"]).await;
+    let encoding = client.cmd(&["OBJECT", "ENCODING", "list"]).await;
+    client
+        .cmd(&["CONFIG", "SET", "maxmemory", &(used / 2).to_string()])
+        .await;
+    let refused = client.cmd(&["SET", "key", &value]).await;
+
+    // Assert
+    assert!(used > 3 * 1024);
+    assert_eq!(encoding, bulk("quicklist"));
+    assert_eq!(
+        refused,
+        Frame::Error("OOM command not allowed when used memory > 'maxmemory'.".into())
+    );
+}
+
+#[tokio::test]
+async fn latency_latest_after_commands() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let before = client.cmd(&["LATENCY", "LATEST"]).await;
+    client.cmd(&["PING"]).await;
+    client.cmd(&["SET", "key", "value"]).await;
+    let latest = client.cmd(&["LATENCY", "LATEST"]).await;
+    let history = client.cmd(&["LATENCY", "HISTORY", "command"]).await;
+    let reset = client.cmd(&["LATENCY", "RESET"]).await;
+
+    // Assert
+    assert_eq!(before, Frame::Array(vec![]));
+    let Frame::Array(latest) = latest else {
+        panic!("expected an array");
+    };
+    let Frame::Array(event) = &latest[0] else {
+        panic!("expected an event entry");
+    };
+    assert_eq!(event[0], bulk("command"));
+    let Frame::Array(history) = history else {
+        panic!("expected an array");
+    };
+    assert!(!history.is_empty());
+    assert_eq!(reset, Frame::Integer(1));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn read_only_rejects_writes() {
+    // Arrange
+    let server = TestServer::spawn_with(ServerConfig {
+        read_only: true,
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut client = server.connect().await;
+
+    // Act
+    let set = client.cmd(&["SET", "key", "value"]).await;
+    let get = client.cmd(&["GET", "key"]).await;
+
+    // Assert
+    assert_eq!(
+        set,
+        Frame::Error("READONLY You can't write against a read only replica.".to_string())
+    );
+    assert_eq!(get, Frame::Null);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn monitor_observes_other_connections() {
+    // Arrange
+    let server = TestServer::spawn_with(ServerConfig {
+        enable_monitor: true,
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut monitor = server.connect().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let started = monitor.cmd(&["MONITOR"]).await;
+    client.cmd(&["SET", "key", "value"]).await;
+    let line = monitor.read().await;
+
+    // Assert
+    assert_eq!(started, ok());
+    let Some(Frame::Simple(line)) = line else {
+        panic!("expected a monitor line, got {line:?}");
+    };
+    let line = String::from_utf8(line.to_vec()).unwrap();
+    assert!(line.contains("[0 127.0.0.1:"), "{line}");
+    assert!(line.ends_with(r#" "SET" "key" "value""#), "{line}");
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn monitor_disabled_by_default() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let response = client.cmd(&["MONITOR"]).await;
+
+    // Assert
+    assert!(matches!(response, Frame::Error(_)));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn client_setname_then_getname() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let unnamed = client.cmd(&["CLIENT", "GETNAME"]).await;
+    let set = client.cmd(&["CLIENT", "SETNAME", "worker-1"]).await;
+    let named = client.cmd(&["CLIENT", "GETNAME"]).await;
+    let invalid = client.cmd(&["CLIENT", "SETNAME", "two words"]).await;
+
+    // Assert
+    assert_eq!(unnamed, Frame::Null);
+    assert_eq!(set, ok());
+    assert_eq!(named, bulk("worker-1"));
+    assert!(matches!(invalid, Frame::Error(_)));
+    assert_eq!(client.cmd(&["CLIENT", "GETNAME"]).await, bulk("worker-1"));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn client_id_unique_per_connection() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut first = server.connect().await;
+    let mut second = server.connect().await;
+
+    // Act
+    let first_id = first.cmd(&["CLIENT", "ID"]).await;
+    let second_id = second.cmd(&["CLIENT", "ID"]).await;
+
+    // Assert
+    assert!(matches!(first_id, Frame::Integer(_)));
+    assert!(matches!(second_id, Frame::Integer(_)));
+    assert_ne!(first_id, second_id);
+    assert_eq!(first.cmd(&["CLIENT", "ID"]).await, first_id);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn client_list_then_kill_by_id() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut admin = server.connect().await;
+    let mut victim = server.connect().await;
+    victim.cmd(&["CLIENT", "SETNAME", "victim"]).await;
+    let Frame::Integer(victim_id) = victim.cmd(&["CLIENT", "ID"]).await else {
+        panic!("expected an integer reply");
+    };
+
+    // Act
+    let Frame::Bulk(list) = admin.cmd(&["CLIENT", "LIST"]).await else {
+        panic!("expected a bulk reply");
+    };
+    let killed = admin
+        .cmd(&["CLIENT", "KILL", "ID", &victim_id.to_string()])
+        .await;
+    let closed = victim.read().await;
+
+    // Assert
+    let list = String::from_utf8(list.to_vec()).unwrap();
+    let line = list
+        .lines()
+        .find(|line| line.starts_with(&format!("id={victim_id} ")))
+        .unwrap();
+    assert!(line.contains(" name=victim "));
+    assert!(line.ends_with(" cmd=client"));
+    assert_eq!(list.lines().count(), 2);
+    assert_eq!(killed, Frame::Integer(1));
+    assert_eq!(closed, None);
+    assert_eq!(
+        admin
+            .cmd(&["CLIENT", "KILL", "ID", &victim_id.to_string()])
+            .await,
+        Frame::Integer(0)
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn disabled_commands_refused_others_allowed() {
+    // Arrange
+    let disabled = ServerConfig {
+        disabled_commands: ["flushall".to_string(), "debug".to_string()].into(),
+        ..ServerConfig::default()
+    };
+    let hidden = TestServer::spawn_with(ServerConfig {
+        hide_disabled_commands: true,
+        ..disabled.clone()
+    })
+    .await;
+    let explicit = TestServer::spawn_with(disabled).await;
+    let mut hidden_client = hidden.connect().await;
+    let mut explicit_client = explicit.connect().await;
+
+    // Act
+    let hidden_flush = hidden_client.cmd(&["FLUSHALL"]).await;
+    let hidden_debug = hidden_client.cmd(&["debug", "object"]).await;
+    let explicit_flush = explicit_client.cmd(&["FlushAll"]).await;
+    let set = explicit_client.cmd(&["SET", "key", "value"]).await;
+    let flushdb = explicit_client.cmd(&["FLUSHDB"]).await;
+
+    // Assert
+    assert_eq!(
+        hidden_flush,
+        Frame::Error("ERR unknown command 'FLUSHALL', with args beginning with: ".into())
+    );
+    assert_eq!(
+        hidden_debug,
+        Frame::Error("ERR unknown command 'debug', with args beginning with: 'object'".into())
+    );
+    assert_eq!(
+        explicit_flush,
+        Frame::Error("ERR command 'FlushAll' is disabled".into())
+    );
+    assert_eq!(set, ok());
+    assert_eq!(flushdb, ok());
+
+    hidden.shutdown().await;
+    explicit.shutdown().await;
+}
+
+#[tokio::test]
+async fn subscriber_mode_counts_and_rejects_other_commands() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let count = |reply: Frame| match reply {
+        Frame::Array(parts) => parts[2].clone(),
+        reply => panic!("expected a confirmation, got {reply:?}"),
+    };
+
+    // Act
+    let first = client.cmd(&["SUBSCRIBE", "a", "b", "c"]).await;
+    let second = client.read().await.unwrap();
+    let third = client.read().await.unwrap();
+    let get = client.cmd(&["GET", "key"]).await;
+    let unsubscribed = client.cmd(&["UNSUBSCRIBE", "b"]).await;
+
+    // Assert
+    assert_eq!(count(first), Frame::Integer(1));
+    assert_eq!(count(second), Frame::Integer(2));
+    assert_eq!(count(third), Frame::Integer(3));
+    let Frame::Error(get) = get else {
+        panic!("expected an error reply");
+    };
+    assert!(get.starts_with("ERR Can't execute 'get': only (P|S)SUBSCRIBE"));
+    assert_eq!(count(unsubscribed), Frame::Integer(2));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn health_endpoint_reports_key_count() {
+    // Arrange
+    let health_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let server = TestServer::spawn_with(ServerConfig {
+        health_addr: Some(health_addr),
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "a", "1"]).await;
+    client.cmd(&["SET", "b", "2"]).await;
+
+    // Act
+    let mut socket = loop {
+        match TcpStream::connect(health_addr).await {
+            Ok(socket) => break socket,
+            Err(_) => time::sleep(Duration::from_millis(10)).await,
+        }
+    };
+    socket
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    socket.read_to_string(&mut response).await.unwrap();
+
+    // Assert
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(body.starts_with(r#"{"status":"ok","keys":2,"uptime_in_seconds":"#));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn timeout_reaps_idle_connections_only() {
+    // Arrange
+    let server = TestServer::spawn_with(ServerConfig {
+        timeout: Duration::from_millis(300),
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut idle = server.connect().await;
+    let mut active = server.connect().await;
+
+    // Act
+    for _ in 0..8 {
+        assert_eq!(active.cmd(&["PING"]).await, Frame::pong());
+        time::sleep(Duration::from_millis(100)).await;
+    }
+    let closed = idle.read().await;
+
+    // Assert
+    assert_eq!(closed, None);
+    assert_eq!(active.cmd(&["PING"]).await, Frame::pong());
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn timeout_spares_subscribers() {
+    // Arrange
+    let server = TestServer::spawn_with(ServerConfig {
+        timeout: Duration::from_millis(300),
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut subscriber = server.connect().await;
+    subscriber.send(&["SUBSCRIBE", "news"]).await;
+    subscriber.read().await.unwrap();
+
+    // Act
+    time::sleep(Duration::from_millis(800)).await;
+    let mut publisher = server.connect().await;
+    publisher.cmd(&["PUBLISH", "news", "hello"]).await;
+    let message = subscriber.read().await;
+
+    // Assert
+    assert_eq!(
+        message,
+        Some(Frame::Array(vec![
+            bulk("message"),
+            bulk("news"),
+            bulk("hello")
+        ]))
+    );
+
+    server.shutdown().await;
+}
+
+async fn info_field(client: &mut TestClient, field: &str) -> usize {
+    let Frame::Bulk(info) = client.cmd(&["INFO"]).await else {
+        panic!("expected a bulk reply");
+    };
+    let info = String::from_utf8(info.to_vec()).unwrap();
+    info.lines()
+        .find_map(|line| line.strip_prefix(&format!("{field}:")))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+/// Writes `bytes` as-is and returns everything the server sends back before
+/// closing the connection.
+async fn send_raw(server: &TestServer, bytes: &[u8]) -> String {
+    let mut socket = TcpStream::connect(server.addr()).await.unwrap();
+    socket.write_all(bytes).await.unwrap();
+    let mut reply = String::new();
+    socket.read_to_string(&mut reply).await.unwrap();
+    reply
+}
+
+#[tokio::test]
+async fn command_info_reports_arity_and_flags() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let info = client
+        .cmd(&["COMMAND", "INFO", "get", "SET", "nosuch"])
+        .await;
+
+    // Assert
+    let Frame::Array(specs) = info else {
+        panic!("expected an array, got {info:?}");
+    };
+    assert_eq!(
+        specs[0],
+        Frame::Array(vec![
+            bulk("get"),
+            Frame::Integer(2),
+            Frame::Array(vec![
+                Frame::Simple("readonly".into()),
+                Frame::Simple("fast".into()),
+            ]),
+            Frame::Integer(1),
+            Frame::Integer(1),
+            Frame::Integer(1),
+        ])
+    );
+    let Frame::Array(set) = &specs[1] else {
+        panic!("expected an array, got {:?}", specs[1]);
+    };
+    assert_eq!(set[1], Frame::Integer(-3));
+    assert!(
+        matches!(&set[2], Frame::Array(flags) if flags.contains(&Frame::Simple("write".into())))
+    );
+    assert_eq!(specs[2], Frame::Null);
+}
+
+#[tokio::test]
+async fn del_counts_list_and_publishes_del_keyevent() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let mut subscriber = server.connect().await;
+    client
+        .cmd(&["CONFIG", "SET", "notify-keyspace-events", "Eg"])
+        .await;
+    subscriber.cmd(&["SUBSCRIBE", "__keyevent@0__:del"]).await;
+    client.cmd(&["RPUSH", "queue", "a", "b"]).await;
+
+    // Act
+    let deleted = client.cmd(&["DEL", "queue", "missing"]).await;
+    let message = subscriber.read().await;
+
+    // Assert
+    assert_eq!(deleted, Frame::Integer(1));
+    assert_eq!(
+        message,
+        Some(Frame::Array(vec![
+            bulk("message"),
+            bulk("__keyevent@0__:del"),
+            bulk("queue"),
+        ]))
+    );
+    assert_eq!(info_field(&mut client, "deleted_lists").await, 1);
+    assert_eq!(info_field(&mut client, "deleted_strings").await, 0);
+}
+
+#[tokio::test]
+async fn default_ttl_applies_unless_set_overrides_it() {
+    // Arrange
+    let server = TestServer::spawn_with(ServerConfig {
+        default_ttl: Some(Duration::from_secs(100)),
+        ..ServerConfig::default()
+    })
+    .await;
+    let mut client = server.connect().await;
+
+    // Act
+    client.cmd(&["SET", "plain", "v"]).await;
+    client.cmd(&["SET", "explicit", "v", "EX", "1000"]).await;
+    client.cmd(&["SET", "kept", "v", "PERSIST"]).await;
+
+    // Assert
+    assert_eq!(client.cmd(&["TTL", "plain"]).await, Frame::Integer(100));
+    assert_eq!(client.cmd(&["TTL", "explicit"]).await, Frame::Integer(1000));
+    assert_eq!(client.cmd(&["TTL", "kept"]).await, Frame::Integer(-1));
+    let conflicting = client.cmd(&["SET", "k", "v", "PERSIST", "EX", "1"]).await;
+    assert_eq!(conflicting, Frame::Error("ERR syntax error".into()));
+}
+
+#[tokio::test]
+async fn commandstats_counts_calls() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "key", "value"]).await;
+
+    // Act
+    for _ in 0..3 {
+        client.cmd(&["GET", "key"]).await;
+    }
+    let Frame::Bulk(info) = client.cmd(&["INFO", "commandstats"]).await else {
+        panic!("expected a bulk reply");
+    };
+
+    // Assert
+    let info = String::from_utf8(info.to_vec()).unwrap();
+    assert!(info.contains("cmdstat_get:calls=3,usec="), "{info}");
+    assert!(info.contains("cmdstat_set:calls=1,usec="), "{info}");
+}
+
+#[tokio::test]
+async fn quit_replies_ok_then_closes_mid_pipeline() {
+    // Arrange
+    let server = TestServer::spawn().await;
+
+    // Act
+    let replies = send_raw(
+        &server,
+        b"*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nQUIT\r\n*1\r\n$4\r\nPING\r\n",
+    )
+    .await;
+
+    // Assert
+    assert_eq!(replies, "+PONG\r\n+OK\r\n");
+}
+
+#[tokio::test]
+async fn keepttl_and_in_place_updates_preserve_ttl() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "counter", "1", "EX", "100"]).await;
+    client.cmd(&["SET", "kept", "a", "EX", "100"]).await;
+
+    // Act
+    client.cmd(&["INCRBYFLOAT", "counter", "1"]).await;
+    let incremented_ttl = client.cmd(&["TTL", "counter"]).await;
+    client.cmd(&["SET", "kept", "b", "KEEPTTL"]).await;
+    let kept_ttl = client.cmd(&["TTL", "kept"]).await;
+    client.cmd(&["SET", "counter", "5"]).await;
+    let overwritten_ttl = client.cmd(&["TTL", "counter"]).await;
+
+    // Assert
+    assert_eq!(incremented_ttl, Frame::Integer(100));
+    assert_eq!(kept_ttl, Frame::Integer(100));
+    assert_eq!(client.cmd(&["GET", "kept"]).await, bulk("b"));
+    assert_eq!(overwritten_ttl, Frame::Integer(-1));
+}
+
+#[tokio::test]
+async fn ping_in_subscriber_mode_replies_as_array() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let outside = client.cmd(&["PING"]).await;
+    client.cmd(&["SUBSCRIBE", "news"]).await;
+    let inside = client.cmd(&["PING"]).await;
+    let inside_with_message = client.cmd(&["PING", "hi"]).await;
+
+    // Assert
+    assert_eq!(outside, Frame::pong());
+    assert_eq!(inside, Frame::Array(vec![bulk("pong"), bulk("")]));
+    assert_eq!(
+        inside_with_message,
+        Frame::Array(vec![bulk("pong"), bulk("hi")])
+    );
+}
+
+#[tokio::test]
+async fn large_sort_does_not_block_other_connections() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut sorter = server.connect().await;
+    let mut pinger = server.connect().await;
+    let mut push = vec!["RPUSH".to_string(), "numbers".to_string()];
+    push.extend((0..300_000).rev().map(|n| n.to_string()));
+    let push: Vec<&str> = push.iter().map(String::as_str).collect();
+    sorter.cmd(&push).await;
+
+    // Act
+    sorter.send(&["SORT", "numbers", "LIMIT", "0", "1"]).await;
+    time::sleep(Duration::from_millis(10)).await;
+    let pong = pinger.cmd(&["PING"]).await;
+    let early = time::timeout(Duration::ZERO, sorter.read()).await.ok();
+    let finished_first = early.is_some();
+    let sorted = match early {
+        Some(sorted) => sorted,
+        None => sorter.read().await,
+    };
+
+    // Assert
+    assert_eq!(pong, Frame::pong());
+    assert!(!finished_first, "SORT finished before PING was answered");
+    assert_eq!(sorted, Some(Frame::Array(vec![bulk("0")])));
+}
+
+#[tokio::test]
+async fn set_get_returns_previous_value() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["RPUSH", "list", "a"]).await;
+
+    // Act
+    let first = client.cmd(&["SET", "key", "one", "GET"]).await;
+    let second = client.cmd(&["SET", "key", "two", "GET", "EX", "100"]).await;
+    let value = client.cmd(&["GET", "key"]).await;
+    let ttl = client.cmd(&["TTL", "key"]).await;
+    let wrong_type = client.cmd(&["SET", "list", "v", "GET"]).await;
+
+    // Assert
+    assert_eq!(first, Frame::Null);
+    assert_eq!(second, bulk("one"));
+    assert_eq!(value, bulk("two"));
+    assert_eq!(ttl, Frame::Integer(100));
+    assert_eq!(
+        wrong_type,
+        Frame::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_nx_get_only_writes_missing_keys() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "present", "old"]).await;
+
+    // Act
+    let present = client.cmd(&["SET", "present", "new", "NX", "GET"]).await;
+    let missing = client.cmd(&["SET", "missing", "new", "NX", "GET"]).await;
+    let xx_missing = client.cmd(&["SET", "other", "new", "XX"]).await;
+    let conflicting = client.cmd(&["SET", "present", "new", "NX", "XX"]).await;
+
+    // Assert
+    assert_eq!(present, bulk("old"));
+    assert_eq!(client.cmd(&["GET", "present"]).await, bulk("old"));
+    assert_eq!(missing, Frame::Null);
+    assert_eq!(client.cmd(&["GET", "missing"]).await, bulk("new"));
+    assert_eq!(xx_missing, Frame::Null);
+    assert_eq!(client.cmd(&["GET", "other"]).await, Frame::Null);
+    assert_eq!(conflicting, Frame::Error("ERR syntax error".to_string()));
+}
+
+#[tokio::test]
+async fn long_pipeline_yields_without_losing_replies() {
+    // Arrange
+    let config = ServerConfig {
+        max_pipeline_burst: 2,
+        ..ServerConfig::default()
+    };
+    let server = TestServer::spawn_with(config).await;
+    let mut other = server.connect().await;
+    let mut pipeline = Vec::new();
+    for n in 0..1000 {
+        pipeline.extend_from_slice(
+            format!("*2\r\n$4\r\nPING\r\n${}\r\n{n}\r\n", n.to_string().len()).as_bytes(),
+        );
+    }
+    pipeline.extend_from_slice(b"*1\r\n$4\r\nQUIT\r\n");
+
+    // Act
+    let (reply, pong) = tokio::join!(send_raw(&server, &pipeline), other.cmd(&["PING"]));
+
+    // Assert
+    let expected: String = (0..1000)
+        .map(|n| format!("${}\r\n{n}\r\n", n.to_string().len()))
+        .chain(["+OK\r\n".to_string()])
+        .collect();
+    assert_eq!(reply, expected);
+    assert_eq!(pong, Frame::pong());
+}
+
+#[tokio::test]
+async fn select_switches_between_separate_databases() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let mut other = server.connect().await;
+    client.cmd(&["SET", "key", "in-0"]).await;
+
+    // Act
+    let selected = client.cmd(&["SELECT", "15"]).await;
+    let missing = client.cmd(&["GET", "key"]).await;
+    client.cmd(&["SET", "key", "in-15"]).await;
+    let out_of_range = client.cmd(&["SELECT", "16"]).await;
+    let still_selected = client.cmd(&["GET", "key"]).await;
+    let unaffected = other.cmd(&["GET", "key"]).await;
+    client.cmd(&["FLUSHDB"]).await;
+    let after_flushdb = other.cmd(&["GET", "key"]).await;
+    client.cmd(&["SET", "key", "in-15"]).await;
+    other.cmd(&["FLUSHALL"]).await;
+    let after_flushall = client.cmd(&["GET", "key"]).await;
+
+    // Assert
+    assert_eq!(selected, ok());
+    assert_eq!(missing, Frame::Null);
+    assert_eq!(
+        out_of_range,
+        Frame::Error("ERR DB index is out of range".to_string())
+    );
+    assert_eq!(still_selected, bulk("in-15"));
+    assert_eq!(unaffected, bulk("in-0"));
+    assert_eq!(after_flushdb, bulk("in-0"));
+    assert_eq!(after_flushall, Frame::Null);
+}
+
+#[tokio::test]
+async fn databases_bounds_select() {
+    // Arrange
+    let config = ServerConfig {
+        databases: 1,
+        ..ServerConfig::default()
+    };
+    let server = TestServer::spawn_with(config).await;
+    let mut client = server.connect().await;
+
+    // Act
+    let first = client.cmd(&["SELECT", "0"]).await;
+    let second = client.cmd(&["SELECT", "1"]).await;
+    let count = client.cmd(&["CONFIG", "GET", "databases"]).await;
+
+    // Assert
+    assert_eq!(first, ok());
+    assert_eq!(
+        second,
+        Frame::Error("ERR DB index is out of range".to_string())
+    );
+    assert_eq!(count, Frame::Array(vec![bulk("databases"), bulk("1")]));
+}
+
+#[tokio::test]
+async fn swapdb_exchanges_databases_for_every_connection() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut first = server.connect().await;
+    let mut second = server.connect().await;
+    second.cmd(&["SELECT", "1"]).await;
+    first.cmd(&["SET", "key", "in-0"]).await;
+    second.cmd(&["SET", "key", "in-1"]).await;
+    second.cmd(&["SET", "only-in-1", "value"]).await;
+
+    // Act
+    let swapped = first.cmd(&["SWAPDB", "0", "1"]).await;
+    let from_first = first.cmd(&["GET", "key"]).await;
+    let moved = first.cmd(&["GET", "only-in-1"]).await;
+    let from_second = second.cmd(&["GET", "key"]).await;
+    let out_of_range = first.cmd(&["SWAPDB", "0", "16"]).await;
+
+    // Assert
+    assert_eq!(swapped, ok());
+    assert_eq!(from_first, bulk("in-1"));
+    assert_eq!(moved, bulk("value"));
+    assert_eq!(from_second, bulk("in-0"));
+    assert_eq!(
+        out_of_range,
+        Frame::Error("ERR DB index is out of range".to_string())
+    );
+}
+
+#[tokio::test]
+async fn swapdb_invalidates_watchers_of_both_databases() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut first = server.connect().await;
+    let mut second = server.connect().await;
+    second.cmd(&["SELECT", "1"]).await;
+    first.cmd(&["SET", "key", "in-0"]).await;
+    second.cmd(&["SET", "key", "in-1"]).await;
+    first.cmd(&["SET", "only-in-0", "value"]).await;
+    first.cmd(&["WATCH", "key"]).await;
+    second.cmd(&["WATCH", "only-in-0"]).await;
+
+    // Act
+    let mut other = server.connect().await;
+    other.cmd(&["SWAPDB", "0", "1"]).await;
+    first.cmd(&["MULTI"]).await;
+    first.cmd(&["SET", "key", "from-first"]).await;
+    let first_exec = first.cmd(&["EXEC"]).await;
+    second.cmd(&["MULTI"]).await;
+    second.cmd(&["SET", "only-in-0", "from-second"]).await;
+    let second_exec = second.cmd(&["EXEC"]).await;
+
+    // Assert
+    assert_eq!(first_exec, Frame::Null);
+    assert_eq!(second_exec, Frame::Null);
+    assert_eq!(first.cmd(&["GET", "key"]).await, bulk("in-1"));
+    assert_eq!(second.cmd(&["GET", "only-in-0"]).await, bulk("value"));
+}
+
+#[tokio::test]
+async fn binary_keys_round_trip() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let key = Bytes::from_static(b"bin\0\xff\xfekey");
+    let lossy = String::from_utf8_lossy(&key).into_owned();
+    let set = Frame::Array(vec![bulk("SET"), Frame::Bulk(key.clone()), bulk("value")]);
+    let get = Frame::Array(vec![bulk("GET"), Frame::Bulk(key)]);
+
+    // Act
+    client.connection.write_frame(&set).await.unwrap();
+    let stored = client.read().await;
+    client.connection.write_frame(&get).await.unwrap();
+    let fetched = client.read().await;
+    let replaced = client.cmd(&["GET", &lossy]).await;
+
+    // Assert
+    assert_eq!(stored, Some(ok()));
+    assert_eq!(fetched, Some(bulk("value")));
+    assert_eq!(replaced, Frame::Null);
+}
+
+#[tokio::test]
+async fn unlink_removes_keys_of_any_size() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let mut push = vec!["RPUSH", "large"];
+    push.extend(["element"; 10_000]);
+    client.cmd(&push).await;
+    client.cmd(&["SET", "small", "value"]).await;
+
+    // Act
+    let unlinked = client.cmd(&["UNLINK", "large", "small", "missing"]).await;
+    let large = client.cmd(&["LPOS", "large", "element"]).await;
+    let threshold = client.cmd(&["CONFIG", "GET", "lazyfree-threshold"]).await;
+
+    // Assert
+    assert_eq!(unlinked, Frame::Integer(2));
+    assert_eq!(large, Frame::Null);
+    assert_eq!(
+        threshold,
+        Frame::Array(vec![bulk("lazyfree-threshold"), bulk("65536")])
+    );
+}
+
+#[tokio::test]
+async fn lolwut_replies_with_version() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+
+    // Act
+    let art = client.cmd(&["LOLWUT"]).await;
+    let versioned = client.cmd(&["LOLWUT", "VERSION", "5"]).await;
+
+    // Assert
+    let Frame::Bulk(art) = art else {
+        panic!("expected a bulk reply, got {art:?}");
+    };
+    let art = String::from_utf8(art.to_vec()).unwrap();
+    assert!(art.contains(&format!("ver. {}", env!("CARGO_PKG_VERSION"))));
+    assert!(matches!(versioned, Frame::Bulk(_)));
+}
+
+#[tokio::test]
+async fn object_encoding_follows_list_limit() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["RPUSH", "list", "a", "b"]).await;
+
+    // Act
+    let packed = client.cmd(&["OBJECT", "ENCODING", "list"]).await;
+    let limit = client.cmd(&["DEBUG", "LIST-MAX-LISTPACK-SIZE", "2"]).await;
+    client.cmd(&["RPUSH", "list", "c"]).await;
+    let unpacked = client.cmd(&["OBJECT", "ENCODING", "list"]).await;
+    let invalid = client.cmd(&["DEBUG", "LIST-MAX-LISTPACK-SIZE", "-6"]).await;
+
+    // Assert
+    assert_eq!(packed, bulk("listpack"));
+    assert_eq!(limit, ok());
+    assert_eq!(unpacked, bulk("quicklist"));
+    assert_eq!(
+        invalid,
+        Frame::Error("ERR value is not an integer or out of range".into())
+    );
+}
+
+#[tokio::test]
+async fn time_replies_with_seconds_and_microseconds() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+    // Act
+    let time = client.cmd(&["TIME"]).await;
+
+    // Assert
+    let after = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let Frame::Array(parts) = time else {
+        panic!("expected an array reply, got {time:?}");
+    };
+    let [Frame::Bulk(secs), Frame::Bulk(micros)] = &parts[..] else {
+        panic!("expected two bulk strings, got {parts:?}");
+    };
+    let secs: u64 = std::str::from_utf8(secs).unwrap().parse().unwrap();
+    let micros: u32 = std::str::from_utf8(micros).unwrap().parse().unwrap();
+    assert!((before.as_secs()..=after.as_secs()).contains(&secs));
+    assert!(micros < 1_000_000);
+}
+
+#[tokio::test]
+async fn scan_type_returns_only_lists() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "string", "value"]).await;
+    client.cmd(&["RPUSH", "list:1", "a"]).await;
+    client.cmd(&["LPUSH", "list:2", "b"]).await;
+    client.cmd(&["SADD", "set", "c"]).await;
+
+    // Act
+    let scan = client
+        .cmd(&["SCAN", "0", "TYPE", "list", "COUNT", "100"])
+        .await;
+
+    // Assert
+    let Frame::Array(reply) = scan else {
+        panic!("expected an array reply, got {scan:?}");
+    };
+    let [cursor, Frame::Array(keys)] = &reply[..] else {
+        panic!("expected a cursor and keys, got {reply:?}");
+    };
+    let mut keys = keys.clone();
+    keys.sort_by_key(|key| format!("{key:?}"));
+    assert_eq!(cursor, &bulk("0"));
+    assert_eq!(keys, [bulk("list:1"), bulk("list:2")]);
+}
+
+struct HelloCustom;
+
+impl CommandHandler for HelloCustom {
+    fn name(&self) -> &str {
+        "HELLO-CUSTOM"
+    }
+
+    fn execute<'a>(&'a self, args: Vec<Bytes>, db: &'a mut ShardedDb) -> HandlerFuture<'a> {
+        Box::pin(async move {
+            let name = args.into_iter().next().unwrap_or_default();
+            let greeting = format!("Hello, {}!", String::from_utf8_lossy(&name));
+            db.insert("last-greeted", name);
+            Frame::Bulk(greeting.into())
+        })
+    }
+}
+
+#[tokio::test]
+async fn custom_command_dispatched_before_built_ins() {
+    // Arrange
+    let mut config = ServerConfig::default();
+    config.commands.register(HelloCustom);
+    let server = TestServer::spawn_with(config).await;
+    let mut client = server.connect().await;
+
+    // Act
+    let greeting = client.cmd(&["hello-custom", "you"]).await;
+    let last_greeted = client.cmd(&["GET", "last-greeted"]).await;
+    client.cmd(&["MULTI"]).await;
+    let queued = client.cmd(&["HELLO-CUSTOM", "again"]).await;
+    let exec = client.cmd(&["EXEC"]).await;
+
+    // Assert
+    assert_eq!(greeting, bulk("Hello, you!"));
+    assert_eq!(last_greeted, bulk("you"));
+    assert_eq!(
+        queued,
+        Frame::Error(
+            "ERR 'hello-custom' is a custom command and can't be used inside MULTI".into()
+        )
+    );
+    assert_eq!(
+        exec,
+        Frame::Error("EXECABORT Transaction discarded because of previous errors.".into())
+    );
+}
+
+#[tokio::test]
+async fn custom_command_held_to_read_only_and_string_args() {
+    // Arrange
+    let mut config = ServerConfig {
+        read_only: true,
+        ..ServerConfig::default()
+    };
+    config.commands.register(HelloCustom);
+    let server = TestServer::spawn_with(config).await;
+    let mut client = server.connect().await;
+    let integer_arg = Frame::Array(vec![bulk("HELLO-CUSTOM"), Frame::Integer(1)]);
+
+    // Act
+    let write = client.cmd(&["HELLO-CUSTOM", "you"]).await;
+    client.connection.write_frame(&integer_arg).await.unwrap();
+    let malformed = client.read().await.unwrap();
+    let last_greeted = client.cmd(&["GET", "last-greeted"]).await;
+
+    // Assert
+    assert_eq!(
+        write,
+        Frame::Error("READONLY You can't write against a read only replica.".into())
+    );
+    assert_eq!(
+        malformed,
+        Frame::Error(
+            "ERR protocol error; expected simple frame or bulk frame, got Integer(1)".into()
+        )
+    );
+    assert_eq!(last_greeted, Frame::Null);
+}
+
+#[tokio::test]
+async fn set_and_expire_reject_unrepresentable_ttl() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    let huge = i64::MAX.to_string();
+
+    // Act
+    let set = client.cmd(&["SET", "key", "value", "EX", &huge]).await;
+    client.cmd(&["SET", "key", "value"]).await;
+    let expire = client.cmd(&["EXPIRE", "key", &huge]).await;
+    let get = client.cmd(&["GET", "key"]).await;
+    let ttl = client.cmd(&["TTL", "key"]).await;
+
+    // Assert
+    assert_eq!(
+        set,
+        Frame::Error("ERR invalid expire time in 'set' command".into())
+    );
+    assert_eq!(
+        expire,
+        Frame::Error("ERR invalid expire time in 'expire' command".into())
+    );
+    assert_eq!(get, bulk("value"));
+    assert_eq!(ttl, Frame::Integer(-1));
+}
+
+#[tokio::test]
+async fn getex_rejects_unrepresentable_ttl() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SET", "key", "value"]).await;
+
+    // Act
+    let getex = client
+        .cmd(&["GETEX", "key", "EX", &i64::MAX.to_string()])
+        .await;
+    let get = client.cmd(&["GET", "key"]).await;
+
+    // Assert
+    assert_eq!(
+        getex,
+        Frame::Error("ERR invalid expire time in 'getex' command".into())
+    );
+    assert_eq!(get, bulk("value"));
+}
+
+#[tokio::test]
+async fn random_member_rejects_huge_negative_count() {
+    // Arrange
+    let server = TestServer::spawn().await;
+    let mut client = server.connect().await;
+    client.cmd(&["SADD", "set", "a", "b"]).await;
+    client.cmd(&["HSET", "hash", "f", "v"]).await;
+
+    // Act
+    let srandmember = client.cmd(&["SRANDMEMBER", "set", "-99999999999999"]).await;
+    let hrandfield = client.cmd(&["HRANDFIELD", "hash", "-99999999999999"]).await;
+    let ping = client.cmd(&["PING"]).await;
+
+    // Assert
+    let out_of_range = Frame::Error("ERR value is out of range".into());
+    assert_eq!(srandmember, out_of_range);
+    assert_eq!(hrandfield, out_of_range);
+    assert_eq!(ping, Frame::Simple("PONG".into()));
+}